@@ -1,23 +1,69 @@
-use std::{
-    fmt::{self, Display},
-    path::PathBuf,
+use std::{io::IsTerminal, path::PathBuf, str::FromStr, time::Duration};
+
+use clap::{
+    builder::{PossibleValue, PossibleValuesParser, TypedValueParser},
+    *,
+};
+use libacmensa::{
+    day::SortKey,
+    meal::{Content, MealType, MeatFlags, Price},
+    mensa::Mensa,
+    records::BoolStyle,
 };
 
-use clap::*;
-use libacmensa::meal::MealType;
+/// Time zone used if neither `--utc` nor `--timezone` are given
+const DEFAULT_TIMEZONE: chrono_tz::Tz = chrono_tz::Europe::Berlin;
 
 /// Fetch, parse, and display/export menu data for Mensen of the Studierendenwerk Aachen.
-#[derive(Parser, Debug, Default)]
+#[derive(Parser, Debug)]
 #[clap(author, version)]
 pub struct Args {
     /// Target mensa.
-    #[arg(short, long, default_value = "ahornstrasse")]
+    #[arg(short, long, default_value = "ahornstrasse", value_parser = mensa_parser())]
     pub mensa: Mensa,
 
     /// Switch to English. Default is German.
     #[arg(short, long)]
     pub english: bool,
 
+    /// Hide meals of these categories in every output (comma separated).
+    #[arg(long, value_delimiter = ',', value_parser = parse_category)]
+    pub hide: Vec<MealType>,
+
+    /// Show categories hidden via --hide or the config file.
+    #[arg(long)]
+    pub show_hidden: bool,
+
+    /// Time zone used to determine "today" (e.g. Europe/London). Default is Europe/Berlin.
+    #[arg(long)]
+    pub timezone: Option<chrono_tz::Tz>,
+
+    /// Use UTC to determine "today".
+    #[arg(long, conflicts_with = "timezone")]
+    pub utc: bool,
+
+    /// Minutes a downloaded page is reused before it is fetched again.
+    #[arg(long, default_value_t = 60)]
+    pub cache_ttl: u64,
+
+    /// Do not read or write the page cache.
+    #[arg(long)]
+    pub no_cache: bool,
+
+    /// Fail on malformed rows or cells of a menu page instead of skipping them with a warning.
+    #[arg(long)]
+    pub strict: bool,
+
+    /// Pretty-print theme (TOML, see the `theme` subcommand). Default is `theme.toml` in the
+    /// config directory if it exists, the built-in theme otherwise.
+    #[arg(long)]
+    pub theme: Option<PathBuf>,
+
+    /// When to print colours. `auto` prints them if stdout is a terminal and `NO_COLOR` is not
+    /// set.
+    #[arg(long, default_value = "auto")]
+    pub color: ColorChoice,
+
     #[command(subcommand)]
     pub verb: Verb,
 }
@@ -26,14 +72,130 @@ pub struct Args {
 pub enum Verb {
     /// Fetches and displays the daily menu for a given mensa.
     Menu(MenuOpts),
-    /// Fetches and exports all available days for a given mensa in JSON.
+    /// Fetches and displays the daily menu of every mensa.
+    All(AllOpts),
+    /// Fetches this and next week of the selected mensen into the page cache.
+    Prefetch(PrefetchOpts),
+    /// Checks that the menu pages of the selected mensen are reachable (without parsing them).
+    Ping(PingOpts),
+    /// Renders the daily menu for a given mensa into a file (e.g. a PNG).
+    Render(RenderOpts),
+    /// Compares the prices of a dish across all mensen.
+    Compare(CompareOpts),
+    /// Finds the next day a dish is served at the selected mensa.
+    When(WhenOpts),
+    /// Finds the mensen serving a dish, on every published day or a single one.
+    Find(FindOpts),
+    /// Prints the pretty-print format (colours/emoji).
+    Theme(ThemeOpts),
+    /// Fetches and exports all available days for a given mensa in JSON or as an OpenMensa feed.
     #[cfg(feature = "json")]
     Export(ExportOpts),
-    /// Dumps the JSON schema for the exports.
+    /// Reports meals added, removed or changed since the previous diff (or an export).
+    #[cfg(feature = "json")]
+    Diff(DiffOpts),
+    /// Pushes a day's menu to ntfy, Gotify or a webhook (needs feature `notify`).
+    #[cfg(feature = "notify")]
+    Notify(NotifyOpts),
+    /// Posts a day's menu to a chat service (needs feature `notify`).
+    #[cfg(feature = "notify")]
+    Post(PostOpts),
+    /// Publishes the menu and opening state to MQTT, with Home Assistant discovery (needs
+    /// feature `mqtt`).
+    #[cfg(feature = "mqtt")]
+    Mqtt(MqttOpts),
+    /// Runs a Matrix bot answering `!mensa [mensa] [day]` (needs feature `matrix`).
+    #[cfg(feature = "matrix")]
+    Matrix(MatrixOpts),
+    /// Stores menus in a local history database and looks them up (needs feature `archive`).
+    #[cfg(feature = "archive")]
+    Archive(ArchiveOpts),
+    /// Prints statistics over the archived menus (needs feature `archive`).
+    #[cfg(feature = "archive")]
+    Stats(StatsOpts),
+    /// Browses the menus of every mensa interactively (needs feature `tui`).
+    #[cfg(feature = "tui")]
+    Tui(TuiOpts),
+    /// Dumps the JSON schema of the exports (see `--what`).
     #[cfg(feature = "json-schema")]
-    Schema,
-    /// Fetches and displays the opening times for a given mensa. (Not implemented yet)
-    Times,
+    Schema(SchemaOpts),
+    /// Fetches and displays the opening times for a given mensa.
+    Times(TimesOpts),
+    /// Lists the supported mensen with their addresses and locations.
+    Mensen(MensenOpts),
+    /// Looks for favorite dishes (`favorites` in the config file) on the menu.
+    Favorites(FavoritesOpts),
+    /// Manages the config file.
+    Config(ConfigOpts),
+}
+
+impl Args {
+    /// Time zone used for the date context (`--utc`/`--timezone`, Europe/Berlin otherwise)
+    pub fn tz(&self) -> chrono_tz::Tz {
+        match self.timezone {
+            _ if self.utc => chrono_tz::UTC,
+            Some(tz) => tz,
+            None => DEFAULT_TIMEZONE,
+        }
+    }
+
+    /// Time cached pages are reused (`None` with `--no-cache`). With `menu --watch`, at most
+    /// the watch interval, so that every refresh reaches the site.
+    pub fn cache_ttl(&self) -> Option<Duration> {
+        let ttl = Duration::from_secs(self.cache_ttl * 60);
+        let ttl = match self.verb {
+            Verb::Menu(ref opts) if opts.watch => ttl.min(opts.interval),
+            _ => ttl,
+        };
+        (!self.no_cache).then_some(ttl)
+    }
+
+    /// Categories to drop before printing/exporting. A category requested explicitly
+    /// (e.g. via `menu --only`) is never hidden.
+    pub fn hidden(&self, only: Option<&MealType>) -> Vec<MealType> {
+        if self.show_hidden {
+            return vec![];
+        }
+
+        self.hide
+            .iter()
+            .filter(|typ| Some(*typ) != only)
+            .cloned()
+            .collect()
+    }
+}
+
+impl MenuOpts {
+    /// Whether a whole week is printed instead of a single day
+    pub fn whole_week(&self) -> bool {
+        self.week.is_some() || self.next_week
+    }
+
+    /// Output format (`--json` is short for `--format json`, `--plain` for `--format plain`)
+    pub fn format(&self) -> MenuFormat {
+        #[cfg(feature = "json")]
+        if self.json {
+            return MenuFormat::Json;
+        }
+        if self.plain {
+            return MenuFormat::Plain;
+        }
+        self.format.clone()
+    }
+
+    /// Contents excluded by `--no-pork`, `--no-fish`, ...
+    pub fn excluded_contents(&self) -> MeatFlags {
+        [
+            (self.no_pork, Content::Pork),
+            (self.no_beef, Content::Beef),
+            (self.no_poultry, Content::Poultry),
+            (self.no_fish, Content::Fish),
+            (self.no_alcohol, Content::Alcohol),
+        ]
+        .into_iter()
+        .filter_map(|(excluded, content)| excluded.then_some(content))
+        .collect()
+    }
 }
 
 impl Default for Verb {
@@ -43,24 +205,38 @@ impl Default for Verb {
 }
 
 #[derive(Parser, Clone, Debug, Default, PartialEq, Eq)]
+#[command(group(ArgGroup::new("whole_week").args(["week", "next_week"]).multiple(true)))]
+#[command(group(ArgGroup::new("codes").args(["allergens", "additives"]).multiple(true)))]
 pub struct MenuOpts {
-    /// Print JSON of day plan.
+    /// Print JSON of day plan (same as --format json).
     #[cfg(feature = "json")]
-    #[arg(short, long)]
+    #[arg(short, long, conflicts_with_all = ["format", "plain"])]
     pub json: bool,
 
-    /// ISO Date (YYYY-MM-DD). Takes precedence over --day/-d.
-    #[arg(long)]
+    /// Date (YYYY-MM-DD, DD.MM.YYYY or DD.MM. for the current year). Takes precedence over
+    /// --day/-d.
+    #[arg(long, value_parser = parse_date)]
     pub date: Option<chrono::NaiveDate>,
 
-    /// Day description.
-    #[arg(short, long, default_value = "today")]
+    /// Day: today, next (tomorrow, or Monday on weekends), tomorrow, a weekday (e.g. mon,
+    /// friday) or an offset in days (e.g. +2).
+    #[arg(short, long, default_value = "today", allow_hyphen_values = true)]
     pub day: MenuDate,
 
+    /// On a weekend or outside of the published weeks, show the nearest day with a menu
+    /// instead of failing.
+    #[arg(long)]
+    pub nearest: bool,
+
     /// Only print meals of the given category.
-    #[arg(short, long)]
+    #[arg(short, long, value_parser = parse_category)]
     pub only: Option<MealType>,
 
+    /// Only print the meals whose description mentions this (case-insensitive, in German or
+    /// English). Sides are left out, and so are days without a match of a whole week.
+    #[arg(long, value_name = "TEXT")]
+    pub grep: Option<String>,
+
     /// Switch to English. Default is German.
     #[arg(short, long)]
     pub english: bool,
@@ -69,10 +245,32 @@ pub struct MenuOpts {
     #[arg(short, long)]
     pub short: bool,
 
+    /// Append the secondary description (sauces etc.) to the headline in parentheses.
+    #[arg(long)]
+    pub inline_subtext: bool,
+
     /// Print meal prices.
     #[arg(short, long)]
     pub prices: bool,
 
+    /// Print prices right-aligned in a column next to the dish names.
+    #[arg(long, requires = "prices")]
+    pub aligned: bool,
+
+    /// Print prices parsed and reformatted (e.g. "2,50 €") instead of as found on the page.
+    #[arg(long, requires = "prices")]
+    pub normalized_prices: bool,
+
+    /// Only print meals costing at most this much (in euros, e.g. 3.50; the student price).
+    /// Meals without a price are left out.
+    #[arg(long, value_name = "EUR", value_parser = parse_cents)]
+    pub max_price: Option<i64>,
+
+    /// Order of the meals (price: cheapest first, category, name). Defaults to the order on
+    /// the page.
+    #[arg(long, value_name = "KEY")]
+    pub sort: Option<SortKey>,
+
     /// Do not print sides.
     #[arg(short = 'm', long)]
     pub skip_sides: bool,
@@ -81,9 +279,273 @@ pub struct MenuOpts {
     #[arg(short = 'n', long)]
     pub skip_vegan: bool,
 
+    /// Only print special offers (burger of the week, pizza of the day).
+    #[arg(long)]
+    pub specials: bool,
+
+    /// Do not print special offers.
+    #[arg(long, conflicts_with = "specials")]
+    pub no_specials: bool,
+
     /// Print allergens. (No guarantee that they are parsed correctly!)
     #[arg(short, long)]
     pub allergens: bool,
+
+    /// Print the names of the allergens and additives (e.g. "Gluten (Weizen)") instead of their
+    /// codes.
+    #[arg(long, requires = "codes")]
+    pub allergen_names: bool,
+
+    /// Do not print meals and sides containing any of these allergens (comma separated codes, e.g. A,C,L).
+    #[arg(long, value_delimiter = ',')]
+    pub exclude_allergens: Vec<String>,
+
+    /// Print additives (Zusatzstoffe, e.g. "2" for preservatives).
+    #[arg(long)]
+    pub additives: bool,
+
+    /// Do not print meals and sides containing any of these additives (comma separated codes, e.g. 2,9).
+    #[arg(long, value_delimiter = ',')]
+    pub exclude_additives: Vec<String>,
+
+    /// Do not print meals containing pork. (Like all of the following, guessed from the
+    /// description and icons.)
+    #[arg(long)]
+    pub no_pork: bool,
+
+    /// Do not print meals containing beef.
+    #[arg(long)]
+    pub no_beef: bool,
+
+    /// Do not print meals containing poultry.
+    #[arg(long)]
+    pub no_poultry: bool,
+
+    /// Do not print meals containing fish or seafood.
+    #[arg(long)]
+    pub no_fish: bool,
+
+    /// Do not print meals containing alcohol.
+    #[arg(long)]
+    pub no_alcohol: bool,
+
+    /// Print the whole week containing the requested day, or the given ISO calendar week
+    /// (e.g. 42; only this and next week are available). A week number takes precedence over
+    /// --date/--day and --next-week.
+    #[arg(short, long, num_args = 0..=1, value_name = "ISO_WEEK", value_parser = clap::value_parser!(u32).range(1..=53))]
+    pub week: Option<Option<u32>>,
+
+    /// Print the whole next week.
+    #[arg(long, conflicts_with_all = ["date", "day"])]
+    pub next_week: bool,
+
+    /// Fetch every mensa and print their menus one after another. With --json, an array of
+    /// the days of all mensen (labelled with the mensa).
+    #[arg(long)]
+    pub all_mensen: bool,
+
+    /// Iterate the week from Friday to Monday. Only useful with --week/--next-week.
+    #[arg(long, requires = "whole_week")]
+    pub reverse_days: bool,
+
+    /// Explain how the requested day was resolved (on stderr) before printing it.
+    #[arg(long)]
+    pub explain: bool,
+
+    /// Do not access the network: read the menu from the files in --data-dir, or from the
+    /// page cache (even if it is outdated).
+    #[arg(long)]
+    pub offline: bool,
+
+    /// Read the menu from a saved menu page of the mensa (`-` for stdin) instead of
+    /// fetching it. The page is taken as the week of the requested day.
+    #[arg(long, value_name = "PAGE", conflicts_with_all = ["offline", "all_mensen"])]
+    pub from_file: Option<PathBuf>,
+
+    /// Directory with files written by `export` (day files, --combined or json-lines, also
+    /// in the per-mensa subdirectories of --all-mensen), read with --offline.
+    #[cfg(feature = "json")]
+    #[arg(long, requires = "offline")]
+    pub data_dir: Option<PathBuf>,
+
+    /// Only print dishes that are new since the given point.
+    #[cfg(feature = "json")]
+    #[arg(long)]
+    pub since: Option<Since>,
+
+    /// Print a screen-reader friendly rendering without emojis, colours or layout
+    /// characters (same as --format plain).
+    #[arg(long, conflicts_with = "format")]
+    pub plain: bool,
+
+    /// Output format.
+    #[arg(long, default_value = "pretty")]
+    pub format: MenuFormat,
+
+    /// How booleans are written with --format csv/tsv (yesno, truefalse, 01).
+    #[arg(long, default_value = "truefalse")]
+    pub bool_style: BoolStyle,
+
+    /// Also write the printed menu without colours to this file.
+    #[arg(long)]
+    pub tee: Option<PathBuf>,
+
+    /// Keep running: fetch the menu again every --interval, redraw the screen and mark the
+    /// dishes that were added, changed or removed since the previous fetch. Shows the nearest
+    /// day with a menu (see --nearest). Only for the pretty format.
+    #[arg(long, conflicts_with_all = ["offline", "from_file", "tee"])]
+    pub watch: bool,
+
+    /// Time between two fetches with --watch (e.g. 30m, 2h or 90s; plain numbers are minutes).
+    #[arg(long, default_value = "30m", value_parser = parse_interval, requires = "watch")]
+    pub interval: Duration,
+}
+
+#[derive(Parser, Clone, Debug, Default, PartialEq, Eq)]
+pub struct AllOpts {
+    /// Print a JSON object mapping each mensa to its day plan.
+    #[cfg(feature = "json")]
+    #[arg(short, long)]
+    pub json: bool,
+
+    /// Date (YYYY-MM-DD, DD.MM.YYYY or DD.MM. for the current year). Takes precedence over
+    /// --day/-d.
+    #[arg(long, value_parser = parse_date)]
+    pub date: Option<chrono::NaiveDate>,
+
+    /// Day: today, next (tomorrow, or Monday on weekends), tomorrow, a weekday (e.g. mon,
+    /// friday) or an offset in days (e.g. +2).
+    #[arg(short, long, default_value = "today", allow_hyphen_values = true)]
+    pub day: MenuDate,
+
+    /// On a weekend or outside of the published weeks, show the nearest day with a menu
+    /// instead of failing.
+    #[arg(long)]
+    pub nearest: bool,
+
+    /// Also write the printed menus without colours to this file.
+    #[arg(long)]
+    pub tee: Option<PathBuf>,
+}
+
+#[derive(Parser, Clone, Debug, PartialEq, Eq)]
+pub struct PrefetchOpts {
+    /// Mensa to prefetch, or "all".
+    #[arg(short, long, default_value = "all")]
+    pub mensa: MensaSelection,
+}
+
+#[derive(Parser, Clone, Debug, PartialEq, Eq)]
+pub struct PingOpts {
+    /// Mensa to check, or "all".
+    #[arg(short, long, default_value = "all")]
+    pub mensa: MensaSelection,
+
+    /// Timeout per request in seconds.
+    #[arg(short, long, default_value_t = 5)]
+    pub timeout: u64,
+}
+
+#[derive(Parser, Clone, Debug, Default, PartialEq, Eq)]
+pub struct RenderOpts {
+    /// Date (YYYY-MM-DD, DD.MM.YYYY or DD.MM. for the current year). Takes precedence over
+    /// --day/-d.
+    #[arg(long, value_parser = parse_date)]
+    pub date: Option<chrono::NaiveDate>,
+
+    /// Day: today, next (tomorrow, or Monday on weekends), tomorrow, a weekday (e.g. mon,
+    /// friday) or an offset in days (e.g. +2).
+    #[arg(short, long, default_value = "today", allow_hyphen_values = true)]
+    pub day: MenuDate,
+
+    /// Output format.
+    #[arg(short, long, default_value = "text")]
+    pub format: RenderFormat,
+
+    /// Output file. Text goes to stdout if not given, images to `menu.png`.
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+
+    /// Image width in pixels.
+    #[cfg(feature = "image-export")]
+    #[arg(long, default_value_t = 800)]
+    pub width: u32,
+
+    /// Image background colour (#rrggbb).
+    #[cfg(feature = "image-export")]
+    #[arg(long, default_value = "#1c1c1c")]
+    pub background: String,
+
+    /// Font (TTF/OTF) for images. Emoji are only drawn if the font has glyphs for them.
+    #[cfg(feature = "image-export")]
+    #[arg(long)]
+    pub font: Option<PathBuf>,
+}
+
+#[derive(ValueEnum, Clone, Debug, Default, PartialEq, Eq)]
+pub enum RenderFormat {
+    /// Plain text without colours.
+    #[default]
+    Text,
+    /// PNG image (needs feature `image-export`).
+    #[cfg(feature = "image-export")]
+    Png,
+}
+
+#[derive(Parser, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CompareOpts {
+    /// Dish to look for (case-insensitive, part of the name is enough).
+    #[arg(short, long)]
+    pub dish: String,
+
+    /// Compare next week instead of this week.
+    #[arg(short, long)]
+    pub next_week: bool,
+}
+
+#[derive(Parser, Clone, Debug, Default, PartialEq, Eq)]
+pub struct WhenOpts {
+    /// Dish to look for (case-insensitive, part of the name is enough).
+    pub dish: String,
+}
+
+#[derive(Parser, Clone, Debug, Default, PartialEq, Eq)]
+pub struct FindOpts {
+    /// Dish to look for (case-insensitive, part of the description or its subtext is enough).
+    pub dish: String,
+
+    /// Only look at this day: today, next (tomorrow, or Monday on weekends), tomorrow, a
+    /// weekday (e.g. mon, friday) or an offset in days (e.g. +2). Default is every day from
+    /// today on.
+    #[arg(short, long, allow_hyphen_values = true)]
+    pub day: Option<MenuDate>,
+
+    /// Only look at this date (YYYY-MM-DD, DD.MM.YYYY or DD.MM. for the current year).
+    #[arg(long, value_parser = parse_date, conflicts_with = "day")]
+    pub date: Option<chrono::NaiveDate>,
+
+    /// Print JSON of the matches.
+    #[cfg(feature = "json")]
+    #[arg(short, long)]
+    pub json: bool,
+}
+
+#[derive(Parser, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TuiOpts {
+    /// Start with the allergens shown (toggled with `a`).
+    #[arg(short, long)]
+    pub allergens: bool,
+
+    /// Start with the prices shown (toggled with `p`).
+    #[arg(short, long)]
+    pub prices: bool,
+}
+
+#[derive(Parser, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ThemeOpts {
+    /// Print a sample day with placeholder meals instead of the format definition.
+    #[arg(short, long)]
+    pub preview: bool,
 }
 
 #[derive(Parser, Clone, Debug, Default, PartialEq, Eq)]
@@ -91,50 +553,584 @@ pub struct ExportOpts {
     /// Put files in this directory.
     #[arg(short, long)]
     pub output: Option<PathBuf>,
+
+    /// Write a single `<mensa>_fortnight.json` holding both weeks instead of one file per day.
+    #[arg(short, long)]
+    pub combined: bool,
+
+    /// Output format.
+    #[arg(short, long, default_value = "json")]
+    pub format: ExportFormat,
+
+    /// Export every mensa into its own subdirectory (`<output>/<mensa>/`).
+    #[arg(long)]
+    pub all_mensen: bool,
+
+    /// Only export the given ISO calendar week (e.g. 42; only this and next week are
+    /// available). Default is both weeks.
+    #[arg(short, long, value_name = "ISO_WEEK", value_parser = clap::value_parser!(u32).range(1..=53))]
+    pub week: Option<u32>,
+
+    /// Only export next week.
+    #[arg(long, conflicts_with = "week")]
+    pub next_week: bool,
+
+    /// How booleans are written in CSV/TSV (yesno, truefalse, 01).
+    #[arg(long, alias = "bools", default_value = "truefalse")]
+    pub bool_style: BoolStyle,
 }
 
-#[allow(clippy::upper_case_acronyms)]
 #[derive(ValueEnum, Clone, Debug, Default, PartialEq, Eq)]
-pub enum Mensa {
-    Academica,
+pub enum ExportFormat {
+    /// JSON files (see `--combined`).
     #[default]
-    Ahornstrasse,
-    BistroTemplergraben,
-    Bayernallee,
-    EupenerStrasse,
-    KMAC,
-    Suedpark,
-    Vita,
-    Juelich,
-}
-
-impl Display for Mensa {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                Mensa::Academica => "academica",
-                Mensa::Ahornstrasse => "ahornstrasse",
-                Mensa::BistroTemplergraben => "bistro_templergraben",
-                Mensa::Bayernallee => "bayernallee",
-                Mensa::EupenerStrasse => "eupener_strasse",
-                Mensa::KMAC => "kmac",
-                Mensa::Suedpark => "suedpark",
-                Mensa::Vita => "vita",
-                Mensa::Juelich => "juelich",
-            }
+    Json,
+    /// A single OpenMensa feed (`<mensa>.xml`) holding both weeks (needs feature `openmensa`).
+    #[cfg(feature = "openmensa")]
+    Openmensa,
+    /// A single `<mensa>.jsonl` with one JSON object per line and day.
+    #[cfg(feature = "json")]
+    JsonLines,
+    /// A single `<mensa>.yaml` holding all days (needs feature `yaml`).
+    #[cfg(feature = "yaml")]
+    Yaml,
+    /// A single `<mensa>.csv` holding one row per dish of both weeks.
+    Csv,
+    /// Like csv, but tab separated (`<mensa>.tsv`).
+    Tsv,
+    /// A single `<mensa>.md` with a table per day.
+    Markdown,
+    /// An RSS feed (`<mensa>.rss`) with an item per day, the menu as HTML.
+    Rss,
+    /// A static website: a page per week and an `index.html` (needs feature `site`).
+    #[cfg(feature = "site")]
+    Html,
+}
+
+#[cfg(feature = "json-schema")]
+#[derive(Parser, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SchemaOpts {
+    /// Document to dump the schema of: a day (export files, `menu --json`), a week (the
+    /// `weeks` of `export --combined`) or the opening times (`times --json`).
+    #[arg(short, long, default_value = "day")]
+    pub what: SchemaKind,
+}
+
+#[cfg(feature = "json-schema")]
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SchemaKind {
+    #[default]
+    Day,
+    Week,
+    Times,
+}
+
+#[derive(Parser, Clone, Debug, Default, PartialEq, Eq)]
+pub struct TimesOpts {
+    /// Print JSON of the opening times.
+    #[cfg(feature = "json")]
+    #[arg(short, long)]
+    pub json: bool,
+}
+
+#[derive(Parser, Clone, Debug, PartialEq, Eq)]
+pub struct ConfigOpts {
+    #[command(subcommand)]
+    pub action: ConfigAction,
+}
+
+#[derive(Subcommand, Clone, Debug, PartialEq, Eq)]
+pub enum ConfigAction {
+    /// Writes a commented config template.
+    Init {
+        /// Overwrite an existing config file.
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Prints the path of the config file.
+    Path,
+}
+
+#[cfg(feature = "json")]
+#[derive(Parser, Clone, Debug, Default, PartialEq, Eq)]
+pub struct DiffOpts {
+    /// Compare against an export (a directory of day files, a `--combined` file or JSON
+    /// lines) instead of the days as fetched by the previous diff.
+    #[arg(long)]
+    pub against: Option<PathBuf>,
+
+    /// Print JSON of the changed days.
+    #[arg(short, long)]
+    pub json: bool,
+}
+
+#[cfg(feature = "notify")]
+#[derive(Parser, Clone, Debug, Default, PartialEq, Eq)]
+pub struct NotifyOpts {
+    /// Date (YYYY-MM-DD, DD.MM.YYYY or DD.MM. for the current year). Takes precedence over
+    /// --day/-d.
+    #[arg(long, value_parser = parse_date)]
+    pub date: Option<chrono::NaiveDate>,
+
+    /// Day: today, next (tomorrow, or Monday on weekends), tomorrow, a weekday (e.g. mon,
+    /// friday) or an offset in days (e.g. +2).
+    #[arg(short, long, default_value = "today", allow_hyphen_values = true)]
+    pub day: MenuDate,
+
+    /// Where to send the notification (ntfy topic, Gotify `/message` endpoint or webhook).
+    #[arg(long)]
+    pub url: Option<String>,
+
+    /// Kind of the endpoint.
+    #[arg(long, default_value = "ntfy")]
+    pub service: NotifyService,
+
+    /// Access token (ntfy/post: bearer token, Gotify: application token).
+    #[arg(long)]
+    pub token: Option<String>,
+
+    /// Title template. Placeholders: {mensa}, {date}, {weekday}, {count}, {meals}, {matches}.
+    #[arg(long, default_value = crate::notify::DEFAULT_TITLE)]
+    pub title: String,
+
+    /// Body template (same placeholders as --title, `\n` for line breaks).
+    #[arg(long, default_value = crate::notify::DEFAULT_BODY)]
+    pub body: String,
+
+    /// Only notify if a main meal contains one of these keywords (comma separated).
+    #[arg(long, value_delimiter = ',')]
+    pub keyword: Vec<String>,
+
+    /// Only notify if a main meal of one of these categories is served (comma separated).
+    #[arg(long, value_delimiter = ',', value_parser = parse_category)]
+    pub category: Vec<MealType>,
+
+    /// Include meal prices.
+    #[arg(short, long)]
+    pub prices: bool,
+
+    /// Print the notification instead of sending it.
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[cfg(feature = "notify")]
+#[derive(ValueEnum, Clone, Debug, Default, PartialEq, Eq)]
+pub enum NotifyService {
+    /// ntfy.sh or a self-hosted ntfy server.
+    #[default]
+    Ntfy,
+    /// Gotify server.
+    Gotify,
+    /// Generic HTTP POST of a JSON object (title, message and the day).
+    Post,
+}
+
+#[cfg(feature = "notify")]
+#[derive(Parser, Clone, Debug, PartialEq, Eq)]
+pub struct PostOpts {
+    #[command(subcommand)]
+    pub target: PostTarget,
+}
+
+#[cfg(feature = "notify")]
+#[derive(Subcommand, Clone, Debug, PartialEq, Eq)]
+pub enum PostTarget {
+    /// Posts the menu as Discord embeds (a coloured embed per category) via a webhook.
+    Discord(DiscordOpts),
+}
+
+#[cfg(feature = "notify")]
+#[derive(Parser, Clone, Debug, Default, PartialEq, Eq)]
+pub struct DiscordOpts {
+    /// Date (YYYY-MM-DD, DD.MM.YYYY or DD.MM. for the current year). Takes precedence over
+    /// --day/-d.
+    #[arg(long, value_parser = parse_date)]
+    pub date: Option<chrono::NaiveDate>,
+
+    /// Day: today, next (tomorrow, or Monday on weekends), tomorrow, a weekday (e.g. mon,
+    /// friday) or an offset in days (e.g. +2).
+    #[arg(short, long, default_value = "today", allow_hyphen_values = true)]
+    pub day: MenuDate,
+
+    /// Webhook to post to (Server Settings → Integrations → Webhooks).
+    #[arg(long)]
+    pub webhook_url: Option<String>,
+
+    /// Name the message is posted under. Default is the name of the webhook.
+    #[arg(long)]
+    pub username: Option<String>,
+
+    /// Include meal prices.
+    #[arg(short, long)]
+    pub prices: bool,
+
+    /// Do not post sides.
+    #[arg(long)]
+    pub skip_sides: bool,
+
+    /// Print the message (JSON) instead of posting it.
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[cfg(feature = "mqtt")]
+#[derive(Parser, Clone, Debug, PartialEq, Eq)]
+pub struct MqttOpts {
+    /// Broker host.
+    #[arg(long, default_value = "localhost")]
+    pub host: String,
+
+    /// Broker port.
+    #[arg(long, default_value_t = 1883)]
+    pub port: u16,
+
+    /// Username for the broker.
+    #[arg(long)]
+    pub username: Option<String>,
+
+    /// Password for the broker.
+    #[arg(long)]
+    pub password: Option<String>,
+
+    /// Client id used for the connection.
+    #[arg(long, default_value = "acmensa-cli")]
+    pub client_id: String,
+
+    /// Prefix of the topics (`<prefix>/<mensa>/menu` and `<prefix>/<mensa>/open`).
+    #[arg(long, default_value = "acmensa")]
+    pub prefix: String,
+
+    /// Prefix of the Home Assistant discovery topics.
+    #[arg(long, default_value = "homeassistant")]
+    pub discovery_prefix: String,
+
+    /// Do not publish Home Assistant discovery messages.
+    #[arg(long)]
+    pub no_discovery: bool,
+
+    /// Publish every mensa instead of the selected one.
+    #[arg(long)]
+    pub all_mensen: bool,
+
+    /// Keep running and publish again every INTERVAL minutes (e.g. to keep `open` current).
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub interval: Option<u64>,
+}
+
+#[cfg(feature = "matrix")]
+#[derive(Parser, Clone, Debug, Default, PartialEq, Eq)]
+pub struct MatrixOpts {
+    /// Homeserver of the bot account (e.g. https://matrix.org).
+    #[arg(long)]
+    pub homeserver: Option<String>,
+
+    /// Access token of the bot account.
+    #[arg(long)]
+    pub token: Option<String>,
+
+    /// Rooms to join (ids or aliases, comma separated).
+    #[arg(long, value_delimiter = ',')]
+    pub room: Vec<String>,
+
+    /// Post the menu of the selected mensa to every room at this time on weekdays (HH:MM).
+    #[arg(long)]
+    pub post_at: Option<chrono::NaiveTime>,
+
+    /// Include meal prices.
+    #[arg(short, long)]
+    pub prices: bool,
+}
+
+#[cfg(feature = "archive")]
+#[derive(Parser, Clone, Debug, PartialEq, Eq)]
+pub struct ArchiveOpts {
+    /// Archive database. Default is `$XDG_DATA_HOME/acmensa/archive.sqlite`.
+    #[arg(long, global = true)]
+    pub db: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub action: ArchiveAction,
+}
+
+#[cfg(feature = "archive")]
+#[derive(Parser, Clone, Debug, PartialEq, Eq)]
+pub struct StatsOpts {
+    /// Archive database. Default is `$XDG_DATA_HOME/acmensa/archive.sqlite`.
+    #[arg(long)]
+    pub db: Option<PathBuf>,
+
+    /// First day (YYYY-MM-DD, DD.MM.YYYY or DD.MM.).
+    #[arg(long, value_parser = parse_date)]
+    pub from: Option<chrono::NaiveDate>,
+
+    /// Last day (YYYY-MM-DD, DD.MM.YYYY or DD.MM.).
+    #[arg(long, value_parser = parse_date)]
+    pub to: Option<chrono::NaiveDate>,
+
+    /// Over every mensa instead of the selected one.
+    #[arg(long)]
+    pub all_mensen: bool,
+
+    /// Number of most frequent meals to print.
+    #[arg(long, default_value_t = 10)]
+    pub top: usize,
+
+    /// Print JSON of the statistics.
+    #[cfg(feature = "json")]
+    #[arg(short, long)]
+    pub json: bool,
+}
+
+#[cfg(feature = "archive")]
+#[derive(Subcommand, Clone, Debug, PartialEq, Eq)]
+pub enum ArchiveAction {
+    /// Fetches this and next week of the selected mensa and stores them in the archive.
+    Sync {
+        /// Sync every mensa.
+        #[arg(long)]
+        all_mensen: bool,
+    },
+    /// Prints archived days of the selected mensa.
+    Query {
+        /// First day (YYYY-MM-DD, DD.MM.YYYY or DD.MM.).
+        #[arg(long, value_parser = parse_date)]
+        from: Option<chrono::NaiveDate>,
+
+        /// Last day (YYYY-MM-DD, DD.MM.YYYY or DD.MM.).
+        #[arg(long, value_parser = parse_date)]
+        to: Option<chrono::NaiveDate>,
+
+        /// Only print main meals containing this text (case-insensitive).
+        #[arg(long)]
+        text: Option<String>,
+
+        /// Query every mensa.
+        #[arg(long)]
+        all_mensen: bool,
+
+        /// Print meal prices.
+        #[arg(short, long)]
+        prices: bool,
+
+        /// Output format.
+        #[arg(long, default_value = "pretty")]
+        format: MenuFormat,
+
+        /// How booleans are written with --format csv/tsv (yesno, truefalse, 01).
+        #[arg(long, default_value = "truefalse")]
+        bool_style: BoolStyle,
+    },
+}
+
+#[derive(Parser, Clone, Debug, PartialEq, Eq)]
+pub struct FavoritesOpts {
+    #[command(subcommand)]
+    pub action: FavoritesAction,
+}
+
+#[derive(Subcommand, Clone, Debug, PartialEq, Eq)]
+pub enum FavoritesAction {
+    /// Prints the favorites from the config file.
+    List,
+    /// Prints the favorites served between --from and --to. Exits with status 3 if any
+    /// favorite is on the menu, e.g. to trigger an alert from cron.
+    Check {
+        /// First day (YYYY-MM-DD, DD.MM.YYYY or DD.MM.). Default is today.
+        #[arg(long, value_parser = parse_date)]
+        from: Option<chrono::NaiveDate>,
+
+        /// Last day (YYYY-MM-DD, DD.MM.YYYY or DD.MM.). Default is the last day with a
+        /// published menu.
+        #[arg(long, value_parser = parse_date)]
+        to: Option<chrono::NaiveDate>,
+
+        /// Keywords to look for (comma separated). Default is `favorites` from the config file.
+        #[arg(long, value_delimiter = ',')]
+        keyword: Vec<String>,
+
+        /// Look at every mensa instead of the selected one.
+        #[arg(long)]
+        all_mensen: bool,
+
+        /// Print JSON of the matches.
+        #[cfg(feature = "json")]
+        #[arg(short, long)]
+        json: bool,
+    },
+}
+
+#[derive(Parser, Clone, Debug, Default, PartialEq, Eq)]
+pub struct MensenOpts {
+    /// Print JSON of the mensen.
+    #[cfg(feature = "json")]
+    #[arg(short, long)]
+    pub json: bool,
+}
+
+/// Parser for `--mensa`. The slugs are offered with "-" instead of "_" (e.g. "eupener-strasse").
+fn mensa_parser() -> impl TypedValueParser<Value = Mensa> {
+    PossibleValuesParser::new(
+        Mensa::all()
+            .iter()
+            .map(|m| PossibleValue::new(m.slug().replace('_', "-")).alias(m.slug())),
+    )
+    .map(|s| Mensa::from_slug(&s).unwrap())
+}
+
+/// Parser for categories (`--hide`, `--only`, ...): the exact name of a category, see
+/// `MealType::from_name`
+pub fn parse_category(s: &str) -> Result<MealType, String> {
+    MealType::from_name(s).ok_or_else(|| {
+        let names = MealType::ALL
+            .iter()
+            .map(|typ| format!("{typ:?}"))
+            .collect::<Vec<_>>();
+        format!(
+            "unknown category \"{s}\" (expected one of {})",
+            names.join(", ")
         )
+    })
+}
+
+/// Parser for dates: ISO (2026-10-16), German (16.10.2026) or German without the year
+/// (16.10., the current year in Europe/Berlin; the parser runs before `--timezone` is known)
+pub fn parse_date(s: &str) -> Result<chrono::NaiveDate, String> {
+    use chrono::{Datelike, NaiveDate};
+
+    let s = s.trim();
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(s, "%d.%m.%Y"))
+        .or_else(|_| {
+            let day_month = s.strip_suffix('.').unwrap_or(s);
+            let year = chrono::Utc::now().with_timezone(&DEFAULT_TIMEZONE).year();
+            NaiveDate::parse_from_str(&format!("{day_month}.{year}"), "%d.%m.%Y")
+        })
+        .map_err(|_| format!("invalid date \"{s}\" (expected YYYY-MM-DD, DD.MM.YYYY or DD.MM.)"))
+}
+
+/// Parser for prices in euros ("3.50", "3,50 €"), in cents
+pub fn parse_cents(s: &str) -> Result<i64, String> {
+    Price::parse(s)
+        .map(|price| price.cents)
+        .ok_or_else(|| format!("invalid price \"{s}\" (expected e.g. 3.50)"))
+}
+
+/// Parses an interval like `30m`, `2h` or `90s` (plain numbers are minutes)
+pub fn parse_interval(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let (number, unit) = s.split_at(s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len()));
+    let unit = match unit.trim() {
+        "s" => 1,
+        "" | "m" | "min" => 60,
+        "h" => 60 * 60,
+        _ => return Err(format!("invalid interval \"{s}\" (expected e.g. 30m)")),
+    };
+    match number.parse::<u64>() {
+        Ok(0) => Err("the interval must not be zero".to_string()),
+        Ok(number) => Ok(Duration::from_secs(number * unit)),
+        Err(_) => Err(format!("invalid interval \"{s}\" (expected e.g. 30m)")),
+    }
+}
+
+/// A single mensa or all of them
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MensaSelection {
+    All,
+    One(Mensa),
+}
+
+impl FromStr for MensaSelection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("all") {
+            Ok(Self::All)
+        } else {
+            Mensa::from_str(s).map(Self::One).map_err(|e| e.to_string())
+        }
     }
 }
 
-impl Mensa {
-    pub fn url_name(&self) -> String {
-        self.to_string()
+impl MensaSelection {
+    /// The selected mensen
+    pub fn mensen(&self) -> Vec<Mensa> {
+        match self {
+            Self::All => Mensa::all().to_vec(),
+            Self::One(mensa) => vec![*mensa],
+        }
     }
 }
 
+#[derive(ValueEnum, Clone, Debug, PartialEq, Eq)]
+pub enum Since {
+    /// The previous time `menu` fetched the same day (same mensa and language)
+    LastFetch,
+}
+
 #[derive(ValueEnum, Default, Clone, Debug, PartialEq, Eq)]
+pub enum MenuFormat {
+    /// Coloured output for the terminal.
+    #[default]
+    Pretty,
+    /// Labelled sentences for screen readers, without emojis, colours or layout characters.
+    Plain,
+    /// JSON of the day plan. With --week/--next-week or --all-mensen, an array of the days.
+    #[cfg(feature = "json")]
+    Json,
+    /// One JSON object per line and day.
+    #[cfg(feature = "json")]
+    JsonLines,
+    /// Like json, but YAML (needs feature `yaml`).
+    #[cfg(feature = "yaml")]
+    Yaml,
+    /// One row per dish, comma separated.
+    Csv,
+    /// One row per dish, tab separated.
+    Tsv,
+    /// GitHub-flavored Markdown (a table per day).
+    Markdown,
+    /// HTML fragment (a list per day, coloured as in the theme).
+    Html,
+    /// Single-line JSON object (text, tooltip, class) for waybar/i3status-rs custom modules.
+    /// Only for a single day of a single mensa (needs feature `json`).
+    #[cfg(feature = "json")]
+    Waybar,
+}
+
+/// When to print colours (see `--color`)
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// If stdout is a terminal and `NO_COLOR` is not set.
+    #[default]
+    Auto,
+    /// Always, even if piped.
+    Always,
+    /// Never.
+    Never,
+}
+
+impl ColorChoice {
+    /// Whether colours are printed (see <https://no-color.org> for `NO_COLOR`)
+    pub fn enabled(&self) -> bool {
+        self.enabled_for(
+            std::env::var_os("NO_COLOR").as_deref(),
+            std::io::stdout().is_terminal(),
+        )
+    }
+
+    /// `enabled` for the given value of `NO_COLOR` and whether stdout is a terminal
+    fn enabled_for(&self, no_color: Option<&std::ffi::OsStr>, terminal: bool) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => no_color.is_none_or(|value| value.is_empty()) && terminal,
+        }
+    }
+}
+
+/// Day relative to today (see `--day`)
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
 pub enum MenuDate {
     /// The day of today
     #[default]
@@ -142,4 +1138,115 @@ pub enum MenuDate {
 
     /// Next day (tomorrow, or Monday if it's a weekend)
     Next,
+
+    /// The day after today
+    Tomorrow,
+
+    /// The next such weekday, today included
+    Weekday(chrono::Weekday),
+
+    /// Today plus (or minus) a number of days
+    Offset(i32),
+}
+
+impl MenuDate {
+    /// Date the day refers to, relative to `today`
+    pub fn date(&self, today: chrono::NaiveDate) -> chrono::NaiveDate {
+        use chrono::{Datelike, Days};
+
+        match self {
+            MenuDate::Today => today,
+            MenuDate::Next => today
+                .iter_days()
+                .skip(1)
+                .find(|day| day.weekday().number_from_monday() <= 5)
+                .unwrap_or(today),
+            MenuDate::Tomorrow => today + Days::new(1),
+            MenuDate::Weekday(weekday) => {
+                let ahead = (7 + weekday.num_days_from_monday()
+                    - today.weekday().num_days_from_monday())
+                    % 7;
+                today + Days::new(ahead as u64)
+            }
+            MenuDate::Offset(days) => today
+                .checked_add_signed(chrono::Duration::days(*days as i64))
+                .unwrap_or(if *days > 0 {
+                    chrono::NaiveDate::MAX
+                } else {
+                    chrono::NaiveDate::MIN
+                }),
+        }
+    }
+}
+
+impl FromStr for MenuDate {
+    type Err = String;
+
+    /// today/heute, next, tomorrow/morgen, a (possibly abbreviated, German or English)
+    /// weekday or an offset in days like "+2"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let day = s.trim().to_lowercase();
+        Ok(match day.as_str() {
+            "today" | "heute" => MenuDate::Today,
+            "next" => MenuDate::Next,
+            "tomorrow" | "morgen" => MenuDate::Tomorrow,
+            _ if day.starts_with(['+', '-']) => MenuDate::Offset(
+                day.parse()
+                    .map_err(|_| format!("invalid offset in days \"{s}\""))?,
+            ),
+            _ => MenuDate::Weekday(crate::pretty_print::parse_weekday(&day).ok_or_else(|| {
+                format!(
+                    "unknown day \"{s}\" (expected today, next, tomorrow, a weekday or an \
+                     offset like +2)"
+                )
+            })?),
+        })
+    }
+}
+
+impl std::fmt::Display for MenuDate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MenuDate::Today => f.write_str("today"),
+            MenuDate::Next => f.write_str("next"),
+            MenuDate::Tomorrow => f.write_str("tomorrow"),
+            MenuDate::Weekday(weekday) => {
+                f.write_str(&crate::pretty_print::weekday_name(*weekday, true).to_lowercase())
+            }
+            MenuDate::Offset(days) => write!(f, "{days:+}"),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::ffi::OsStr;
+
+    use super::*;
+
+    #[test]
+    fn colour_choices() {
+        let unset = None;
+        let set = Some(OsStr::new("1"));
+        let empty = Some(OsStr::new(""));
+
+        // `NO_COLOR` only counts if non-empty, and only for `auto`
+        assert!(ColorChoice::Auto.enabled_for(unset, true));
+        assert!(ColorChoice::Auto.enabled_for(empty, true));
+        assert!(!ColorChoice::Auto.enabled_for(set, true));
+        assert!(!ColorChoice::Auto.enabled_for(unset, false));
+        assert!(ColorChoice::Always.enabled_for(set, false));
+        assert!(!ColorChoice::Never.enabled_for(unset, true));
+    }
+
+    #[test]
+    fn watch_intervals() {
+        assert_eq!(parse_interval("30m"), Ok(Duration::from_secs(30 * 60)));
+        assert_eq!(parse_interval("45"), Ok(Duration::from_secs(45 * 60)));
+        assert_eq!(parse_interval("2h"), Ok(Duration::from_secs(2 * 60 * 60)));
+        assert_eq!(parse_interval("90s"), Ok(Duration::from_secs(90)));
+        assert!(parse_interval("0m").is_err());
+        assert!(parse_interval("3d").is_err());
+        assert!(parse_interval("m").is_err());
+    }
 }
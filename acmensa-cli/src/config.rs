@@ -0,0 +1,491 @@
+use std::path::{Path, PathBuf};
+
+use clap::{Arg, ArgAction, Command, builder::ArgPredicate};
+use serde::{Deserialize, Serialize};
+
+const CONFIG_DIR: &str = "acmensa";
+const CONFIG_FILE: &str = "config.toml";
+const CONFIG_TEMPLATE: &str = include_str!("../../res/config-template.toml");
+
+/// User defaults for CLI options, read from `$XDG_CONFIG_HOME/acmensa/config.toml`.
+/// Every value is optional and only replaces the built-in default, i.e. explicit
+/// flags on the command line still take precedence.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UserConfig {
+    /// Default mensa (e.g. "academica").
+    pub mensa: Option<String>,
+    /// Switch to English.
+    pub english: Option<bool>,
+    /// Minutes a downloaded page is reused.
+    pub cache_ttl: Option<u64>,
+    /// Pretty-print theme file (`~` is expanded).
+    pub theme: Option<PathBuf>,
+    /// Meal categories to hide in every output (e.g. ["BurgerClassics"]).
+    pub hidden_categories: Vec<String>,
+    /// Favorite dishes (keywords, e.g. ["Schnitzel", "Käsespätzle"]).
+    pub favorites: Vec<String>,
+    /// Defaults for the `menu` subcommand.
+    pub menu: MenuDefaults,
+    /// Defaults for the `notify` subcommand.
+    pub notify: NotifyDefaults,
+    /// Defaults for the `post discord` subcommand.
+    pub discord: DiscordDefaults,
+    /// Defaults for the `matrix` subcommand.
+    pub matrix: MatrixDefaults,
+    /// Defaults for the `mqtt` subcommand.
+    pub mqtt: MqttDefaults,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MenuDefaults {
+    /// Only print meals of the given category.
+    pub only: Option<String>,
+    /// Order of the meals (price, category, name).
+    pub sort: Option<String>,
+    /// Only print meals costing at most this much (e.g. "3.50").
+    pub max_price: Option<String>,
+    /// Only print headline for main meals.
+    pub short: Option<bool>,
+    /// Print meal prices.
+    pub prices: Option<bool>,
+    /// Print prices parsed and reformatted.
+    pub normalized_prices: Option<bool>,
+    /// Do not print sides.
+    pub skip_sides: Option<bool>,
+    /// Do not print vegan meals.
+    pub skip_vegan: Option<bool>,
+    /// Print allergens.
+    pub allergens: Option<bool>,
+    /// Print the names of the allergens and additives instead of their codes.
+    pub allergen_names: Option<bool>,
+    /// Do not print meals containing any of these allergens.
+    pub exclude_allergens: Vec<String>,
+    /// Print additives.
+    pub additives: Option<bool>,
+    /// Do not print meals containing any of these additives.
+    pub exclude_additives: Vec<String>,
+    /// Do not print meals containing pork/beef/poultry/fish/alcohol.
+    pub no_pork: Option<bool>,
+    pub no_beef: Option<bool>,
+    pub no_poultry: Option<bool>,
+    pub no_fish: Option<bool>,
+    pub no_alcohol: Option<bool>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NotifyDefaults {
+    /// Where to send notifications.
+    pub url: Option<String>,
+    /// Kind of the endpoint (ntfy, gotify, post).
+    pub service: Option<String>,
+    /// Access token.
+    pub token: Option<String>,
+    /// Title template.
+    pub title: Option<String>,
+    /// Body template.
+    pub body: Option<String>,
+    /// Only notify if a main meal contains one of these keywords.
+    pub keywords: Vec<String>,
+    /// Only notify if a main meal of one of these categories is served.
+    pub categories: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DiscordDefaults {
+    /// Webhook to post to.
+    pub webhook_url: Option<String>,
+    /// Name the message is posted under.
+    pub username: Option<String>,
+    /// Include meal prices.
+    pub prices: Option<bool>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MatrixDefaults {
+    /// Homeserver of the bot account.
+    pub homeserver: Option<String>,
+    /// Access token of the bot account.
+    pub token: Option<String>,
+    /// Rooms to join.
+    pub rooms: Vec<String>,
+    /// Time the menu is posted on weekdays (HH:MM).
+    pub post_at: Option<String>,
+    /// Include meal prices.
+    pub prices: Option<bool>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MqttDefaults {
+    /// Broker host.
+    pub host: Option<String>,
+    /// Broker port.
+    pub port: Option<u16>,
+    /// Username for the broker.
+    pub username: Option<String>,
+    /// Password for the broker.
+    pub password: Option<String>,
+    /// Prefix of the topics.
+    pub prefix: Option<String>,
+    /// Prefix of the Home Assistant discovery topics.
+    pub discovery_prefix: Option<String>,
+    /// Minutes between publications.
+    pub interval: Option<u64>,
+}
+
+/// Directory holding the CLI configuration (respects `XDG_CONFIG_HOME`)
+pub fn config_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join(CONFIG_DIR))
+}
+
+/// Replaces a leading `~` in a path from the config file with the home directory (the shell
+/// does that for paths on the command line)
+pub fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), std::env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+impl UserConfig {
+    /// Path of the config file
+    pub fn path() -> Option<PathBuf> {
+        config_dir().map(|dir| dir.join(CONFIG_FILE))
+    }
+
+    /// Loads the config file. A missing file is not an error.
+    pub fn load() -> anyhow::Result<Self> {
+        let Some(path) = Self::path().filter(|p| p.exists()) else {
+            return Ok(Self::default());
+        };
+
+        log::info!("Loading config from {}", path.display());
+        Ok(toml::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    /// Writes the commented template to the config path. An existing config is only
+    /// replaced if `force` is set.
+    pub fn init(force: bool) -> anyhow::Result<PathBuf> {
+        let Some(path) = Self::path() else {
+            anyhow::bail!("could not determine config directory");
+        };
+        if path.exists() && !force {
+            anyhow::bail!(
+                "{} already exists (use --force to overwrite it)",
+                path.display()
+            );
+        }
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, CONFIG_TEMPLATE)?;
+        Ok(path)
+    }
+
+    /// Sets the configured values as defaults of the matching arguments
+    pub fn apply(&self, cmd: Command) -> Command {
+        let mut cmd = cmd;
+        if let Some(ref mensa) = self.mensa {
+            cmd = cmd.mut_arg("mensa", |a| a.default_value(mensa.clone()));
+        }
+        if let Some(english) = self.english {
+            cmd = set_flag(cmd, "english", english);
+            cmd = cmd.mut_subcommand("menu", |c| set_flag(c, "english", english));
+        }
+
+        if let Some(ref theme) = self.theme {
+            let theme = expand_home(theme);
+            cmd = cmd.mut_arg("theme", |a| a.default_value(theme.display().to_string()));
+        }
+
+        if let Some(ttl) = self.cache_ttl {
+            cmd = cmd.mut_arg("cache_ttl", |a| a.default_value(ttl.to_string()));
+        }
+
+        if !self.hidden_categories.is_empty() {
+            let hidden = self.hidden_categories.clone();
+            cmd = cmd.mut_arg("hide", |a| a.default_values(hidden));
+        }
+
+        #[cfg(feature = "notify")]
+        {
+            let notify = self.notify.clone();
+            cmd = cmd.mut_subcommand("notify", |mut c| {
+                for (id, value) in [
+                    ("url", notify.url),
+                    ("service", notify.service),
+                    ("token", notify.token),
+                    ("title", notify.title),
+                    ("body", notify.body),
+                ] {
+                    if let Some(value) = value {
+                        c = c.mut_arg(id, |a| a.default_value(value));
+                    }
+                }
+                for (id, values) in [
+                    ("keyword", notify.keywords),
+                    ("category", notify.categories),
+                ] {
+                    if !values.is_empty() {
+                        c = c.mut_arg(id, |a| a.default_values(values));
+                    }
+                }
+                c
+            });
+        }
+
+        #[cfg(feature = "notify")]
+        {
+            let discord = self.discord.clone();
+            cmd = cmd.mut_subcommand("post", |c| {
+                c.mut_subcommand("discord", |mut c| {
+                    for (id, value) in [
+                        ("webhook_url", discord.webhook_url),
+                        ("username", discord.username),
+                    ] {
+                        if let Some(value) = value {
+                            c = c.mut_arg(id, |a| a.default_value(value));
+                        }
+                    }
+                    if let Some(prices) = discord.prices {
+                        c = set_flag(c, "prices", prices);
+                    }
+                    c
+                })
+            });
+        }
+
+        #[cfg(feature = "matrix")]
+        {
+            let matrix = self.matrix.clone();
+            cmd = cmd.mut_subcommand("matrix", |mut c| {
+                for (id, value) in [
+                    ("homeserver", matrix.homeserver),
+                    ("token", matrix.token),
+                    ("post_at", matrix.post_at),
+                ] {
+                    if let Some(value) = value {
+                        c = c.mut_arg(id, |a| a.default_value(value));
+                    }
+                }
+                if !matrix.rooms.is_empty() {
+                    c = c.mut_arg("room", |a| a.default_values(matrix.rooms));
+                }
+                if let Some(prices) = matrix.prices {
+                    c = set_flag(c, "prices", prices);
+                }
+                c
+            });
+        }
+
+        #[cfg(feature = "mqtt")]
+        {
+            let mqtt = self.mqtt.clone();
+            cmd = cmd.mut_subcommand("mqtt", |mut c| {
+                for (id, value) in [
+                    ("host", mqtt.host),
+                    ("port", mqtt.port.map(|port| port.to_string())),
+                    ("username", mqtt.username),
+                    ("password", mqtt.password),
+                    ("prefix", mqtt.prefix),
+                    ("discovery_prefix", mqtt.discovery_prefix),
+                    (
+                        "interval",
+                        mqtt.interval.map(|interval| interval.to_string()),
+                    ),
+                ] {
+                    if let Some(value) = value {
+                        c = c.mut_arg(id, |a| a.default_value(value));
+                    }
+                }
+                c
+            });
+        }
+
+        if !self.favorites.is_empty() {
+            let favorites = self.favorites.clone();
+            cmd = cmd.mut_subcommand("favorites", |c| {
+                c.mut_subcommand("check", |c| {
+                    c.mut_arg("keyword", |a| a.default_values(favorites))
+                })
+            });
+        }
+
+        let menu = self.menu.clone();
+        cmd.mut_subcommand("menu", |mut c| {
+            if let Some(only) = menu.only {
+                c = c.mut_arg("only", |a| a.default_value(only));
+            }
+            if let Some(sort) = menu.sort {
+                c = c.mut_arg("sort", |a| a.default_value(sort));
+            }
+            if let Some(max_price) = menu.max_price {
+                c = c.mut_arg("max_price", |a| a.default_value(max_price));
+            }
+            if !menu.exclude_allergens.is_empty() {
+                c = c.mut_arg("exclude_allergens", |a| {
+                    a.default_values(menu.exclude_allergens)
+                });
+            }
+            if !menu.exclude_additives.is_empty() {
+                c = c.mut_arg("exclude_additives", |a| {
+                    a.default_values(menu.exclude_additives)
+                });
+            }
+            for (id, value) in [
+                ("short", menu.short),
+                ("prices", menu.prices),
+                ("normalized_prices", menu.normalized_prices),
+                ("skip_sides", menu.skip_sides),
+                ("skip_vegan", menu.skip_vegan),
+                ("allergens", menu.allergens),
+                ("allergen_names", menu.allergen_names),
+                ("additives", menu.additives),
+                ("no_pork", menu.no_pork),
+                ("no_beef", menu.no_beef),
+                ("no_poultry", menu.no_poultry),
+                ("no_fish", menu.no_fish),
+                ("no_alcohol", menu.no_alcohol),
+            ] {
+                if let Some(value) = value {
+                    c = set_flag(c, id, value);
+                }
+            }
+            c
+        })
+    }
+}
+
+/// Sets the default of a flag. Flags enabled by the config get a negated counterpart
+/// (`--no-<flag>`, or `--with-<x>` for `--no-<x>`) to turn them off again.
+fn set_flag(cmd: Command, id: &'static str, value: bool) -> Command {
+    if !value {
+        return cmd.mut_arg(id, |a| a.default_value("false"));
+    }
+
+    let long = cmd
+        .get_arguments()
+        .find(|a| a.get_id() == id)
+        .and_then(|a| a.get_long())
+        .unwrap_or(id)
+        .to_string();
+    let negated = match long.strip_prefix("no-") {
+        Some(rest) => format!("with-{rest}"),
+        None => format!("no-{long}"),
+    };
+    let negated_id = negated.replace('-', "_");
+
+    cmd.mut_arg(id, |a| {
+        a.default_value("true").default_value_if(
+            &negated_id,
+            ArgPredicate::IsPresent,
+            Some("false"),
+        )
+    })
+    .arg(
+        Arg::new(negated_id.clone())
+            .long(negated)
+            .action(ArgAction::SetTrue)
+            .overrides_with(id)
+            .help(format!(
+                "Turn off --{long}, which is enabled in the config file"
+            )),
+    )
+}
+
+#[cfg(test)]
+pub mod tests {
+    use clap::{CommandFactory, FromArgMatches};
+
+    use super::*;
+    use crate::args::{self, Verb};
+
+    #[test]
+    fn theme_path_from_home() {
+        let home = PathBuf::from(std::env::var_os("HOME").unwrap());
+        assert_eq!(
+            expand_home(Path::new("~/.config/acmensa/my-theme.toml")),
+            home.join(".config/acmensa/my-theme.toml")
+        );
+        assert_eq!(expand_home(Path::new("~")), home);
+        // Only a leading `~` component is expanded
+        for path in [
+            "/etc/theme.toml",
+            "theme.toml",
+            "~user/theme.toml",
+            "a/~/theme.toml",
+        ] {
+            assert_eq!(expand_home(Path::new(path)), Path::new(path));
+        }
+    }
+
+    #[test]
+    fn config_defaults() {
+        let config: UserConfig = toml::from_str(
+            r#"
+            mensa = "academica"
+            english = true
+            cache_ttl = 120
+            hidden_categories = ["BurgerClassics"]
+            favorites = ["Schnitzel"]
+
+            [menu]
+            only = "Wok"
+            sort = "price"
+            max_price = "3.50"
+            prices = true
+            skip_sides = false
+            exclude_allergens = ["A"]
+            no_pork = true
+
+            [notify]
+            url = "https://ntfy.sh/mensa"
+            keywords = ["Schnitzel"]
+
+            [discord]
+            prices = true
+
+            [matrix]
+            rooms = ["!room:example.org"]
+            prices = true
+
+            [mqtt]
+            port = 1884
+            "#,
+        )
+        .unwrap();
+        // Every id the config refers to exists (`mut_arg` panics otherwise), and the added
+        // flags are consistent
+        let cmd = config.apply(args::Args::command());
+        cmd.clone().debug_assert();
+
+        let menu = |argv: &[&str]| {
+            let matches = cmd
+                .clone()
+                .try_get_matches_from(["acmensa-cli", "menu"].iter().chain(argv))
+                .unwrap();
+            match args::Args::from_arg_matches(&matches).unwrap().verb {
+                Verb::Menu(opts) => opts,
+                verb => panic!("not a menu: {verb:?}"),
+            }
+        };
+        let opts = menu(&[]);
+        assert!(opts.prices && opts.no_pork && opts.english && !opts.skip_sides);
+
+        let opts = menu(&["--no-prices", "--with-pork", "--no-english"]);
+        assert!(!opts.prices && !opts.no_pork && !opts.english);
+        // The last one wins
+        assert!(menu(&["--no-prices", "--prices"]).prices);
+        assert!(!menu(&["--prices", "--no-prices"]).prices);
+    }
+}
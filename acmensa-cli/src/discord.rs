@@ -0,0 +1,179 @@
+use std::time::Duration;
+
+use chrono::Datelike;
+use libacmensa::{meal::MealType, mensa::Mensa, scrape::DatedDay};
+use serde::Serialize;
+
+use crate::{
+    args::MenuOpts,
+    html::ansi_rgb,
+    pretty_print::{
+        FORMAT_SIDE, diet_marker, main_style, price_text, shown_mains, shown_sides, weekday_name,
+    },
+};
+
+/// Most embeds Discord accepts in a single message
+const MAX_EMBEDS: usize = 10;
+/// Longest field name/value Discord accepts
+const MAX_NAME: usize = 256;
+const MAX_VALUE: usize = 1024;
+/// Field values must not be empty
+const EMPTY_VALUE: &str = "\u{200b}";
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Body of a webhook execution
+#[derive(Debug, Serialize)]
+pub struct Message {
+    content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    username: Option<String>,
+    embeds: Vec<Embed>,
+}
+
+#[derive(Debug, Serialize)]
+struct Embed {
+    title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    color: Option<u32>,
+    fields: Vec<Field>,
+}
+
+#[derive(Debug, Serialize)]
+struct Field {
+    name: String,
+    value: String,
+    inline: bool,
+}
+
+/// Cuts `text` to at most `max` characters
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    text.chars().take(max - 1).chain(['…']).collect()
+}
+
+impl Field {
+    fn new(name: &str, value: &str) -> Self {
+        let value = value.trim();
+        Self {
+            name: truncate(name.trim(), MAX_NAME),
+            value: match value {
+                "" => EMPTY_VALUE.to_string(),
+                _ => truncate(value, MAX_VALUE),
+            },
+            inline: false,
+        }
+    }
+}
+
+/// Renders a day as a message with an embed per category (coloured as in the theme, fields
+/// per meal) and one for the sides. Respects the same filters and options as the pretty
+/// printer. The first embed links to `link` (e.g. the menu page).
+pub fn message(
+    mensa: &Mensa,
+    day: &DatedDay,
+    opts: &MenuOpts,
+    link: Option<&str>,
+    username: Option<&str>,
+) -> Message {
+    let view = day.day.view();
+
+    let mut categories: Vec<(&MealType, Embed)> = vec![];
+    for main in shown_mains(&view, opts) {
+        let mut value = main
+            .subtext
+            .trim_start_matches(|c: char| c == '|' || c.is_whitespace())
+            .to_string();
+        let price = price_text(main, opts);
+        if opts.prices && !price.is_empty() {
+            value = format!("{value}\n**{price}**");
+        }
+        let field = Field::new(&format!("{}{}", main.text, diet_marker(main)), &value);
+
+        match categories.iter_mut().find(|(typ, _)| *typ == &main.typ) {
+            Some((_, embed)) => embed.fields.push(field),
+            None => {
+                let style = main_style(main);
+                categories.push((
+                    &main.typ,
+                    Embed {
+                        title: format!("{} {}", style.emoji, main.typ.name(opts.english)),
+                        url: None,
+                        color: ansi_rgb(&style.colour),
+                        fields: vec![field],
+                    },
+                ));
+            }
+        }
+    }
+    let mut embeds = categories
+        .into_iter()
+        .map(|(_, embed)| embed)
+        .collect::<Vec<_>>();
+
+    let sides = shown_sides(&view, opts);
+    if !sides.is_empty() {
+        embeds.push(Embed {
+            title: if opts.english { "Sides" } else { "Beilagen" }.to_string(),
+            url: None,
+            color: ansi_rgb(&FORMAT_SIDE.head_colour),
+            fields: sides
+                .iter()
+                .map(|side| {
+                    let alternatives = side
+                        .alternatives
+                        .iter()
+                        .map(|a| a.text.trim())
+                        .filter(|text| !text.is_empty())
+                        .collect::<Vec<_>>();
+                    // Sides are short, so they are placed next to each other
+                    Field {
+                        inline: true,
+                        ..Field::new(side.display_name(opts.english), &alternatives.join("\n"))
+                    }
+                })
+                .collect(),
+        });
+    }
+
+    if embeds.len() > MAX_EMBEDS {
+        log::warn!(
+            "only {MAX_EMBEDS} of {} categories fit into a message",
+            embeds.len()
+        );
+        embeds.truncate(MAX_EMBEDS);
+    }
+    if let Some(embed) = embeds.first_mut() {
+        embed.url = link.map(str::to_string);
+    }
+
+    Message {
+        content: format!(
+            "**{}**, {} {}",
+            mensa.name(),
+            weekday_name(day.date.weekday(), opts.english),
+            day.date.format(crate::STDOUT_DATE_FMT)
+        ),
+        username: username.map(str::to_string),
+        embeds,
+    }
+}
+
+/// Executes the webhook at `url` with `message`
+pub async fn send(url: &str, message: &Message) -> anyhow::Result<()> {
+    reqwest::Client::builder()
+        .timeout(TIMEOUT)
+        .user_agent(crate::fetch::USER_AGENT)
+        .build()?
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(serde_json::to_string(message)?)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use std::fmt::Write;
+
+use chrono::{Datelike, NaiveDate};
+use libacmensa::{Week, mensa::Mensa, scrape};
+
+use crate::{
+    args::MenuOpts,
+    html::{self, escape},
+    pretty_print::weekday_name,
+};
+
+/// Page a day was published on (this or next week's menu page)
+fn menu_link(mensa: &Mensa, date: NaiveDate, english: bool) -> String {
+    let next_week = scrape::week_start(Week::Next, crate::fetch::today());
+    let week = Week::from(date >= next_week);
+    scrape::menu_url(mensa.slug(), week, english)
+}
+
+/// Appends an RSS `<item>` for a day: title = date + mensa, description = the day as HTML
+/// (see `html::day`). The guid stays the same for a day, so readers update the entry when
+/// the menu changes.
+pub fn item(out: &mut String, mensa: &Mensa, day: &scrape::DatedDay, opts: &MenuOpts) {
+    let mut description = String::new();
+    html::day(&mut description, day.day.view(), opts);
+
+    let _ = write!(
+        out,
+        "    <item>\n      <title>{}, {} – {}</title>\n      <link>{}</link>\n      \
+         <guid isPermaLink=\"false\">acmensa:{}:{}</guid>\n      <pubDate>{}</pubDate>\n      \
+         <description>{}</description>\n    </item>\n",
+        weekday_name(day.date.weekday(), opts.english),
+        day.date.format(crate::STDOUT_DATE_FMT),
+        escape(mensa.name()),
+        escape(&menu_link(mensa, day.date, opts.english)),
+        mensa.slug(),
+        day.date,
+        day.date
+            .and_time(chrono::NaiveTime::MIN)
+            .and_utc()
+            .to_rfc2822(),
+        escape(&description),
+    );
+}
+
+/// Wraps the `items` of a mensa into an RSS 2.0 document
+pub fn channel(mensa: &Mensa, items: &str, english: bool) -> String {
+    let (title, description) = if english {
+        ("Menu", "Daily menu of the")
+    } else {
+        ("Speiseplan", "Tagesaktueller Speiseplan der")
+    };
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<rss version=\"2.0\">\n  <channel>\n    \
+         <title>{name} – {title}</title>\n    <link>{link}</link>\n    \
+         <description>{description} {name}</description>\n    \
+         <language>{language}</language>\n    <lastBuildDate>{now}</lastBuildDate>\n{items}  \
+         </channel>\n</rss>\n",
+        name = escape(mensa.name()),
+        link = escape(&scrape::menu_url(mensa.slug(), Week::This, english)),
+        language = if english { "en" } else { "de" },
+        now = chrono::Utc::now().to_rfc2822(),
+    )
+}
@@ -0,0 +1,137 @@
+use std::{
+    future::Future,
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
+
+use libacmensa::{Week, cache::Cache, client::ScrapeClient, scrape::WeekData, times::OpeningTimes};
+use tokio::{sync::Semaphore, task::JoinSet};
+
+use libacmensa::mensa::Mensa;
+
+/// Maximum number of requests in flight at any time, to stay polite to the Studierendenwerk
+/// site when fetching multiple mensen
+pub const MAX_CONCURRENT: usize = 4;
+
+/// Permits for requests (see `MAX_CONCURRENT`), taken by every fetch below
+static REQUESTS: Semaphore = Semaphore::const_new(MAX_CONCURRENT);
+
+/// User agent of all requests made by the CLI
+pub const USER_AGENT: &str = concat!("acmensa-cli/", env!("CARGO_PKG_VERSION"));
+
+static CLIENT: OnceLock<ScrapeClient> = OnceLock::new();
+
+/// Sets up the client used for all requests. `ttl` is the time cached pages are reused,
+/// `None` disables the page cache. Malformed parts of pages are skipped unless `strict` is
+/// set. The weeks of the pages are determined in `timezone`. Must be called before the
+/// first request.
+pub fn init(ttl: Option<Duration>, strict: bool, timezone: chrono_tz::Tz) -> anyhow::Result<()> {
+    let client = ScrapeClient::builder()
+        .cache(ttl.and_then(Cache::with_default_dir))
+        .user_agent(USER_AGENT)
+        .lenient(!strict)
+        .timezone(timezone)
+        .build()?;
+    CLIENT
+        .set(client)
+        .map_err(|_| anyhow::anyhow!("client is already set up"))
+}
+
+fn client() -> &'static ScrapeClient {
+    CLIENT.get().expect("fetch::init was not called")
+}
+
+/// Today in the time zone passed to `init`
+pub fn today() -> chrono::NaiveDate {
+    client().today()
+}
+
+/// Fetches the page of a mensa. Uses the page cache unless `refresh` is set, and updates
+/// it after downloading.
+pub async fn fetch_html(
+    mensa: &Mensa,
+    week: Week,
+    english: bool,
+    refresh: bool,
+) -> anyhow::Result<String> {
+    let _permit = REQUESTS.acquire().await?;
+    client()
+        .fetch_html(mensa.slug(), week, english, refresh)
+        .await
+}
+
+/// Fetches, parses and sorts a single week of a mensa
+pub async fn fetch_week(mensa: &Mensa, week: Week, english: bool) -> anyhow::Result<WeekData> {
+    let _permit = REQUESTS.acquire().await?;
+    client().fetch_week(mensa.slug(), week, english).await
+}
+
+/// Fetches the opening times of a mensa
+pub async fn times(mensa: &Mensa) -> anyhow::Result<OpeningTimes> {
+    let _permit = REQUESTS.acquire().await?;
+    client().fetch_times(mensa.slug()).await
+}
+
+/// Client for `ping`: the user agent of all requests, but with `timeout` and no page cache
+pub fn ping_client(timeout: Duration) -> anyhow::Result<ScrapeClient> {
+    ScrapeClient::builder()
+        .user_agent(USER_AGENT)
+        .timeout(timeout)
+        .build()
+}
+
+/// Parses the cached page of a week without any network access (see `--offline`)
+pub async fn cached_week(mensa: &Mensa, week: Week, english: bool) -> anyhow::Result<WeekData> {
+    client().cached_week(mensa.slug(), week, english).await
+}
+
+/// Parses a menu page of a mensa (see `init` for malformed pages)
+pub async fn parse_page(mensa: &Mensa, html: &str) -> anyhow::Result<WeekData> {
+    client().parse_page(mensa.slug(), html).await
+}
+
+/// Fetches this and next week of a mensa concurrently (each counting as a request, see
+/// `MAX_CONCURRENT`)
+pub async fn fetch_fortnight(
+    mensa: &Mensa,
+    english: bool,
+) -> (anyhow::Result<WeekData>, anyhow::Result<WeekData>) {
+    tokio::join!(
+        fetch_week(mensa, Week::This, english),
+        fetch_week(mensa, Week::Next, english),
+    )
+}
+
+/// Runs `jobs` with at most `MAX_CONCURRENT` of them at a time. Requests made through this
+/// module are limited on their own, so jobs fetching several pages stay within the limit.
+/// Results are returned in the order of `jobs`.
+pub async fn limited<T, F>(jobs: impl IntoIterator<Item = F>) -> Vec<T>
+where
+    T: Send + 'static,
+    F: Future<Output = T> + Send + 'static,
+{
+    let limiter = Arc::new(Semaphore::new(MAX_CONCURRENT));
+    let mut tasks = JoinSet::new();
+
+    for (i, job) in jobs.into_iter().enumerate() {
+        let limiter = limiter.clone();
+        tasks.spawn(async move {
+            let _permit = limiter.acquire_owned().await;
+            (i, job.await)
+        });
+    }
+
+    let mut results = tasks.join_all().await;
+    results.sort_by_key(|(i, _)| *i);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Fetches a week for every mensa, at most `MAX_CONCURRENT` at a time.
+/// Results are returned in the order of `Mensa::all()`.
+pub async fn fetch_all(week: Week, english: bool) -> Vec<(Mensa, anyhow::Result<WeekData>)> {
+    limited(Mensa::all().iter().copied().map(|mensa| async move {
+        let result = fetch_week(&mensa, week, english).await;
+        (mensa, result)
+    }))
+    .await
+}
@@ -0,0 +1,135 @@
+use std::fmt::Write;
+
+use chrono::{Datelike, NaiveDate};
+use libacmensa::{mensa::Mensa, scrape::DayView};
+
+use crate::{
+    args::MenuOpts,
+    pretty_print::{
+        code_lines, diet_marker, main_style, price_text, shown_mains, shown_sides, weekday_name,
+    },
+};
+
+/// Levels of the 6x6x6 colour cube of the 256 colour palette
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// The 16 system colours (xterm defaults)
+const SYSTEM_COLOURS: [u32; 16] = [
+    0x000000, 0xcd0000, 0x00cd00, 0xcdcd00, 0x0000ee, 0xcd00cd, 0x00cdcd, 0xe5e5e5, 0x7f7f7f,
+    0xff0000, 0x00ff00, 0xffff00, 0x5c5cff, 0xff00ff, 0x00ffff, 0xffffff,
+];
+
+/// Escapes text for use in elements and attribute values
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// RGB value of a colour of the 256 colour palette, as used by the theme (e.g. "120")
+pub fn ansi_rgb(code: &str) -> Option<u32> {
+    Some(match code.trim().parse::<u8>().ok()? {
+        n @ 0..=15 => SYSTEM_COLOURS[n as usize],
+        n @ 16..=231 => {
+            let n = n - 16;
+            let level = |i: u8| CUBE_LEVELS[i as usize] as u32;
+            level(n / 36) << 16 | level(n / 6 % 6) << 8 | level(n % 6)
+        }
+        n => {
+            let grey = 8 + 10 * (n - 232) as u32;
+            grey << 16 | grey << 8 | grey
+        }
+    })
+}
+
+/// Wraps `text` (already escaped) in a `<font>` of a theme colour, if it is a valid one.
+/// `<font color>` is understood by browsers and Matrix clients alike.
+fn coloured(text: &str, code: &str) -> String {
+    match ansi_rgb(code) {
+        Some(rgb) => format!("<font color=\"#{rgb:06x}\">{text}</font>"),
+        None => text.to_string(),
+    }
+}
+
+/// Weekday/date heading (used when printing multiple days)
+pub fn day_header(out: &mut String, date: NaiveDate, english: bool) {
+    let _ = writeln!(
+        out,
+        "<h3>{}, {}</h3>",
+        weekday_name(date.weekday(), english),
+        date.format(crate::STDOUT_DATE_FMT)
+    );
+}
+
+/// Mensa heading (used when printing multiple mensen)
+pub fn mensa_header(out: &mut String, mensa: &Mensa) {
+    let _ = writeln!(out, "<h2>{}</h2>", escape(mensa.name()));
+}
+
+/// Renders a day as a list of the main meals (coloured as in the theme) followed by a
+/// list of the sides. Respects the same filters and options as the pretty printer.
+pub fn day(out: &mut String, day: DayView<'_>, opts: &MenuOpts) {
+    let mains = shown_mains(&day, opts);
+    if !mains.is_empty() {
+        out.push_str("<ul>\n");
+    }
+    for main in &mains {
+        let style = main_style(main);
+        let headline = format!(
+            "{} <b>{}</b>{}",
+            style.emoji,
+            escape(main.text.trim()),
+            diet_marker(main)
+        );
+        let _ = write!(out, "<li>{}", coloured(&headline, &style.colour));
+
+        let subtext = main
+            .subtext
+            .trim_start_matches(|c: char| c == '|' || c.is_whitespace());
+        if !opts.short && !subtext.is_empty() {
+            let _ = write!(out, " <i>{}</i>", escape(subtext.trim()));
+        }
+        let price = price_text(main, opts);
+        if opts.prices && !price.is_empty() {
+            let _ = write!(out, " – {}", escape(&price));
+        }
+        if !opts.short {
+            for line in code_lines(&main.allergens, &main.additives, opts) {
+                let _ = write!(out, "<br><i>{}</i>", escape(&line));
+            }
+        }
+        out.push_str("</li>\n");
+    }
+    if !mains.is_empty() {
+        out.push_str("</ul>\n");
+    }
+
+    let sides = shown_sides(&day, opts);
+    if sides.is_empty() {
+        return;
+    }
+    out.push_str("<ul>\n");
+    for side in sides {
+        let alternatives = side
+            .alternatives
+            .iter()
+            .filter(|a| !a.text.trim().is_empty())
+            .map(|a| {
+                let codes = code_lines(&a.allergens, &a.additives, opts);
+                if codes.is_empty() {
+                    a.text.clone()
+                } else {
+                    format!("{} ({})", a.text, codes.join("; "))
+                }
+            })
+            .collect::<Vec<_>>();
+        let _ = writeln!(
+            out,
+            "<li><b>{}:</b> {}</li>",
+            escape(side.display_name(opts.english)),
+            escape(&alternatives.join(", "))
+        );
+    }
+    out.push_str("</ul>\n");
+}
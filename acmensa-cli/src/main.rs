@@ -1,15 +1,51 @@
-use chrono::{DateTime, Datelike, Duration, FixedOffset, Weekday};
-use clap::Parser;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Weekday};
+use chrono_tz::Tz;
+use clap::{CommandFactory, FromArgMatches};
 
+use libacmensa::scrape::{DatedDay, TwoWeeks, WeekData};
 #[cfg(feature = "json")]
-use std::path::PathBuf;
+use libacmensa::{SchemaVersion, scrape::DayData};
+use libacmensa::{Week, meal::MealInfo, mensa::Mensa};
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "json")]
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
 
-#[cfg(feature = "json-schema")]
-use libacmensa::scrape::WeekData;
+use std::{collections::HashMap, io::IsTerminal, ops::RangeInclusive};
 
 use crate::args::{MenuDate, Verb};
 
+mod config;
+#[cfg(feature = "notify")]
+mod discord;
+#[cfg(feature = "json")]
+mod feed;
+mod fetch;
+mod html;
+mod markdown;
+#[cfg(feature = "matrix")]
+mod matrix;
+#[cfg(feature = "mqtt")]
+mod mqtt;
+#[cfg(feature = "notify")]
+mod notify;
+mod offline;
+mod plain;
 mod pretty_print;
+#[cfg(feature = "image-export")]
+mod render;
+mod renderer;
+#[cfg(feature = "site")]
+mod site;
+#[cfg(feature = "json")]
+mod snapshot;
+#[cfg(feature = "tui")]
+mod tui;
+#[cfg(feature = "json")]
+mod waybar;
 #[cfg(debug_assertions)]
 const DEF_LOG_LEVEL: &str = "info";
 
@@ -20,6 +56,9 @@ const ENV_LOG_LEVEL: &str = "RUST_LOG";
 
 const STDOUT_DATE_FMT: &str = "%d.%m.%Y";
 
+/// Exit status of `favorites check` if a favorite is on the menu (errors exit with 1)
+const FAVORITES_FOUND: i32 = 3;
+
 #[cfg(feature = "json")]
 const JSON_NAME_DATE_FMT: &str = "%Y%m%d.json";
 
@@ -27,14 +66,204 @@ use pretty_print::*;
 
 pub(crate) mod args;
 
+/// Entry of the `all` JSON map: either the day plan or why it is missing
+#[cfg(feature = "json")]
+#[derive(Serialize)]
+#[serde(untagged)]
+enum MensaDay {
+    Day(DatedDay),
+    Error { error: String },
+}
+
+/// Document written by `export --combined`
+#[cfg(feature = "json")]
+#[derive(Serialize, Deserialize)]
+struct CombinedExport {
+    #[serde(default)]
+    schema_version: SchemaVersion,
+    mensa: String,
+    weeks: Vec<WeekData>,
+}
+
 #[derive(Debug, Clone)]
 struct DateCtx {
-    pub utc2: FixedOffset,
-    pub now: DateTime<FixedOffset>,
-    pub day_n: u32,
+    pub now: DateTime<Tz>,
 
-    pub first_avail_date: DateTime<FixedOffset>,
-    pub last_avail_date: DateTime<FixedOffset>,
+    /// Monday of this week
+    pub first_avail_date: NaiveDate,
+    /// Friday of next week
+    pub last_avail_date: NaiveDate,
+}
+
+/// Why there is no menu for a requested date
+#[derive(Debug, thiserror::Error)]
+enum MenuError {
+    #[error(
+        "{} is on a weekend, the next day with a menu is {}",
+        .date.format(STDOUT_DATE_FMT),
+        .nearest.format(STDOUT_DATE_FMT)
+    )]
+    Weekend { date: NaiveDate, nearest: NaiveDate },
+
+    #[error(
+        "there is no menu for {} (available: {} -- {}), the nearest day with one is {}",
+        .date.format(STDOUT_DATE_FMT),
+        .available.start().format(STDOUT_DATE_FMT),
+        .available.end().format(STDOUT_DATE_FMT),
+        .nearest.format(STDOUT_DATE_FMT)
+    )]
+    OutOfRange {
+        date: NaiveDate,
+        available: RangeInclusive<NaiveDate>,
+        nearest: NaiveDate,
+    },
+
+    #[error(
+        "calendar week {week} is not available (only weeks {} and {}), the nearest one is week {}",
+        .available[0],
+        .available[1],
+        .nearest.iso_week().week()
+    )]
+    UnavailableWeek {
+        week: u32,
+        available: [u32; 2],
+        nearest: NaiveDate,
+    },
+}
+
+impl MenuError {
+    /// Open day closest to the requested one (see `--nearest`), the Monday of the closest
+    /// available week for `UnavailableWeek`
+    fn nearest(&self) -> NaiveDate {
+        match self {
+            MenuError::Weekend { nearest, .. }
+            | MenuError::OutOfRange { nearest, .. }
+            | MenuError::UnavailableWeek { nearest, .. } => *nearest,
+        }
+    }
+}
+
+impl DateCtx {
+    /// The available weeks around `now`. They are derived from the local date only, so the
+    /// hour lost or gained at DST transitions cannot move them.
+    fn new(now: DateTime<Tz>) -> Self {
+        let first_avail_date = libacmensa::scrape::week_start(Week::This, now.date_naive());
+        Self {
+            now,
+            first_avail_date,
+            last_avail_date: first_avail_date + Duration::days(11),
+        }
+    }
+
+    /// Days with a published menu (Monday of this week to Friday of the next one)
+    fn available(&self) -> RangeInclusive<NaiveDate> {
+        self.first_avail_date..=self.last_avail_date
+    }
+
+    /// Fails if there is no menu on `date` (see `MenuError`)
+    fn check(&self, date: NaiveDate) -> Result<NaiveDate, MenuError> {
+        let available = self.available();
+        if !available.contains(&date) {
+            return Err(MenuError::OutOfRange {
+                date,
+                nearest: date.clamp(*available.start(), *available.end()),
+                available,
+            });
+        }
+        // The available weeks end on a Friday, so the Monday after a weekend inside them
+        // is available as well
+        if [Weekday::Sat, Weekday::Sun].contains(&date.weekday()) {
+            return Err(MenuError::Weekend {
+                date,
+                nearest: date.week(Weekday::Mon).first_day() + Duration::days(7),
+            });
+        }
+        Ok(date)
+    }
+
+    /// Maps an ISO calendar week onto this or next week. Week numbers repeat every year, so
+    /// the occurrence closest to this week is meant.
+    fn iso_week(&self, week: u32) -> Result<Week, MenuError> {
+        let this = self.first_avail_date;
+        let next = this + Duration::days(7);
+        let year = this.iso_week().year();
+        let monday = (year - 1..=year + 1)
+            .filter_map(|year| NaiveDate::from_isoywd_opt(year, week, Weekday::Mon))
+            .min_by_key(|monday| (*monday - this).num_days().abs());
+
+        match monday {
+            Some(monday) if monday == this => Ok(Week::This),
+            Some(monday) if monday == next => Ok(Week::Next),
+            monday => Err(MenuError::UnavailableWeek {
+                week,
+                available: [this.iso_week().week(), next.iso_week().week()],
+                nearest: if monday.is_some_and(|monday| monday > next) {
+                    next
+                } else {
+                    this
+                },
+            }),
+        }
+    }
+}
+
+/// Resolves `--date`/`--day` to the week (this/next) and the day index inside it. Days
+/// without a menu (weekends, outside of the two published weeks) fail, or are replaced by
+/// the nearest one with `nearest`.
+fn resolve_day(
+    date: Option<NaiveDate>,
+    day: &MenuDate,
+    datectx: &DateCtx,
+    nearest: bool,
+) -> Result<(Week, i64), MenuError> {
+    let date = date.unwrap_or_else(|| day.date(datectx.now.date_naive()));
+
+    let date = match datectx.check(date) {
+        Ok(date) => date,
+        Err(e) if nearest => {
+            log::info!("{e}, showing that one instead");
+            e.nearest()
+        }
+        Err(e) => return Err(e),
+    };
+
+    let diff = (date - datectx.first_avail_date).num_days();
+    Ok((Week::from(diff > 6), diff % 7))
+}
+
+/// Resolves `--week <ISO_WEEK>` to this or next week. Weeks that are not available fail,
+/// or are replaced by the nearest one with `nearest`.
+fn resolve_week(week: u32, datectx: &DateCtx, nearest: bool) -> Result<Week, MenuError> {
+    match datectx.iso_week(week) {
+        Ok(week) => Ok(week),
+        Err(e) if nearest => {
+            log::info!("{e}, showing that one instead");
+            Ok(Week::from(e.nearest() > datectx.first_avail_date))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Prints how `resolve_day` arrived at its result (see `--explain`)
+fn explain_day(opts: &args::MenuOpts, datectx: &DateCtx, week: Week, idx: i64) {
+    let source = match (opts.week, opts.date, &opts.day) {
+        (Some(Some(week)), ..) => format!("--week {week}"),
+        _ if opts.next_week => "--next-week".to_string(),
+        (_, Some(date), _) => format!("--date {date}"),
+        (_, None, day) => format!("--day {day}"),
+    };
+    let date = datectx.first_avail_date + Duration::days(7 * week.offset() + idx);
+
+    eprintln!("Resolved from:  {source}");
+    eprintln!("Today:          {}", datectx.now.format(STDOUT_DATE_FMT));
+    eprintln!(
+        "Available:      {} -- {}",
+        datectx.first_avail_date.format(STDOUT_DATE_FMT),
+        datectx.last_avail_date.format(STDOUT_DATE_FMT)
+    );
+    eprintln!("Chosen date:    {}", date.format(STDOUT_DATE_FMT));
+    eprintln!("Week:           {week}");
+    eprintln!("Index in week:  {idx}");
 }
 
 async fn handle_menu(
@@ -42,118 +271,1527 @@ async fn handle_menu(
     opts: &args::MenuOpts,
     datectx: &DateCtx,
 ) -> anyhow::Result<()> {
-    let DateCtx {
-        utc2: utc1,
-        now,
-        day_n,
-        first_avail_date,
-        last_avail_date,
-    } = *datectx;
-
-    let (next_week, idx) = if let Some(date) = opts.date {
-        if [Weekday::Sat, Weekday::Sun].contains(&date.weekday()) {
-            log::error!("requested date falls in a weekend");
-            panic!();
-        }
+    if opts.watch {
+        return watch_menu(args, opts).await;
+    }
+    let days = menu_days(args, opts, datectx).await?;
+    render_days(opts, &days)?.flush(opts.tee.as_deref())
+}
 
-        let date = date
-            .and_time(chrono::NaiveTime::from_hms_opt(0, 0, 0).unwrap())
-            .and_local_timezone(utc1)
-            .unwrap();
+/// Fetches the menu again every `--interval`, clearing the screen and marking the dishes
+/// that were added, changed or removed since the previous fetch (see `--watch`). Failed
+/// fetches are logged and leave the previous menu on the screen.
+async fn watch_menu(args: &args::Args, opts: &args::MenuOpts) -> anyhow::Result<()> {
+    if opts.format() != args::MenuFormat::Pretty {
+        anyhow::bail!("--watch only works with the pretty format");
+    }
+    // Keeps showing a menu on weekends and after the last published day
+    let opts = &args::MenuOpts {
+        nearest: true,
+        ..opts.clone()
+    };
+
+    let mut previous: Option<MenuDays> = None;
+    loop {
+        // Runs across days and weeks, so "today" is determined anew
+        let datectx = DateCtx::new(chrono::Utc::now().with_timezone(&args.tz()));
+        match menu_days(args, opts, &datectx).await {
+            Ok(days) => {
+                let next = datectx.now + Duration::from_std(opts.interval)?;
+                let mut out = Output::default();
+                print_watch_status(&mut out, datectx.now, next, opts.english);
+                print_changes(&mut out, &days, previous.as_deref(), opts);
 
-        if date > last_avail_date || date < first_avail_date {
-            log::error!("requested date falls outside available data range");
-            panic!();
+                // Clear the screen and move the cursor to the top left
+                if std::io::stdout().is_terminal() {
+                    print!("\x1b[2J\x1b[H");
+                }
+                out.flush(None)?;
+                previous = Some(days);
+            }
+            Err(e) => log::error!("could not refresh the menu: {e}"),
         }
+        tokio::time::sleep(opts.interval).await;
+    }
+}
 
-        let diff = (date - first_avail_date).num_days();
-        let next_week = diff > 6;
-        let idx = diff % 7;
+/// Shown days of each mensa, as rendered by `menu`
+type MenuDays = Vec<(Mensa, Vec<DatedDay>)>;
 
-        (next_week, idx)
-    } else if opts.day == MenuDate::Today {
-        if [Weekday::Sat, Weekday::Sun].contains(&now.weekday()) {
-            log::error!("requested date falls in a weekend");
-            panic!();
+/// Renders the days in the format requested by `opts`
+fn render_days(opts: &args::MenuOpts, days: &[(Mensa, Vec<DatedDay>)]) -> anyhow::Result<Output> {
+    let mut renderer = opts.format().renderer(opts.whole_week(), opts.bool_style);
+    for (mensa, days) in days {
+        if opts.all_mensen {
+            renderer.mensa(mensa)?;
         }
+        for day in days {
+            renderer.day(day, opts)?;
+        }
+    }
+    Ok(Output::from(renderer.finish()?))
+}
 
-        let diff = (now - first_avail_date).num_days();
-        let next_week = diff > 6;
-        let idx = diff % 7;
+/// Fetches and filters the days requested by `opts`
+async fn menu_days(
+    args: &args::Args,
+    opts: &args::MenuOpts,
+    datectx: &DateCtx,
+) -> anyhow::Result<MenuDays> {
+    let (week, idx) = match opts.week {
+        Some(Some(week)) => (resolve_week(week, datectx, opts.nearest)?, 0),
+        _ if opts.next_week => (Week::Next, 0),
+        _ => resolve_day(opts.date, &opts.day, datectx, opts.nearest)?,
+    };
 
-        (next_week, idx)
+    if opts.explain {
+        explain_day(opts, datectx, week, idx);
+    }
+
+    // Mensen that could not be fetched are skipped with --all-mensen
+    let monday = datectx.first_avail_date + Duration::days(7 * week.offset());
+    let mut translations = HashMap::new();
+    let results = if let Some(ref path) = opts.from_file {
+        let page = offline::page(path, &args.mensa).await?;
+        vec![(
+            args.mensa,
+            page.with_week_start(monday).with_mensa(args.mensa.slug()),
+        )]
+    } else if opts.offline {
+        let mensen = if opts.all_mensen {
+            libacmensa::mensa::Mensa::all().to_vec()
+        } else {
+            vec![args.mensa]
+        };
+        // The data directory is read once for all mensen
+        let exported = offline::exported(opts)?;
+        let mut results = vec![];
+        for mensa in mensen {
+            let days = offline::days(&mensa, opts, exported.as_deref()).await;
+            match offline::week(&mensa, monday, &days) {
+                Ok(week) => results.push((mensa, week)),
+                Err(e) if opts.all_mensen => log::error!("{e}"),
+                Err(e) => return Err(e),
+            }
+        }
+        results
     } else {
-        let next_week = day_n > 5;
-        let idx = if next_week { 0 } else { day_n };
+        let mensen = if opts.all_mensen {
+            libacmensa::mensa::Mensa::all().to_vec()
+        } else {
+            vec![args.mensa]
+        };
+        // --grep also searches the other language, its weeks are fetched alongside
+        let languages = if opts.grep.is_some() {
+            vec![opts.english, !opts.english]
+        } else {
+            vec![opts.english]
+        };
+        let jobs = mensen.into_iter().flat_map(|mensa| {
+            languages
+                .clone()
+                .into_iter()
+                .map(move |english| async move {
+                    (
+                        mensa,
+                        english,
+                        fetch::fetch_week(&mensa, week, english).await,
+                    )
+                })
+        });
 
-        let next_day = first_avail_date
-            + if next_week {
-                Duration::days(7)
-            } else {
-                Duration::days(day_n.into())
+        let mut results = vec![];
+        for (mensa, english, result) in fetch::limited(jobs).await {
+            match result {
+                Ok(fetched) if english != opts.english => {
+                    translations.insert(mensa, fetched);
+                }
+                Ok(fetched) => results.push((mensa, fetched)),
+                Err(e) if english != opts.english => {
+                    log::warn!("could not search {mensa} in both languages: {e}")
+                }
+                Err(e) if opts.all_mensen => log::error!("could not fetch {mensa}: {e}"),
+                Err(e) => return Err(e),
+            }
+        }
+        results
+    };
+
+    let excluded = opts
+        .exclude_allergens
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+    let excluded_additives = opts
+        .exclude_additives
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+    let hidden = args.hidden(opts.only.as_ref());
+
+    let mut shown = vec![];
+    let mut found = false;
+    for (mensa, fetched) in results {
+        let mut result = fetched.clone().without_types(&hidden);
+
+        if let Some(ref query) = opts.grep {
+            // Pages read from files or the cache are only searched in their own language
+            let translated = translations
+                .remove(&mensa)
+                .map(|w| w.without_types(&hidden));
+            result = grep_week(result, translated, query);
+        }
+
+        let mut days = if opts.whole_week() {
+            (0..result.main_dishes.len()).collect::<Vec<_>>()
+        } else {
+            vec![idx as usize]
+        };
+        if opts.grep.is_some() && opts.whole_week() {
+            days.retain(|day| !result.main_dishes[*day].is_empty());
+        }
+        found |= days.iter().any(|day| !result.main_dishes[*day].is_empty());
+        if opts.reverse_days {
+            days.reverse();
+        }
+
+        let mut mensa_days = vec![];
+        for day in days {
+            let dated = result.get_dated_day(day)?;
+            let result_day = dated
+                .day
+                .view()
+                .filter_allergens(&excluded)
+                .view()
+                .filter_additives(&excluded_additives)
+                .view()
+                .filter_contents(opts.excluded_contents());
+            let result_day = match opts.max_price {
+                Some(max) => result_day.view().filter_max_price(max),
+                None => result_day,
             };
-        log::info!("Next day is {}", next_day.format(STDOUT_DATE_FMT));
+            let result_day = match opts.sort {
+                Some(key) => result_day.view().sorted_by(key),
+                None => result_day,
+            };
+
+            #[cfg(feature = "json")]
+            let result_day = remember_day(
+                &mensa,
+                dated.date,
+                opts,
+                &fetched.get_day(day)?.into(),
+                result_day,
+            );
+
+            mensa_days.push(DatedDay {
+                day: result_day,
+                ..dated
+            });
+        }
+        shown.push((mensa, mensa_days));
+    }
+
+    if let Some(ref query) = opts.grep
+        && !found
+    {
+        log::warn!("no meals mentioning \"{query}\"");
+    }
 
-        (next_week, idx as i64)
+    Ok(shown)
+}
+
+/// Reduces the week to the main dishes mentioning `query` (see `WeekData::search`), in its
+/// language or in `translated`, the same week in the other language. Sides are dropped.
+fn grep_week(mut week: WeekData, translated: Option<WeekData>, query: &str) -> WeekData {
+    let positions = |week: &WeekData| {
+        week.search(query)
+            .iter()
+            .map(|hit| (hit.day, hit.index))
+            .collect::<Vec<_>>()
     };
+    let mut hits = positions(&week);
+    // The dishes of both languages are matched by their position on the page
+    if let Some(translated) = translated {
+        let same_layout = translated
+            .main_dishes
+            .iter()
+            .map(Vec::len)
+            .eq(week.main_dishes.iter().map(Vec::len));
+        if same_layout {
+            hits.extend(positions(&translated));
+        }
+    }
+
+    for (day, mains) in week.main_dishes.iter_mut().enumerate() {
+        let mut index = 0;
+        mains.retain(|_| {
+            index += 1;
+            hits.contains(&(day, index - 1))
+        });
+    }
+    week.side_dishes = Default::default();
+    week
+}
+
+/// Stores the day as fetched (before any filter) for `--since last-fetch` and, if
+/// requested, reduces the shown (filtered) day to the dishes that are new since the
+/// previous fetch.
+#[cfg(feature = "json")]
+fn remember_day(
+    mensa: &Mensa,
+    date: NaiveDate,
+    opts: &args::MenuOpts,
+    fetched: &DayData,
+    day: DayData,
+) -> DayData {
+    let previous = opts
+        .since
+        .as_ref()
+        .and_then(|_| snapshot::load(snapshot::Store::Menu, mensa, date, opts.english));
 
-    let get = libacmensa::scrape::get(&args.mensa.url_name(), next_week, opts.english).await?;
-    let html = get.text().await?;
-    let result = libacmensa::scrape::scrape_page(&html).await?.sorted();
-    let result_day = result.get_day(idx as usize);
+    if let Err(e) = snapshot::store(snapshot::Store::Menu, mensa, date, opts.english, fetched) {
+        log::warn!("could not remember fetched day: {e}");
+    }
+
+    match previous {
+        Some(previous) => day.added_since(&previous),
+        None => {
+            if opts.since.is_some() {
+                log::info!("no previous fetch for {date}, showing everything");
+            }
+            day
+        }
+    }
+}
+
+async fn handle_all(
+    args: &args::Args,
+    opts: &args::AllOpts,
+    datectx: &DateCtx,
+) -> anyhow::Result<()> {
+    let (week, idx) = resolve_day(opts.date, &opts.day, datectx, opts.nearest)?;
+    let hidden = args.hidden(None);
+    let results = fetch::fetch_all(week, args.english)
+        .await
+        .into_iter()
+        .map(|(mensa, result)| {
+            let day = result
+                .and_then(|week| Ok(week.without_types(&hidden).get_dated_day(idx as usize)?));
+            (mensa, day)
+        })
+        .collect::<Vec<_>>();
 
     #[cfg(feature = "json")]
     if opts.json {
-        println!("{}", serde_json::to_string_pretty(&result_day)?);
+        let days = results
+            .into_iter()
+            .map(|(mensa, result)| {
+                let entry = match result {
+                    Ok(day) => MensaDay::Day(day),
+                    Err(e) => {
+                        log::warn!("could not fetch {mensa}: {e}");
+                        MensaDay::Error {
+                            error: e.to_string(),
+                        }
+                    }
+                };
+                (mensa.slug(), entry)
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        println!("{}", serde_json::to_string_pretty(&days)?);
+        return Ok(());
+    }
+
+    let menu_opts = args::MenuOpts {
+        english: args.english,
+        ..Default::default()
+    };
+    let mut out = Output::default();
+    for (mensa, result) in results {
+        print_mensa_header(&mut out, &mensa);
+        match result {
+            Ok(day) => pretty_print_all(&mut out, day.day.view(), &menu_opts),
+            Err(e) => log::error!("could not fetch {mensa}: {e}"),
+        }
+    }
+
+    out.flush(opts.tee.as_deref())
+}
+
+async fn handle_prefetch(args: &args::Args, opts: &args::PrefetchOpts) -> anyhow::Result<()> {
+    let jobs = opts
+        .mensa
+        .mensen()
+        .into_iter()
+        .flat_map(|mensa| Week::ALL.map(|week| (mensa, week)))
+        .collect::<Vec<_>>();
+
+    let english = args.english;
+    let results =
+        fetch::limited(jobs.iter().cloned().map(|(mensa, week)| async move {
+            fetch::fetch_html(&mensa, week, english, true).await
+        }))
+        .await;
+
+    let mut failed = 0;
+    for ((mensa, week), result) in jobs.iter().zip(results) {
+        match result {
+            Ok(_) => println!("ok      {mensa} ({week} week)"),
+            Err(e) => {
+                failed += 1;
+                println!("failed  {mensa} ({week} week): {e}");
+            }
+        }
+    }
+
+    println!("{} fetched, {failed} failed", jobs.len() - failed);
+    if failed > 0 {
+        anyhow::bail!("{failed} of {} pages could not be fetched", jobs.len());
+    }
+
+    Ok(())
+}
+
+async fn handle_ping(opts: &args::PingOpts) -> anyhow::Result<()> {
+    let jobs = opts
+        .mensa
+        .mensen()
+        .into_iter()
+        .flat_map(|mensa| Week::ALL.map(|week| (mensa, week)))
+        .flat_map(|(mensa, week)| [false, true].map(|english| (mensa, week, english)))
+        .collect::<Vec<_>>();
+
+    let client = fetch::ping_client(std::time::Duration::from_secs(opts.timeout))?;
+    let results = fetch::limited(jobs.iter().cloned().map(|(mensa, week, english)| {
+        let client = client.clone();
+        async move { client.ping(mensa.slug(), week, english).await }
+    }))
+    .await;
+
+    let mut failed = 0;
+    for ((mensa, week, english), result) in jobs.iter().zip(results) {
+        let page = format!(
+            "{mensa} ({week} week, {})",
+            if *english { "en" } else { "de" }
+        );
+        match result {
+            Ok((status, latency)) => {
+                if !(200..300).contains(&status) {
+                    failed += 1;
+                }
+                println!("{status}  {:>5} ms  {page}", latency.as_millis());
+            }
+            Err(e) => {
+                failed += 1;
+                println!("ERR  {:>8}  {page}: {e}", "-");
+            }
+        }
+    }
+
+    if failed > 0 {
+        anyhow::bail!("{failed} of {} pages are not reachable", jobs.len());
+    }
+
+    Ok(())
+}
+
+async fn handle_render(
+    args: &args::Args,
+    opts: &args::RenderOpts,
+    datectx: &DateCtx,
+) -> anyhow::Result<()> {
+    let (week, idx) = resolve_day(opts.date, &opts.day, datectx, false)?;
+    let result = fetch::fetch_week(&args.mensa, week, args.english)
+        .await?
+        .without_types(&args.hidden(None));
+
+    let date = datectx.first_avail_date + Duration::days(7 * week.offset() + idx);
+    let menu_opts = args::MenuOpts {
+        english: args.english,
+        prices: true,
+        ..Default::default()
+    };
+    let mut out = Output::default();
+    print_mensa_header(&mut out, &args.mensa);
+    print_day_header(&mut out, date, args.english);
+    pretty_print_all(&mut out, result.get_day(idx as usize)?, &menu_opts);
+
+    // Headers start with an empty line to separate them from the previous output
+    let lines = out
+        .lines()
+        .skip_while(|l| l.is_empty())
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    match opts.format {
+        args::RenderFormat::Text => {
+            let text = lines
+                .iter()
+                .map(|l| to_plain_line(l))
+                .collect::<Vec<_>>()
+                .join("\n")
+                + "\n";
+            match opts.output {
+                Some(ref path) => std::fs::write(path, text)?,
+                None => print!("{text}"),
+            }
+        }
+        #[cfg(feature = "image-export")]
+        args::RenderFormat::Png => {
+            let path = opts.output.clone().unwrap_or(PathBuf::from("menu.png"));
+            let png_opts = render::PngOpts {
+                width: opts.width,
+                background: render::parse_colour(&opts.background)?,
+                font: opts.font.as_deref(),
+            };
+            render::write_png(&lines, &png_opts, &path)?;
+            log::info!("wrote {}", path.display());
+        }
+    }
+
+    Ok(())
+}
+
+async fn handle_compare(args: &args::Args, opts: &args::CompareOpts) -> anyhow::Result<()> {
+    let hidden = args.hidden(None);
+    let mut rows = vec![];
+    for (mensa, result) in fetch::fetch_all(opts.next_week.into(), args.english).await {
+        let week = match result {
+            Ok(week) => week.without_types(&hidden),
+            Err(e) => {
+                log::warn!("could not fetch {mensa}, skipping it: {e}");
+                continue;
+            }
+        };
+
+        // Same dish on several days -> only list it once per mensa
+        let mut found: Vec<MealInfo> = vec![];
+        for meal in week.main_dishes.iter().flatten() {
+            if meal.matches(&opts.dish) && !found.iter().any(|m| m.same_dish(meal)) {
+                found.push(meal.clone());
+            }
+        }
+        rows.extend(found.into_iter().map(|meal| (mensa, meal)));
+    }
+
+    if rows.is_empty() {
+        anyhow::bail!("no dish matching \"{}\" found", opts.dish);
+    }
+
+    // Cheapest first, unparseable prices last
+    rows.sort_by_key(|(_, meal)| (meal.price_value.is_none(), meal.price_value));
+    let mut out = Output::default();
+    print_price_table(&mut out, &rows);
+    out.flush(None)
+}
+
+async fn handle_when(
+    args: &args::Args,
+    opts: &args::WhenOpts,
+    datectx: &DateCtx,
+) -> anyhow::Result<()> {
+    let hidden = args.hidden(None);
+    let (this_week, next_week) = fetch::fetch_fortnight(&args.mensa, args.english).await;
+    let weeks = TwoWeeks {
+        this_week: this_week?.without_types(&hidden),
+        next_week: next_week?.without_types(&hidden),
+    };
+
+    match weeks.next_occurrence(
+        &opts.dish,
+        datectx.first_avail_date,
+        datectx.now.date_naive(),
+    ) {
+        Some((date, meal)) => println!(
+            "{} ({}): {}",
+            date.format(STDOUT_DATE_FMT),
+            meal.typ.name(args.english),
+            meal.text
+        ),
+        None => println!(
+            "\"{}\" is not on the menu of {} until {}",
+            opts.dish,
+            args.mensa,
+            datectx.last_avail_date.format(STDOUT_DATE_FMT)
+        ),
+    }
+
+    Ok(())
+}
+
+async fn handle_find(
+    args: &args::Args,
+    opts: &args::FindOpts,
+    datectx: &DateCtx,
+) -> anyhow::Result<()> {
+    // A single day needs a single week per mensa, otherwise both are searched
+    let day = match (opts.date, &opts.day) {
+        (None, None) => None,
+        (date, day) => Some(resolve_day(
+            date,
+            day.as_ref().unwrap_or(&args::MenuDate::Today),
+            datectx,
+            false,
+        )?),
+    };
+    let weeks = match day {
+        Some((week, _)) => fetch::fetch_all(week, args.english)
+            .await
+            .into_iter()
+            .map(|(mensa, result)| (mensa, vec![result]))
+            .collect::<Vec<_>>(),
+        None => {
+            let english = args.english;
+            fetch::limited(
+                libacmensa::mensa::Mensa::all()
+                    .iter()
+                    .map(|&mensa| async move {
+                        let (this_week, next_week) = fetch::fetch_fortnight(&mensa, english).await;
+                        (mensa, vec![this_week, next_week])
+                    }),
+            )
+            .await
+        }
+    };
+
+    let today = datectx.now.date_naive();
+    let hidden = args.hidden(None);
+    let mut matches = vec![];
+    for (mensa, results) in weeks {
+        for result in results {
+            let week = match result {
+                Ok(week) => week.without_types(&hidden),
+                Err(e) => {
+                    log::warn!("could not fetch {mensa}, skipping it: {e}");
+                    continue;
+                }
+            };
+            for hit in week.search(&opts.dish) {
+                let Some(date) = week.date_of(hit.day) else {
+                    continue;
+                };
+                let wanted = match day {
+                    Some((_, idx)) => hit.day == idx as usize,
+                    None => date >= today,
+                };
+                if wanted {
+                    matches.push((date, mensa, hit.meal.clone()));
+                }
+            }
+        }
+    }
+
+    if matches.is_empty() {
+        anyhow::bail!("no mensa serves a dish matching \"{}\"", opts.dish);
+    }
+    // Day by day, the mensen in their usual order
+    matches.sort_by_key(|(date, _, _)| *date);
+
+    #[cfg(feature = "json")]
+    if opts.json {
+        let matches = matches
+            .iter()
+            .map(|(date, mensa, meal)| {
+                serde_json::json!({
+                    "mensa": mensa.slug(),
+                    "date": date,
+                    "meal": meal,
+                })
+            })
+            .collect::<Vec<_>>();
+        println!("{}", serde_json::to_string_pretty(&matches)?);
+        return Ok(());
+    }
+
+    for (date, mensa, meal) in &matches {
+        let price = meal.price.trim();
+        println!(
+            "{} {} ({}): {}{}{price}",
+            date.format(STDOUT_DATE_FMT),
+            mensa.name(),
+            meal.typ.name(args.english),
+            meal.text.trim(),
+            if price.is_empty() { "" } else { " – " },
+        );
+    }
+
+    Ok(())
+}
+
+async fn handle_favorites(args: &args::Args, opts: &args::FavoritesOpts) -> anyhow::Result<()> {
+    let args::FavoritesAction::Check {
+        from,
+        to,
+        ref keyword,
+        all_mensen,
+        #[cfg(feature = "json")]
+        json,
+    } = opts.action
+    else {
+        let config = config::UserConfig::load()?;
+        if config.favorites.is_empty() {
+            log::warn!("no favorites (add `favorites = [...]` to the config file)");
+        }
+        config
+            .favorites
+            .iter()
+            .for_each(|favorite| println!("{favorite}"));
+        return Ok(());
+    };
+
+    if keyword.is_empty() {
+        anyhow::bail!(
+            "no favorites to look for (add `favorites = [...]` to the config file or use --keyword)"
+        );
+    }
+
+    let mensen = if all_mensen {
+        libacmensa::mensa::Mensa::all().to_vec()
     } else {
-        pretty_print_all(result_day, opts).await;
+        vec![args.mensa]
+    };
+    let english = args.english;
+    let fortnights = fetch::limited(
+        mensen
+            .into_iter()
+            .map(|mensa| async move { (mensa, fetch::fetch_fortnight(&mensa, english).await) }),
+    )
+    .await;
+
+    let from = from.unwrap_or_else(fetch::today);
+    let hidden = args.hidden(None);
+    let mut matches = vec![];
+    let mut fetched = false;
+    for (mensa, (this_week, next_week)) in fortnights {
+        for week in [this_week, next_week] {
+            let week = match week {
+                Ok(week) => week.without_types(&hidden),
+                Err(e) => {
+                    log::error!("could not fetch {mensa}: {e}");
+                    continue;
+                }
+            };
+            fetched = true;
+            for day in week.dated_days() {
+                if day.date < from || to.is_some_and(|to| day.date > to) {
+                    continue;
+                }
+                for meal in &day.day.main_dishes {
+                    if let Some(favorite) = meal.first_match(keyword) {
+                        matches.push((mensa, day.date, favorite, meal.clone()));
+                    }
+                }
+            }
+        }
+    }
+    // Otherwise "no favorites" could not be told apart from "no menu"
+    if !fetched {
+        anyhow::bail!("could not fetch any menu");
     }
 
+    #[cfg(feature = "json")]
+    if json {
+        let matches = matches
+            .iter()
+            .map(|(mensa, date, favorite, meal)| {
+                serde_json::json!({
+                    "favorite": favorite,
+                    "mensa": mensa.slug(),
+                    "date": date,
+                    "meal": meal,
+                })
+            })
+            .collect::<Vec<_>>();
+        println!("{}", serde_json::to_string_pretty(&matches)?);
+    }
+    #[cfg(feature = "json")]
+    let print = !json;
     #[cfg(not(feature = "json"))]
-    pretty_print_all(result_day, opts).await;
+    let print = true;
+
+    if print {
+        for (mensa, date, favorite, meal) in &matches {
+            println!(
+                "{} {} ({}): {} [{favorite}]",
+                date.format(STDOUT_DATE_FMT),
+                mensa.name(),
+                meal.typ.name(args.english),
+                meal.text.trim()
+            );
+        }
+    }
 
+    // Distinct exit status for scripts (errors exit with 1), see `FAVORITES_FOUND`
+    if !matches.is_empty() {
+        std::process::exit(FAVORITES_FOUND);
+    }
     Ok(())
 }
 
+async fn handle_theme(args: &args::Args, opts: &args::ThemeOpts) -> anyhow::Result<()> {
+    if !opts.preview {
+        print!("{}", theme_toml());
+        return Ok(());
+    }
+
+    let menu_opts = args::MenuOpts {
+        english: args.english,
+        prices: true,
+        ..Default::default()
+    };
+    let mut out = Output::default();
+    pretty_print_all(&mut out, preview_day(args.english).view(), &menu_opts);
+    out.flush(None)
+}
+
 #[cfg(feature = "json")]
 async fn handle_export(
     args: &args::Args,
     opts: &args::ExportOpts,
     datectx: &DateCtx,
 ) -> anyhow::Result<()> {
-    let first_avail_date = datectx.first_avail_date;
     let outdir = opts.output.clone().unwrap_or(PathBuf::from("./"));
+    let only = match opts.week {
+        Some(week) => Some(datectx.iso_week(week)?),
+        None => opts.next_week.then_some(Week::Next),
+    };
+
+    if !opts.all_mensen {
+        let weeks = fetch_weeks(args.mensa, args.english, only).await;
+        return export_mensa(args, opts, &args.mensa, &outdir, weeks);
+    }
+
+    let english = args.english;
+    let all_weeks = fetch::limited(
+        Mensa::all()
+            .iter()
+            .copied()
+            .map(|mensa| async move { (mensa, fetch_weeks(mensa, english, only).await) }),
+    )
+    .await;
+
+    let mut exported = vec![];
+    for (mensa, weeks) in all_weeks {
+        let dir = outdir.join(mensa.slug());
+        // No (empty) directories for mensen that could not be fetched at all
+        if weeks.iter().any(|(_, result)| result.is_ok()) {
+            std::fs::create_dir_all(&dir)?;
+        }
+        match export_mensa(args, opts, &mensa, &dir, weeks) {
+            Ok(()) => exported.push(mensa),
+            Err(e) => log::error!("could not export {mensa}: {e}"),
+        }
+    }
+
+    if exported.is_empty() {
+        anyhow::bail!("could not export any mensa");
+    }
+
+    #[cfg(feature = "site")]
+    if opts.format == args::ExportFormat::Html {
+        std::fs::write(
+            outdir.join("index.html"),
+            site::Site::new(args.english)?.index(&exported)?,
+        )?;
+    }
 
-    let get_this = libacmensa::scrape::get(&args.mensa.url_name(), false, args.english).await?;
-    let html_this = get_this.text().await?;
-    let result_this = libacmensa::scrape::scrape_page(&html_this).await?.sorted();
+    Ok(())
+}
 
-    let get_next = libacmensa::scrape::get(&args.mensa.url_name(), true, args.english).await?;
-    let html_next = get_next.text().await?;
-    let result_next = libacmensa::scrape::scrape_page(&html_next).await?.sorted();
+/// Fetches both weeks of `mensa`, or only the week `only`
+#[cfg(feature = "json")]
+async fn fetch_weeks(
+    mensa: Mensa,
+    english: bool,
+    only: Option<Week>,
+) -> Vec<(Week, anyhow::Result<WeekData>)> {
+    match only {
+        Some(week) => vec![(week, fetch::fetch_week(&mensa, week, english).await)],
+        None => {
+            let (this_week, next_week) = fetch::fetch_fortnight(&mensa, english).await;
+            Week::ALL.into_iter().zip([this_week, next_week]).collect()
+        }
+    }
+}
 
-    for i in 0..5 {
-        let date1 = first_avail_date + Duration::days(i);
-        let date2 = first_avail_date + Duration::days(i + 7);
+/// Writes the fetched `weeks` of `mensa` into `outdir` (see `ExportOpts`)
+#[cfg(feature = "json")]
+fn export_mensa(
+    args: &args::Args,
+    opts: &args::ExportOpts,
+    mensa: &Mensa,
+    outdir: &Path,
+    weeks: Vec<(Week, anyhow::Result<WeekData>)>,
+) -> anyhow::Result<()> {
+    let hidden = args.hidden(None);
+    let total = weeks.len();
+    let mut errors = vec![];
+    let mut combined = vec![];
+    for (week, result) in weeks {
+        let result = result.map(|w| w.without_types(&hidden));
+
+        match result {
+            Ok(result) if opts.combined || opts.format != args::ExportFormat::Json => {
+                combined.push(result)
+            }
+            Ok(result) => {
+                write_week(outdir, &result)?;
+                log::info!("wrote {week} week");
+            }
+            Err(e) => {
+                log::warn!("could not fetch {week} week, skipping it: {e}");
+                errors.push(format!("{week}: {e}"));
+            }
+        }
+    }
+
+    if errors.len() == total {
+        anyhow::bail!("could not fetch any week ({})", errors.join("; "));
+    }
+
+    combined.sort_by_key(|w| w.week_start);
+
+    let menu_opts = args::MenuOpts {
+        english: args.english,
+        prices: true,
+        ..Default::default()
+    };
+
+    #[cfg(feature = "site")]
+    if opts.format == args::ExportFormat::Html {
+        let site = site::Site::new(args.english)?;
+        let parent = opts.all_mensen.then_some("../index.html");
+        for (file, page) in site.mensa_pages(mensa, &combined, &menu_opts, parent)? {
+            std::fs::write(outdir.join(file), page)?;
+        }
+        return Ok(());
+    }
+
+    if let Some((mut renderer, extension)) = opts.format.renderer(opts.bool_style) {
+        for day in combined.iter().flat_map(WeekData::dated_days) {
+            renderer.day(&day, &menu_opts)?;
+        }
         std::fs::write(
-            outdir.join(format!("{}", date1.format(JSON_NAME_DATE_FMT))),
-            serde_json::to_string_pretty(&result_this.get_day(i as usize))
-                .unwrap_or("{}".to_string()),
+            outdir.join(format!("{}.{extension}", mensa.slug())),
+            renderer.finish()?,
         )?;
+        return Ok(());
+    }
 
+    #[cfg(feature = "openmensa")]
+    if opts.format == args::ExportFormat::Openmensa {
         std::fs::write(
-            outdir.join(format!("{}", date2.format(JSON_NAME_DATE_FMT))),
-            serde_json::to_string_pretty(&result_next.get_day(i as usize))
-                .unwrap_or("{}".to_string()),
+            outdir.join(format!("{}.xml", mensa.slug())),
+            libacmensa::openmensa::to_feed(&combined, args.english)?,
+        )?;
+        return Ok(());
+    }
+
+    if opts.combined {
+        let export = CombinedExport {
+            schema_version: SchemaVersion::current(),
+            mensa: mensa.slug().to_string(),
+            weeks: combined,
+        };
+        std::fs::write(
+            outdir.join(format!("{}_fortnight.json", export.mensa)),
+            serde_json::to_string_pretty(&export)?,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Writes one JSON file per day of a week into `outdir`
+#[cfg(feature = "json")]
+fn write_week(outdir: &Path, week: &WeekData) -> anyhow::Result<()> {
+    for day in week.dated_days() {
+        std::fs::write(
+            outdir.join(format!("{}", day.date.format(JSON_NAME_DATE_FMT))),
+            serde_json::to_string_pretty(&day)?,
         )?;
     }
 
     Ok(())
 }
 
+#[cfg(feature = "json")]
+async fn handle_diff(args: &args::Args, opts: &args::DiffOpts) -> anyhow::Result<()> {
+    let hidden = args.hidden(None);
+    let (this_week, next_week) = fetch::fetch_fortnight(&args.mensa, args.english).await;
+
+    let mut errors = vec![];
+    let mut current = vec![];
+    for (week, result) in Week::ALL.into_iter().zip([this_week, next_week]) {
+        match result {
+            Ok(result) => current.extend(result.without_types(&hidden).dated_days()),
+            Err(e) => {
+                log::warn!("could not fetch {week} week, skipping it: {e}");
+                errors.push(format!("{week}: {e}"));
+            }
+        }
+    }
+    if errors.len() == 2 {
+        anyhow::bail!("could not fetch either week ({})", errors.join("; "));
+    }
+
+    let previous = match &opts.against {
+        Some(path) => {
+            let mut days = load_export(path)?;
+            // Exports of several mensen hold the others, too
+            days.retain(|day| {
+                day.mensa
+                    .as_deref()
+                    .is_none_or(|slug| slug == args.mensa.slug())
+            });
+            for day in &mut days {
+                day.day
+                    .main_dishes
+                    .retain(|meal| !hidden.contains(&meal.typ));
+            }
+            days
+        }
+        None => {
+            let previous = current
+                .iter()
+                .filter_map(|day| {
+                    snapshot::load(snapshot::Store::Diff, &args.mensa, day.date, args.english).map(
+                        |previous| DatedDay {
+                            day: previous,
+                            ..day.clone()
+                        },
+                    )
+                })
+                .collect::<Vec<_>>();
+            if previous.is_empty() {
+                log::info!("no previous fetch to compare with");
+            }
+            for day in &current {
+                if let Err(e) = snapshot::store(
+                    snapshot::Store::Diff,
+                    &args.mensa,
+                    day.date,
+                    args.english,
+                    &day.day,
+                ) {
+                    log::warn!("could not remember fetched day: {e}");
+                }
+            }
+            previous
+        }
+    };
+
+    let diffs = libacmensa::diff::diff_days(&previous, &current);
+    if opts.json {
+        println!("{}", serde_json::to_string_pretty(&diffs)?);
+        return Ok(());
+    }
+    let mut out = Output::default();
+    print_diff(&mut out, &diffs, args.english);
+    out.flush(None)
+}
+
+/// Days of an earlier export: a directory of exports (day files `YYYYMMDD.json`, `--combined`
+/// files and JSON lines, also in the `<mensa>/` subdirectories written by `--all-mensen`), a
+/// `--combined` file, a file holding a day or an array of days, or JSON lines. Days in a
+/// `<mensa>/` subdirectory that do not name their mensa are taken to be of that one.
+#[cfg(feature = "json")]
+fn load_export(path: &Path) -> anyhow::Result<Vec<DatedDay>> {
+    if path.is_dir() {
+        let mut days = vec![];
+        for entry in std::fs::read_dir(path)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                let mut mensa_days = load_export(&path)?;
+                let mensa = path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .and_then(Mensa::from_slug);
+                if let Some(mensa) = mensa {
+                    for day in &mut mensa_days {
+                        day.mensa.get_or_insert_with(|| mensa.slug().to_string());
+                    }
+                }
+                days.extend(mensa_days);
+            } else if path
+                .extension()
+                .is_some_and(|ext| ext == "json" || ext == "jsonl")
+            {
+                days.extend(load_export(&path)?);
+            }
+        }
+        return Ok(days);
+    }
+
+    // Exports of older versions are migrated to the current layout
+    let text = std::fs::read_to_string(path)?;
+    if let Ok(export) = serde_json::from_str::<CombinedExport>(&text) {
+        return Ok(export
+            .weeks
+            .into_iter()
+            .flat_map(|week| week.migrated().dated_days())
+            .collect());
+    }
+    if let Ok(day) = serde_json::from_str::<DatedDay>(&text) {
+        return Ok(vec![day.migrated()]);
+    }
+    if let Ok(days) = serde_json::from_str::<Vec<DatedDay>>(&text) {
+        return Ok(days.into_iter().map(DatedDay::migrated).collect());
+    }
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).map(DatedDay::migrated))
+        .collect::<Result<_, _>>()
+        .map_err(|e| anyhow::anyhow!("{} is not an export: {e}", path.display()))
+}
+
+#[cfg(feature = "notify")]
+async fn handle_notify(
+    args: &args::Args,
+    opts: &args::NotifyOpts,
+    datectx: &DateCtx,
+) -> anyhow::Result<()> {
+    let (week, idx) = resolve_day(opts.date, &opts.day, datectx, false)?;
+    let day = fetch::fetch_week(&args.mensa, week, args.english)
+        .await?
+        .without_types(&args.hidden(None))
+        .get_dated_day(idx as usize)?;
+
+    let matching = notify::matches(&day, &opts.keyword, &opts.category);
+    let filtered = !opts.keyword.is_empty() || !opts.category.is_empty();
+    if filtered && matching.is_empty() {
+        log::info!("nothing on the menu matches, not notifying");
+        return Ok(());
+    }
+    if day.day.main_dishes.is_empty() {
+        log::info!("nothing is served on {}, not notifying", day.date);
+        return Ok(());
+    }
+
+    let menu_opts = args::MenuOpts {
+        english: args.english,
+        prices: opts.prices,
+        ..Default::default()
+    };
+    let message = notify::Message {
+        title: notify::fill(&opts.title, &args.mensa, &day, &matching, &menu_opts),
+        body: notify::fill(&opts.body, &args.mensa, &day, &matching, &menu_opts),
+    };
+
+    if opts.dry_run {
+        println!("{}\n\n{}", message.title, message.body);
+        return Ok(());
+    }
+    let Some(ref url) = opts.url else {
+        anyhow::bail!(
+            "no url to notify (use --url or `url` in the [notify] section of the config)"
+        );
+    };
+    notify::send(&opts.service, url, opts.token.as_deref(), &message, &day).await
+}
+
+#[cfg(feature = "notify")]
+async fn handle_post(
+    args: &args::Args,
+    opts: &args::PostOpts,
+    datectx: &DateCtx,
+) -> anyhow::Result<()> {
+    let args::PostTarget::Discord(ref opts) = opts.target;
+
+    let (week, idx) = resolve_day(opts.date, &opts.day, datectx, false)?;
+    let day = fetch::fetch_week(&args.mensa, week, args.english)
+        .await?
+        .without_types(&args.hidden(None))
+        .get_dated_day(idx as usize)?;
+    if day.day.main_dishes.is_empty() {
+        log::info!("nothing is served on {}, not posting", day.date);
+        return Ok(());
+    }
+
+    let menu_opts = args::MenuOpts {
+        english: args.english,
+        prices: opts.prices,
+        skip_sides: opts.skip_sides,
+        ..Default::default()
+    };
+    let link = libacmensa::scrape::menu_url(args.mensa.slug(), week, args.english);
+    let message = discord::message(
+        &args.mensa,
+        &day,
+        &menu_opts,
+        Some(&link),
+        opts.username.as_deref(),
+    );
+
+    if opts.dry_run {
+        println!("{}", serde_json::to_string_pretty(&message)?);
+        return Ok(());
+    }
+    let Some(ref url) = opts.webhook_url else {
+        anyhow::bail!(
+            "no webhook to post to (use --webhook-url or `webhook_url` in the [discord] section of the config)"
+        );
+    };
+    discord::send(url, &message).await
+}
+
+#[cfg(feature = "mqtt")]
+async fn handle_mqtt(args: &args::Args, opts: &args::MqttOpts) -> anyhow::Result<()> {
+    use libacmensa::{
+        Week,
+        scrape::{DatedDay, DayData},
+    };
+
+    let mensen = if opts.all_mensen {
+        libacmensa::mensa::Mensa::all().to_vec()
+    } else {
+        vec![args.mensa]
+    };
+    let hidden = args.hidden(None);
+    let english = args.english;
+    let mut discovered = opts.no_discovery;
+
+    loop {
+        let now = chrono::Utc::now().with_timezone(&args.tz());
+        let today = now.date_naive();
+        let fetched = fetch::limited(mensen.iter().copied().map(|mensa| async move {
+            let week = fetch::fetch_week(&mensa, Week::This, english).await;
+            let times = fetch::times(&mensa).await;
+            (mensa, week, times)
+        }))
+        .await;
+
+        let mut publications = vec![];
+        if !discovered {
+            for mensa in &mensen {
+                publications.extend(mqtt::discovery(opts, mensa, english));
+            }
+        }
+        for (mensa, week, times) in fetched {
+            let week = match week {
+                Ok(week) => week.without_types(&hidden),
+                Err(e) => {
+                    log::error!("could not fetch {mensa}: {e}");
+                    continue;
+                }
+            };
+            let open = match times {
+                Ok(times) => Some(times.is_open(now.naive_local())),
+                Err(e) => {
+                    log::warn!("could not fetch the opening times of {mensa}: {e}");
+                    None
+                }
+            };
+            // Nothing is served on weekends
+            let day = week
+                .get_day_by_date(today)
+                .map(DayData::from)
+                .unwrap_or(DayData {
+                    main_dishes: vec![],
+                    side_dishes: vec![],
+                });
+            let day = DatedDay {
+                schema_version: SchemaVersion::current(),
+                mensa: Some(mensa.slug().to_string()),
+                date: today,
+                day,
+            };
+            publications.extend(mqtt::state(opts, &mensa, &day, open)?);
+        }
+
+        let count = publications.len();
+        let result = mqtt::publish(opts, publications).await;
+        let Some(interval) = opts.interval else {
+            return result;
+        };
+        match result {
+            Ok(()) => {
+                discovered = true;
+                log::info!("Published {count} messages");
+            }
+            Err(e) => log::error!("could not publish: {e}"),
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(interval * 60)).await;
+    }
+}
+
+#[cfg(feature = "matrix")]
+async fn handle_matrix(args: &args::Args, opts: &args::MatrixOpts) -> anyhow::Result<()> {
+    use matrix::Command;
+
+    let (Some(homeserver), Some(token)) = (&opts.homeserver, &opts.token) else {
+        anyhow::bail!(
+            "no homeserver or token (use --homeserver/--token or the [matrix] section of the config)"
+        );
+    };
+    let mut client = matrix::Client::new(homeserver, token)?;
+    let user = client.whoami().await?;
+    log::info!("Logged in as {user}");
+
+    let mut rooms = vec![];
+    for room in &opts.room {
+        let id = client.join(room).await?;
+        log::info!("Joined {room} ({id})");
+        rooms.push(id);
+    }
+
+    let menu_opts = args::MenuOpts {
+        english: args.english,
+        prices: opts.prices,
+        ..Default::default()
+    };
+    let hidden = args.hidden(None);
+    let now = || chrono::Utc::now().with_timezone(&args.tz());
+    let closed = if args.english {
+        "Nothing is served on that day."
+    } else {
+        "An diesem Tag gibt es nichts."
+    };
+
+    // Starting after today's posting time does not post right away
+    let mut posted = opts
+        .post_at
+        .filter(|at| now().time() >= *at)
+        .map(|_| now().date_naive());
+    let mut since = client.sync(None).await?.next_batch;
+
+    loop {
+        let today = now();
+        if let Some(at) = opts.post_at
+            && today.time() >= at
+            && posted != Some(today.date_naive())
+        {
+            posted = Some(today.date_naive());
+            let date = today.date_naive();
+            match matrix::menu(&args.mensa, date, date, &menu_opts, &hidden).await {
+                Ok(Some((body, html))) => {
+                    for room in &rooms {
+                        if let Err(e) = client.send(room, &body, &html).await {
+                            log::error!("could not post to {room}: {e}");
+                        }
+                    }
+                }
+                Ok(None) => log::info!("nothing is served on {date}, not posting"),
+                Err(e) => log::error!("could not fetch the menu of {date}: {e}"),
+            }
+        }
+
+        let sync = match client.sync(Some(&since)).await {
+            Ok(sync) => sync,
+            Err(e) => {
+                log::error!("sync failed: {e}");
+                tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+                continue;
+            }
+        };
+        since = sync.next_batch.clone();
+
+        for message in sync.messages() {
+            if message.sender == user {
+                continue;
+            }
+            let today = now().date_naive();
+            let (body, html) = match Command::parse(&message.body, today) {
+                None => continue,
+                Some(Ok(Command::Help)) => {
+                    let help = matrix::help();
+                    let html = html::escape(&help);
+                    (help, html)
+                }
+                Some(Ok(Command::Menu { mensa, date })) => {
+                    let mensa = mensa.unwrap_or(args.mensa);
+                    match matrix::menu(&mensa, date, today, &menu_opts, &hidden).await {
+                        Ok(Some(menu)) => menu,
+                        Ok(None) => (closed.to_string(), closed.to_string()),
+                        Err(e) => {
+                            log::error!("could not fetch the menu of {mensa} on {date}: {e}");
+                            let error = format!("Could not fetch the menu: {e}");
+                            let html = html::escape(&error);
+                            (error, html)
+                        }
+                    }
+                }
+                Some(Err(error)) => {
+                    let html = html::escape(&error);
+                    (error, html)
+                }
+            };
+            if let Err(e) = client.send(&message.room, &body, &html).await {
+                log::error!("could not answer in {}: {e}", message.room);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "archive")]
+async fn handle_archive(args: &args::Args, opts: &args::ArchiveOpts) -> anyhow::Result<()> {
+    use libacmensa::{
+        archive::{Archive, Query},
+        mensa::Mensa,
+    };
+
+    let path = archive_path(opts.db.as_deref())?;
+    let mut db = Archive::open(&path)?;
+
+    match opts.action {
+        args::ArchiveAction::Sync { all_mensen } => {
+            let mensen = if all_mensen {
+                Mensa::all().to_vec()
+            } else {
+                vec![args.mensa]
+            };
+            let english = args.english;
+            let fortnights = fetch::limited(mensen.into_iter().map(|mensa| async move {
+                (mensa, fetch::fetch_fortnight(&mensa, english).await)
+            }))
+            .await;
+
+            let mut stored = 0;
+            for (mensa, (this_week, next_week)) in fortnights {
+                for week in [this_week, next_week] {
+                    match week {
+                        Ok(week) => stored += db.insert_week(&week)?,
+                        Err(e) => log::error!("could not fetch {mensa}: {e}"),
+                    }
+                }
+            }
+            println!("Archived {stored} days in {}", path.display());
+        }
+        args::ArchiveAction::Query {
+            from,
+            to,
+            ref text,
+            all_mensen,
+            prices,
+            ref format,
+            bool_style,
+        } => {
+            let query = Query {
+                mensa: (!all_mensen).then(|| args.mensa.slug().to_string()),
+                from,
+                to,
+                text: text.clone(),
+            };
+            let hidden = args.hidden(None);
+            let menu_opts = args::MenuOpts {
+                english: args.english,
+                prices,
+                ..Default::default()
+            };
+
+            let mut renderer = format.renderer(true, bool_style);
+            let mut current = None;
+            for mut day in db.days(&query)? {
+                if all_mensen && day.mensa != current {
+                    if let Some(mensa) = day.mensa.as_deref().and_then(Mensa::from_slug) {
+                        renderer.mensa(&mensa)?;
+                    }
+                    current = day.mensa.clone();
+                }
+                day.day
+                    .main_dishes
+                    .retain(|meal| !hidden.contains(&meal.typ));
+                renderer.day(&day, &menu_opts)?;
+            }
+            Output::from(renderer.finish()?).flush(None)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "archive")]
+fn handle_stats(args: &args::Args, opts: &args::StatsOpts) -> anyhow::Result<()> {
+    use libacmensa::{
+        archive::{Archive, Query},
+        stats::Stats,
+    };
+
+    let db = Archive::open(&archive_path(opts.db.as_deref())?)?;
+    let hidden = args.hidden(None);
+    let mut days = db.days(&Query {
+        mensa: (!opts.all_mensen).then(|| args.mensa.slug().to_string()),
+        from: opts.from,
+        to: opts.to,
+        text: None,
+    })?;
+    for day in &mut days {
+        day.day
+            .main_dishes
+            .retain(|meal| !hidden.contains(&meal.typ));
+    }
+    let stats = Stats::from_days(&days, opts.top);
+
+    #[cfg(feature = "json")]
+    if opts.json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+    let mut out = Output::default();
+    print_stats(&mut out, &stats, args.english);
+    out.flush(None)
+}
+
+/// Archive database given via `--db`, or the default one
+#[cfg(feature = "archive")]
+fn archive_path(db: Option<&std::path::Path>) -> anyhow::Result<std::path::PathBuf> {
+    match db {
+        Some(path) => Ok(path.to_path_buf()),
+        None => libacmensa::archive::default_path()
+            .ok_or_else(|| anyhow::anyhow!("could not determine data directory")),
+    }
+}
+
+#[cfg_attr(not(feature = "json"), allow(unused_variables))]
+async fn handle_times(args: &args::Args, opts: &args::TimesOpts) -> anyhow::Result<()> {
+    let times = fetch::times(&args.mensa).await?;
+
+    #[cfg(feature = "json")]
+    if opts.json {
+        println!("{}", serde_json::to_string_pretty(&times)?);
+        return Ok(());
+    }
+    let mut out = Output::default();
+    print_times(&mut out, &times, args.english);
+    out.flush(None)
+}
+
+#[cfg_attr(not(feature = "json"), allow(unused_variables))]
+fn handle_mensen(opts: &args::MensenOpts) -> anyhow::Result<()> {
+    let mensen = libacmensa::mensa::all();
+
+    #[cfg(feature = "json")]
+    if opts.json {
+        println!("{}", serde_json::to_string_pretty(mensen)?);
+        return Ok(());
+    }
+    let mut out = Output::default();
+    print_mensen(&mut out, mensen);
+    out.flush(None)
+}
+
+fn handle_config(opts: &args::ConfigOpts) -> anyhow::Result<()> {
+    match opts.action {
+        args::ConfigAction::Init { force } => {
+            let path = config::UserConfig::init(force)?;
+            println!("Wrote {}", path.display());
+        }
+        args::ConfigAction::Path => match config::UserConfig::path() {
+            Some(path) => println!("{}", path.display()),
+            None => anyhow::bail!("could not determine config directory"),
+        },
+    }
+    Ok(())
+}
+
 #[cfg(feature = "json-schema")]
-async fn handle_schema() -> anyhow::Result<()> {
-    println!("{}", WeekData::day_schema()?);
+async fn handle_schema(opts: &args::SchemaOpts) -> anyhow::Result<()> {
+    let schema = match opts.what {
+        args::SchemaKind::Day => WeekData::day_schema()?,
+        args::SchemaKind::Week => WeekData::week_schema()?,
+        args::SchemaKind::Times => libacmensa::times::OpeningTimes::schema()?,
+    };
+    println!("{schema}");
     Ok(())
 }
 
@@ -164,43 +1802,443 @@ async fn main() -> anyhow::Result<()> {
     }
     pretty_env_logger::init();
 
-    let args = args::Args::parse();
-
-    let utc2 = chrono::FixedOffset::east_opt(7200).unwrap();
-    let now = chrono::Utc::now()
-        .with_timezone(&utc2)
-        .with_time(chrono::NaiveTime::from_hms_opt(0, 0, 0).unwrap())
-        .unwrap();
+    let user_config = config::UserConfig::load().unwrap_or_else(|e| {
+        log::warn!("could not load config, ignoring it: {e}");
+        Default::default()
+    });
+    let matches = user_config.apply(args::Args::command()).get_matches();
+    let args = args::Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
 
-    let day_n = now.weekday().number_from_monday();
+    fetch::init(args.cache_ttl(), args.strict, args.tz())?;
+    load_theme(args.theme.as_deref())?;
+    set_colour(args.color.enabled());
 
-    let first_avail_date = now - chrono::Duration::days((day_n - 1) as i64);
-    let last_avail_date =
-        now + chrono::Duration::days((13 - day_n) as i64) - chrono::Duration::milliseconds(1);
+    let datectx = &DateCtx::new(chrono::Utc::now().with_timezone(&args.tz()));
 
-    log::info!("Today is: {}", now.format(STDOUT_DATE_FMT));
+    log::info!("Today is: {}", datectx.now.format(STDOUT_DATE_FMT));
     log::info!(
         "Available date range: {} -- {}",
-        first_avail_date.format(STDOUT_DATE_FMT),
-        last_avail_date.format(STDOUT_DATE_FMT)
+        datectx.first_avail_date.format(STDOUT_DATE_FMT),
+        datectx.last_avail_date.format(STDOUT_DATE_FMT)
     );
 
-    let datectx = &DateCtx {
-        utc2,
-        now,
-        day_n,
-        first_avail_date,
-        last_avail_date,
-    };
-
     match args.verb {
         Verb::Menu(ref menu_opts) => handle_menu(&args, menu_opts, datectx).await?,
+        Verb::All(ref all_opts) => handle_all(&args, all_opts, datectx).await?,
+        Verb::Prefetch(ref prefetch_opts) => handle_prefetch(&args, prefetch_opts).await?,
+        Verb::Ping(ref ping_opts) => handle_ping(ping_opts).await?,
+        Verb::Render(ref render_opts) => handle_render(&args, render_opts, datectx).await?,
+        Verb::Compare(ref compare_opts) => handle_compare(&args, compare_opts).await?,
+        Verb::When(ref when_opts) => handle_when(&args, when_opts, datectx).await?,
+        Verb::Find(ref find_opts) => handle_find(&args, find_opts, datectx).await?,
+        #[cfg(feature = "tui")]
+        Verb::Tui(ref tui_opts) => tui::run(&args, tui_opts, datectx).await?,
+        Verb::Theme(ref theme_opts) => handle_theme(&args, theme_opts).await?,
+        Verb::Favorites(ref favorites_opts) => handle_favorites(&args, favorites_opts).await?,
         #[cfg(feature = "json")]
         Verb::Export(ref menu_opts) => handle_export(&args, menu_opts, datectx).await?,
-        Verb::Times => todo!(),
+        #[cfg(feature = "json")]
+        Verb::Diff(ref diff_opts) => handle_diff(&args, diff_opts).await?,
+        #[cfg(feature = "notify")]
+        Verb::Notify(ref notify_opts) => handle_notify(&args, notify_opts, datectx).await?,
+        #[cfg(feature = "notify")]
+        Verb::Post(ref post_opts) => handle_post(&args, post_opts, datectx).await?,
+        #[cfg(feature = "mqtt")]
+        Verb::Mqtt(ref mqtt_opts) => handle_mqtt(&args, mqtt_opts).await?,
+        #[cfg(feature = "matrix")]
+        Verb::Matrix(ref matrix_opts) => handle_matrix(&args, matrix_opts).await?,
+        #[cfg(feature = "archive")]
+        Verb::Archive(ref archive_opts) => handle_archive(&args, archive_opts).await?,
+        #[cfg(feature = "archive")]
+        Verb::Stats(ref stats_opts) => handle_stats(&args, stats_opts)?,
+        Verb::Times(ref times_opts) => handle_times(&args, times_opts).await?,
+        Verb::Mensen(ref mensen_opts) => handle_mensen(mensen_opts)?,
+        Verb::Config(ref config_opts) => handle_config(config_opts)?,
         #[cfg(feature = "json-schema")]
-        Verb::Schema => handle_schema().await?,
+        Verb::Schema(ref schema_opts) => handle_schema(schema_opts).await?,
     };
 
     return Ok(());
 }
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// Date context at the UTC instant `utc`, in the default time zone
+    fn ctx(utc: &str) -> DateCtx {
+        let now = utc.parse::<DateTime<chrono::Utc>>().unwrap();
+        DateCtx::new(now.with_timezone(&chrono_tz::Europe::Berlin))
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn today_is_local() {
+        // 23:30 CET on a Sunday in winter (a fixed UTC+2 would already be on Monday)
+        let winter = ctx("2026-01-04T22:30:00Z");
+        assert_eq!(winter.now.date_naive(), date("2026-01-04"));
+        assert_eq!(winter.first_avail_date, date("2025-12-29"));
+
+        // 00:30 CEST on a Monday in summer
+        let summer = ctx("2026-07-05T22:30:00Z");
+        assert_eq!(summer.now.date_naive(), date("2026-07-06"));
+        assert_eq!(summer.first_avail_date, date("2026-07-06"));
+    }
+
+    #[test]
+    fn window_across_dst_transitions() {
+        // (UTC instant, first and last available day)
+        let cases = [
+            // Spring forward on Sunday, 29.03.2026 at 01:00 UTC
+            ("2026-03-29T00:59:59Z", "2026-03-23", "2026-04-03"),
+            ("2026-03-29T01:00:00Z", "2026-03-23", "2026-04-03"),
+            ("2026-03-29T21:59:59Z", "2026-03-23", "2026-04-03"),
+            ("2026-03-29T22:00:00Z", "2026-03-30", "2026-04-10"),
+            // Fall back on Sunday, 25.10.2026 at 01:00 UTC (02:30 happens twice)
+            ("2026-10-25T00:30:00Z", "2026-10-19", "2026-10-30"),
+            ("2026-10-25T01:30:00Z", "2026-10-19", "2026-10-30"),
+            ("2026-10-25T22:59:59Z", "2026-10-19", "2026-10-30"),
+            ("2026-10-25T23:00:00Z", "2026-10-26", "2026-11-06"),
+        ];
+        for (utc, first, last) in cases {
+            let ctx = ctx(utc);
+            assert_eq!(ctx.available(), date(first)..=date(last), "at {utc}");
+        }
+    }
+
+    #[test]
+    fn menu_dates() {
+        use chrono::Weekday;
+
+        let parse = |s: &str| s.parse::<MenuDate>();
+        assert_eq!(parse("Heute"), Ok(MenuDate::Today));
+        assert_eq!(parse("next"), Ok(MenuDate::Next));
+        assert_eq!(parse(" morgen "), Ok(MenuDate::Tomorrow));
+        assert_eq!(parse("+2"), Ok(MenuDate::Offset(2)));
+        assert_eq!(parse("-1"), Ok(MenuDate::Offset(-1)));
+        assert_eq!(parse("Freitag"), Ok(MenuDate::Weekday(Weekday::Fri)));
+        assert!(parse("+x").is_err());
+        assert!(parse("someday").is_err());
+
+        // From a Friday
+        let friday = date("2026-10-16");
+        assert_eq!(MenuDate::Today.date(friday), friday);
+        assert_eq!(MenuDate::Next.date(friday), date("2026-10-19"));
+        assert_eq!(MenuDate::Tomorrow.date(friday), date("2026-10-17"));
+        assert_eq!(MenuDate::Weekday(Weekday::Fri).date(friday), friday);
+        assert_eq!(
+            MenuDate::Weekday(Weekday::Mon).date(friday),
+            date("2026-10-19")
+        );
+        assert_eq!(MenuDate::Offset(-4).date(friday), date("2026-10-12"));
+
+        // "next" skips the weekend, but not a weekday
+        assert_eq!(MenuDate::Next.date(date("2026-10-17")), date("2026-10-19"));
+        assert_eq!(MenuDate::Next.date(date("2026-10-18")), date("2026-10-19"));
+        assert_eq!(MenuDate::Next.date(date("2026-10-14")), date("2026-10-15"));
+    }
+
+    #[test]
+    fn resolve_days() {
+        use chrono::Weekday;
+
+        // Friday, 16.10.2026: this week starts on 12.10., next week ends on 23.10.
+        let friday = ctx("2026-10-16T10:00:00Z");
+        let resolve = |day: MenuDate, ctx: &DateCtx, nearest| {
+            resolve_day(None, &day, ctx, nearest).map_err(|e| e.to_string())
+        };
+        assert_eq!(
+            resolve(MenuDate::Today, &friday, false),
+            Ok((Week::This, 4))
+        );
+        assert_eq!(resolve(MenuDate::Next, &friday, false), Ok((Week::Next, 0)));
+        assert_eq!(
+            resolve(MenuDate::Weekday(Weekday::Wed), &friday, false),
+            Ok((Week::Next, 2))
+        );
+        assert_eq!(
+            resolve(MenuDate::Offset(7), &friday, false),
+            Ok((Week::Next, 4))
+        );
+        assert_eq!(
+            resolve(MenuDate::Offset(-3), &friday, false),
+            Ok((Week::This, 1))
+        );
+        assert_eq!(
+            resolve_day(Some(date("2026-10-13")), &MenuDate::Next, &friday, false).unwrap(),
+            (Week::This, 1)
+        );
+
+        // Weekend days fail, or are replaced by the following Monday
+        assert!(matches!(
+            friday.check(date("2026-10-17")),
+            Err(MenuError::Weekend { nearest, .. }) if nearest == date("2026-10-19")
+        ));
+        assert!(resolve(MenuDate::Tomorrow, &friday, false).is_err());
+        assert_eq!(
+            resolve(MenuDate::Tomorrow, &friday, true),
+            Ok((Week::Next, 0))
+        );
+        let saturday = ctx("2026-10-17T10:00:00Z");
+        assert!(resolve(MenuDate::Today, &saturday, false).is_err());
+        assert_eq!(
+            resolve(MenuDate::Today, &saturday, true),
+            Ok((Week::Next, 0))
+        );
+        assert_eq!(
+            resolve(MenuDate::Next, &saturday, false),
+            Ok((Week::Next, 0))
+        );
+
+        // Days outside of the two weeks fail, or are clamped to them
+        assert!(matches!(
+            friday.check(date("2026-10-26")),
+            Err(MenuError::OutOfRange { nearest, .. }) if nearest == date("2026-10-23")
+        ));
+        assert!(matches!(
+            friday.check(date("2026-10-11")),
+            Err(MenuError::OutOfRange { nearest, .. }) if nearest == date("2026-10-12")
+        ));
+        assert_eq!(
+            friday.check(date("2026-10-23")).unwrap(),
+            date("2026-10-23")
+        );
+        assert_eq!(
+            resolve(MenuDate::Offset(14), &friday, true),
+            Ok((Week::Next, 4))
+        );
+        assert_eq!(
+            resolve(MenuDate::Offset(-5), &friday, true),
+            Ok((Week::This, 0))
+        );
+    }
+
+    #[test]
+    fn next_skips_the_weekend() {
+        // `--day next` from Thursday 15.10.2026 to Sunday 18.10.2026: Friday stays in this
+        // week, everything after it is Monday of next week (not Saturday of this one)
+        let next = |utc| resolve_day(None, &MenuDate::Next, &ctx(utc), false).unwrap();
+        assert_eq!(next("2026-10-15T10:00:00Z"), (Week::This, 4));
+        assert_eq!(next("2026-10-16T10:00:00Z"), (Week::Next, 0));
+        assert_eq!(next("2026-10-17T10:00:00Z"), (Week::Next, 0));
+        assert_eq!(next("2026-10-18T10:00:00Z"), (Week::Next, 0));
+        // Late on Friday in Berlin is still Friday
+        assert_eq!(next("2026-10-16T21:30:00Z"), (Week::Next, 0));
+    }
+
+    #[test]
+    fn iso_weeks_at_year_end() {
+        // Wednesday, 30.12.2026 is in week 53 of 2026, the next week is week 1 of 2027
+        let december = ctx("2026-12-30T10:00:00Z");
+        assert!(matches!(december.iso_week(53), Ok(Week::This)));
+        assert!(matches!(december.iso_week(1), Ok(Week::Next)));
+        assert!(matches!(
+            december.iso_week(52),
+            Err(MenuError::UnavailableWeek { available: [53, 1], nearest, .. })
+                if nearest == date("2026-12-28")
+        ));
+        assert!(matches!(
+            december.iso_week(2),
+            Err(MenuError::UnavailableWeek { nearest, .. }) if nearest == date("2027-01-04")
+        ));
+
+        // Wednesday, 06.01.2027: week 53 is the past one, not the one at the end of 2027
+        let january = ctx("2027-01-06T10:00:00Z");
+        assert!(matches!(january.iso_week(1), Ok(Week::This)));
+        assert!(matches!(january.iso_week(2), Ok(Week::Next)));
+        assert!(matches!(
+            january.iso_week(53),
+            Err(MenuError::UnavailableWeek { nearest, .. }) if nearest == date("2027-01-04")
+        ));
+        assert_eq!(resolve_week(53, &january, true).unwrap(), Week::This);
+        assert!(january.iso_week(54).is_err());
+    }
+
+    #[test]
+    fn parse_dates() {
+        use chrono::Datelike;
+
+        assert_eq!(args::parse_date("2026-10-16"), Ok(date("2026-10-16")));
+        assert_eq!(args::parse_date(" 16.10.2026 "), Ok(date("2026-10-16")));
+        assert_eq!(args::parse_date("1.2.2027"), Ok(date("2027-02-01")));
+
+        // Without the year, the current one (in the default time zone)
+        let year = chrono::Utc::now()
+            .with_timezone(&chrono_tz::Europe::Berlin)
+            .year();
+        assert_eq!(
+            args::parse_date("16.10."),
+            Ok(date(&format!("{year}-10-16")))
+        );
+        assert_eq!(args::parse_date("16.10"), args::parse_date("16.10."));
+
+        assert!(args::parse_date("31.02.2026").is_err());
+        assert!(args::parse_date("2026-13-01").is_err());
+        assert!(args::parse_date("morgen").is_err());
+        assert!(args::parse_date("").is_err());
+    }
+
+    #[test]
+    fn categories_are_exact() {
+        use clap::Parser;
+        use libacmensa::meal::MealType;
+
+        let args = args::Args::try_parse_from([
+            "acmensa-cli",
+            "--hide",
+            "BurgerClassics,Burger der Woche",
+            "menu",
+        ])
+        .unwrap();
+        assert_eq!(args.hide, [MealType::BurgerClassics, MealType::BurgerWoche]);
+
+        assert!(args::Args::try_parse_from(["acmensa-cli", "--hide", "Burger", "menu"]).is_err());
+        assert!(
+            args::Args::try_parse_from(["acmensa-cli", "menu", "--only", "Klassiker2"]).is_err()
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn load_export_dirs() {
+        let day = |mensa: Option<&str>, date: &str| DatedDay {
+            schema_version: SchemaVersion::current(),
+            mensa: mensa.map(str::to_string),
+            date: date.parse().unwrap(),
+            day: DayData {
+                main_dishes: vec![],
+                side_dishes: vec![],
+            },
+        };
+        let dir = std::env::temp_dir().join(format!("acmensa-load-export-{}", std::process::id()));
+        let sub = dir.join("academica");
+        std::fs::create_dir_all(&sub).unwrap();
+
+        // Day file without a mensa in the `<mensa>/` directory of `export --all-mensen`
+        let json = serde_json::to_string(&day(None, "2026-10-12")).unwrap();
+        std::fs::write(sub.join("20261012.json"), json).unwrap();
+        // JSON lines of another mensa at the top
+        let lines = [
+            day(Some("vita"), "2026-10-12"),
+            day(Some("vita"), "2026-10-13"),
+        ]
+        .iter()
+        .map(|day| serde_json::to_string(day).unwrap())
+        .collect::<Vec<_>>();
+        std::fs::write(dir.join("vita.jsonl"), lines.join("\n")).unwrap();
+
+        let mut days = load_export(&dir)
+            .unwrap()
+            .into_iter()
+            .map(|day| (day.mensa.unwrap_or_default(), day.date.to_string()))
+            .collect::<Vec<_>>();
+        days.sort();
+        assert_eq!(
+            days,
+            [
+                ("academica".to_string(), "2026-10-12".to_string()),
+                ("vita".to_string(), "2026-10-12".to_string()),
+                ("vita".to_string(), "2026-10-13".to_string()),
+            ]
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn grep_both_languages() {
+        use libacmensa::meal::MealType;
+
+        let week = |mains: &[(MealType, &str)]| {
+            let mut week = WeekData::empty();
+            week.main_dishes[0] = mains
+                .iter()
+                .map(|(typ, text)| MealInfo::new(typ.clone(), *text, ""))
+                .collect();
+            week
+        };
+        let texts = |week: &WeekData| {
+            week.main_dishes[0]
+                .iter()
+                .map(|meal| meal.text.clone())
+                .collect::<Vec<_>>()
+        };
+        let german = week(&[
+            (MealType::Klassiker, "Schnitzel"),
+            (MealType::Vegetarisch, "Gemüsecurry"),
+        ]);
+        let english = week(&[
+            (MealType::Klassiker, "Schnitzel"),
+            (MealType::Vegetarisch, "Vegetable curry"),
+        ]);
+
+        // Hits in the translation select the dish at the same position
+        let found = grep_week(german.clone(), Some(english.clone()), "vegetable");
+        assert_eq!(texts(&found), ["Gemüsecurry"]);
+        let found = grep_week(german.clone(), Some(english), "schnitzel");
+        assert_eq!(texts(&found), ["Schnitzel"]);
+        assert!(found.side_dishes.iter().all(Vec::is_empty));
+
+        // Translations with other dishes are not used, the own language still is
+        let other = week(&[(MealType::Vegetarisch, "Vegetable curry")]);
+        let found = grep_week(german.clone(), Some(other.clone()), "vegetable");
+        assert!(texts(&found).is_empty());
+        let found = grep_week(german, Some(other), "curry");
+        assert_eq!(texts(&found), ["Gemüsecurry"]);
+    }
+
+    #[test]
+    fn watch_marks_changed_dishes() {
+        use libacmensa::{SchemaVersion, meal::MealType, scrape::DayData};
+
+        let days = |mains: Vec<MealInfo>| {
+            vec![(
+                "academica".parse::<Mensa>().unwrap(),
+                vec![DatedDay {
+                    schema_version: SchemaVersion::current(),
+                    mensa: None,
+                    date: date("2026-10-16"),
+                    day: DayData {
+                        main_dishes: mains,
+                        side_dishes: vec![],
+                    },
+                }],
+            )]
+        };
+        let previous = days(vec![
+            MealInfo::new(MealType::Klassiker, "Schnitzel", "2,90 €"),
+            MealInfo::new(MealType::Wok, "Curry", "3,50 €"),
+            MealInfo::new(MealType::Tellergericht, "Eintopf", "2,20 €"),
+        ]);
+        let current = days(vec![
+            MealInfo::new(MealType::Klassiker, "Schnitzel", "3,10 €"),
+            MealInfo::new(MealType::Tellergericht, "Eintopf", "2,20 €"),
+            MealInfo::new(MealType::Vegetarisch, "Salat", "2,00 €"),
+        ]);
+        let opts = args::MenuOpts {
+            prices: true,
+            ..Default::default()
+        };
+        let marked = |previous: Option<&[(Mensa, Vec<DatedDay>)]>| {
+            let mut out = Output::default();
+            print_changes(&mut out, &current, previous, &opts);
+            out.plain()
+                .lines()
+                .map(|line| line.chars().take(2).collect::<String>())
+                .collect::<Vec<_>>()
+        };
+
+        // Nothing is marked on the first fetch
+        assert!(marked(None).iter().all(|gutter| gutter == "  "));
+
+        // Schnitzel (changed price), Eintopf (unchanged), Salat (new), Curry (removed), each
+        // with its price line
+        let gutters = marked(Some(&previous));
+        assert_eq!(gutters, ["▌ ", "▌ ", "  ", "  ", "▌ ", "▌ ", "▌ "]);
+        let mut out = Output::default();
+        print_changes(&mut out, &current, Some(&previous), &opts);
+        assert!(out.plain().ends_with("▌  Curry (Wok)\n"));
+    }
+}
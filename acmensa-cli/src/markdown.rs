@@ -0,0 +1,114 @@
+use std::fmt::Write;
+
+use chrono::{Datelike, NaiveDate};
+use libacmensa::{mensa::Mensa, scrape::DayView};
+
+use crate::{
+    args::MenuOpts,
+    pretty_print::{
+        code_lines, code_text, diet_marker, price_text, shown_mains, shown_sides, weekday_name,
+    },
+};
+
+/// Escapes characters that would break a table cell
+fn cell(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace('|', "\\|")
+}
+
+/// Weekday/date section (used when printing multiple days)
+pub fn day_header(out: &mut String, date: NaiveDate, english: bool) {
+    let _ = writeln!(
+        out,
+        "### {}, {}\n",
+        weekday_name(date.weekday(), english),
+        date.format(crate::STDOUT_DATE_FMT)
+    );
+}
+
+/// Mensa section (used when printing multiple mensen)
+pub fn mensa_header(out: &mut String, mensa: &Mensa) {
+    let _ = writeln!(out, "## {}\n", mensa.name());
+}
+
+/// Renders a day as a table of the main meals followed by a list of the sides.
+/// Respects the same filters and options as the pretty printer.
+pub fn day(out: &mut String, day: DayView<'_>, opts: &MenuOpts) {
+    let (category, dish, price) = if opts.english {
+        ("Category", "Dish", "Price")
+    } else {
+        ("Kategorie", "Gericht", "Preis")
+    };
+
+    let mut header = vec![category, dish];
+    if opts.prices {
+        header.push(price);
+    }
+    if opts.allergens {
+        header.push(if opts.english {
+            "Allergens"
+        } else {
+            "Allergene"
+        });
+    }
+    if opts.additives {
+        header.push(if opts.english {
+            "Additives"
+        } else {
+            "Zusatzstoffe"
+        });
+    }
+    let _ = writeln!(out, "| {} |", header.join(" | "));
+    let _ = writeln!(out, "|{}", "---|".repeat(header.len()));
+
+    for main in shown_mains(&day, opts) {
+        let subtext = main
+            .subtext
+            .trim_start_matches(|c: char| c == '|' || c.is_whitespace());
+        let mut text = format!("**{}**{}", cell(&main.text), diet_marker(main));
+        if !opts.short && !subtext.is_empty() {
+            let _ = write!(text, " ({})", cell(subtext));
+        }
+
+        let mut row = vec![cell(main.typ.name(opts.english)), text];
+        if opts.prices {
+            row.push(cell(&price_text(main, opts)));
+        }
+        if opts.allergens {
+            row.push(cell(&code_text(&main.allergens, opts)));
+        }
+        if opts.additives {
+            row.push(cell(&code_text(&main.additives, opts)));
+        }
+        let _ = writeln!(out, "| {} |", row.join(" | "));
+    }
+
+    let sides = shown_sides(&day, opts);
+    if !sides.is_empty() {
+        out.push('\n');
+    }
+    for side in sides {
+        let alternatives = side
+            .alternatives
+            .iter()
+            .filter(|a| !a.text.trim().is_empty())
+            .map(|a| {
+                let codes = code_lines(&a.allergens, &a.additives, opts);
+                if codes.is_empty() {
+                    a.text.clone()
+                } else {
+                    format!("{} ({})", a.text, codes.join("; "))
+                }
+            })
+            .collect::<Vec<_>>();
+        let _ = writeln!(
+            out,
+            "- **{}:** {}",
+            side.display_name(opts.english),
+            alternatives.join(", ")
+        );
+    }
+    out.push('\n');
+}
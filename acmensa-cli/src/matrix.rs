@@ -0,0 +1,320 @@
+use std::{collections::HashMap, time::Duration};
+
+use chrono::{Datelike, NaiveDate};
+use libacmensa::{SchemaVersion, Week, mensa::Mensa, scrape};
+use reqwest::Url;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    args::{MenuDate, MenuFormat, MenuOpts},
+    pretty_print::{to_plain_line, weekday_name},
+};
+
+/// Prefix of bot commands
+pub const COMMAND: &str = "!mensa";
+
+/// Time the homeserver may hold a `/sync` request open
+const SYNC_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Only message events are of interest, everything else is left out of `/sync`
+const SYNC_FILTER: &str = r#"{"presence":{"types":[]},"account_data":{"types":[]},"room":{"timeline":{"limit":20,"types":["m.room.message"]},"state":{"types":[]},"ephemeral":{"types":[]},"account_data":{"types":[]}}}"#;
+
+/// Minimal Matrix client-server API client (access token login, sync, join, send)
+pub struct Client {
+    http: reqwest::Client,
+    homeserver: Url,
+    token: String,
+    /// Transaction ids must be unique per access token
+    txn_prefix: String,
+    txn: u64,
+}
+
+/// Response of `/sync`, reduced to the messages of joined rooms
+#[derive(Deserialize)]
+pub struct Sync {
+    pub next_batch: String,
+    #[serde(default)]
+    rooms: SyncRooms,
+}
+
+#[derive(Default, Deserialize)]
+struct SyncRooms {
+    #[serde(default)]
+    join: HashMap<String, JoinedRoom>,
+}
+
+#[derive(Deserialize)]
+struct JoinedRoom {
+    #[serde(default)]
+    timeline: Timeline,
+}
+
+#[derive(Default, Deserialize)]
+struct Timeline {
+    #[serde(default)]
+    events: Vec<Event>,
+}
+
+#[derive(Deserialize)]
+struct Event {
+    #[serde(rename = "type")]
+    typ: String,
+    sender: String,
+    #[serde(default)]
+    content: MessageContent,
+}
+
+#[derive(Default, Deserialize)]
+struct MessageContent {
+    #[serde(default)]
+    msgtype: String,
+    #[serde(default)]
+    body: String,
+}
+
+/// A text message received in a room
+#[derive(Debug)]
+pub struct Message {
+    pub room: String,
+    pub sender: String,
+    pub body: String,
+}
+
+impl Sync {
+    /// Text messages of the sync, in timeline order per room
+    pub fn messages(self) -> Vec<Message> {
+        self.rooms
+            .join
+            .into_iter()
+            .flat_map(|(room, joined)| {
+                joined
+                    .timeline
+                    .events
+                    .into_iter()
+                    .filter(|e| e.typ == "m.room.message" && e.content.msgtype == "m.text")
+                    .map(move |e| Message {
+                        room: room.clone(),
+                        sender: e.sender,
+                        body: e.content.body,
+                    })
+            })
+            .collect()
+    }
+}
+
+impl Client {
+    pub fn new(homeserver: &str, token: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            http: reqwest::Client::builder()
+                .timeout(SYNC_TIMEOUT + Duration::from_secs(30))
+                .user_agent(crate::fetch::USER_AGENT)
+                .build()?,
+            homeserver: Url::parse(homeserver)?,
+            token: token.to_string(),
+            txn_prefix: chrono::Utc::now().timestamp_millis().to_string(),
+            txn: 0,
+        })
+    }
+
+    /// `/_matrix/client/v3/<segments>` on the homeserver (segments are percent-encoded)
+    fn url(&self, segments: &[&str]) -> anyhow::Result<Url> {
+        let mut url = self.homeserver.clone();
+        url.path_segments_mut()
+            .map_err(|_| anyhow::anyhow!("{} cannot be a homeserver", self.homeserver))?
+            .pop_if_empty()
+            .extend(["_matrix", "client", "v3"])
+            .extend(segments);
+        Ok(url)
+    }
+
+    async fn send_request(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> anyhow::Result<serde_json::Value> {
+        let response = request.bearer_auth(&self.token).send().await?;
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            anyhow::bail!("homeserver returned {status}: {body}");
+        }
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    /// User id the access token belongs to
+    pub async fn whoami(&self) -> anyhow::Result<String> {
+        let response = self
+            .send_request(self.http.get(self.url(&["account", "whoami"])?))
+            .await?;
+        response["user_id"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| anyhow::anyhow!("whoami did not return a user id"))
+    }
+
+    /// Joins a room by id or alias, returns the room id
+    pub async fn join(&self, room: &str) -> anyhow::Result<String> {
+        let response = self
+            .send_request(self.http.post(self.url(&["join", room])?).body("{}"))
+            .await?;
+        response["room_id"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| anyhow::anyhow!("joining {room} did not return a room id"))
+    }
+
+    /// Waits for new events since `since`. Without `since`, returns immediately with the
+    /// current position only (old messages are not answered).
+    pub async fn sync(&self, since: Option<&str>) -> anyhow::Result<Sync> {
+        let timeout = match since {
+            Some(_) => SYNC_TIMEOUT.as_millis().to_string(),
+            None => "0".to_string(),
+        };
+        let mut query = vec![("filter", SYNC_FILTER), ("timeout", &timeout)];
+        if let Some(since) = since {
+            query.push(("since", since));
+        }
+        let response = self
+            .send_request(self.http.get(self.url(&["sync"])?).query(&query))
+            .await?;
+        Ok(serde_json::from_value(response)?)
+    }
+
+    /// Sends a message with a plain `body` and an HTML `html` version to `room`
+    pub async fn send(&mut self, room: &str, body: &str, html: &str) -> anyhow::Result<()> {
+        self.txn += 1;
+        let txn = format!("acmensa-{}-{}", self.txn_prefix, self.txn);
+        let content = json!({
+            "msgtype": "m.notice",
+            "body": body,
+            "format": "org.matrix.custom.html",
+            "formatted_body": html,
+        });
+        self.send_request(
+            self.http
+                .put(self.url(&["rooms", room, "send", "m.room.message", &txn])?)
+                .body(serde_json::to_string(&content)?),
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+/// A `!mensa [mensa] [day]` command
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    Help,
+    Menu {
+        mensa: Option<Mensa>,
+        date: NaiveDate,
+    },
+}
+
+/// Day of a command word: a `--day` value (e.g. today/heute, tomorrow/morgen, a weekday,
+/// +2) or a date (see `args::parse_date`)
+fn parse_day(word: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match word.parse::<MenuDate>() {
+        Ok(day) => Some(day.date(today)),
+        Err(_) => crate::args::parse_date(word).ok(),
+    }
+}
+
+impl Command {
+    /// Parses a message. `None` if it is not a command; an error message if it is one,
+    /// but cannot be understood.
+    pub fn parse(body: &str, today: NaiveDate) -> Option<Result<Command, String>> {
+        let mut words = body.split_whitespace();
+        if words.next()? != COMMAND {
+            return None;
+        }
+
+        let (mut mensa, mut date) = (None, today);
+        for word in words {
+            if word == "help" || word == "hilfe" {
+                return Some(Ok(Command::Help));
+            }
+            if let Some(day) = parse_day(word, today) {
+                date = day;
+            } else if let Ok(m) = word.parse::<Mensa>() {
+                mensa = Some(m);
+            } else {
+                return Some(Err(format!("Unknown mensa or day: {word}")));
+            }
+        }
+        Some(Ok(Command::Menu { mensa, date }))
+    }
+}
+
+/// Help text of the bot (plain text)
+pub fn help() -> String {
+    format!(
+        "{COMMAND} [mensa] [day] prints the menu. Day: today/heute (default), \
+         tomorrow/morgen, a weekday (e.g. mo, fr), +N days or YYYY-MM-DD. Mensen: {}",
+        Mensa::all()
+            .iter()
+            .map(Mensa::slug)
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+/// Renders the menu of `mensa` on `date` as plain text and HTML, using the renderers of
+/// `menu --format pretty|html`. `Ok(None)` if nothing is served on that day.
+pub async fn menu(
+    mensa: &Mensa,
+    date: NaiveDate,
+    today: NaiveDate,
+    opts: &MenuOpts,
+    hidden: &[libacmensa::meal::MealType],
+) -> anyhow::Result<Option<(String, String)>> {
+    let week = Week::ALL
+        .into_iter()
+        .find(|week| (0..7).contains(&(date - scrape::week_start(*week, today)).num_days()))
+        .ok_or_else(|| anyhow::anyhow!("no menu published for {date}"))?;
+
+    let day = match crate::fetch::fetch_week(mensa, week, opts.english)
+        .await?
+        .without_types(hidden)
+        .get_day_by_date(date)
+    {
+        Ok(view) => scrape::DatedDay {
+            schema_version: SchemaVersion::current(),
+            mensa: Some(mensa.slug().to_string()),
+            date,
+            day: view.into(),
+        },
+        // Weekend
+        Err(_) => return Ok(None),
+    };
+    if day.day.main_dishes.is_empty() {
+        return Ok(None);
+    }
+
+    let heading = format!(
+        "{}, {} {}",
+        mensa.name(),
+        weekday_name(date.weekday(), opts.english),
+        date.format(crate::STDOUT_DATE_FMT)
+    );
+
+    let mut pretty = MenuFormat::Pretty.renderer(false, Default::default());
+    pretty.day(&day, opts)?;
+    let plain = pretty
+        .finish()?
+        .lines()
+        .map(to_plain_line)
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut html = MenuFormat::Html.renderer(false, Default::default());
+    html.day(&day, opts)?;
+
+    Ok(Some((
+        format!("{heading}\n{plain}"),
+        format!(
+            "<b>{}</b>\n{}",
+            crate::html::escape(&heading),
+            html.finish()?
+        ),
+    )))
+}
@@ -0,0 +1,129 @@
+use std::time::Duration;
+
+use libacmensa::{mensa::Mensa, scrape::DatedDay};
+use rumqttc::{AsyncClient, Event, Incoming, MqttOptions, Outgoing, QoS};
+use serde_json::json;
+
+use crate::args::MqttOpts;
+
+/// Largest packet sent or accepted (menus with long descriptions exceed the default 10 KiB)
+const MAX_PACKET_SIZE: usize = 256 * 1024;
+
+/// State payloads of the `open` topic
+pub const OPEN: &str = "open";
+pub const CLOSED: &str = "closed";
+
+/// A retained message
+#[derive(Debug)]
+pub struct Publication {
+    pub topic: String,
+    pub payload: String,
+}
+
+/// Topic of a mensa below the configured prefix (e.g. `acmensa/academica/menu`)
+fn topic(opts: &MqttOpts, mensa: &Mensa, name: &str) -> String {
+    format!("{}/{}/{name}", opts.prefix, mensa.slug())
+}
+
+/// Messages of a mensa: the menu of `day` as JSON (`<prefix>/<mensa>/menu`) and, if the
+/// opening times are known, `open`/`closed` (`<prefix>/<mensa>/open`)
+pub fn state(
+    opts: &MqttOpts,
+    mensa: &Mensa,
+    day: &DatedDay,
+    open: Option<bool>,
+) -> anyhow::Result<Vec<Publication>> {
+    let mut publications = vec![Publication {
+        topic: topic(opts, mensa, "menu"),
+        payload: serde_json::to_string(day)?,
+    }];
+    if let Some(open) = open {
+        publications.push(Publication {
+            topic: topic(opts, mensa, "open"),
+            payload: if open { OPEN } else { CLOSED }.to_string(),
+        });
+    }
+    Ok(publications)
+}
+
+/// Home Assistant discovery messages of a mensa: a sensor holding the main meals (the whole
+/// menu in its attributes) and a binary sensor for `open`, grouped into a device per mensa
+pub fn discovery(opts: &MqttOpts, mensa: &Mensa, english: bool) -> Vec<Publication> {
+    let id = format!("acmensa_{}", mensa.slug());
+    let device = json!({
+        "identifiers": [id],
+        "name": mensa.name(),
+        "manufacturer": "Studierendenwerk Aachen",
+        "model": "acmensa-cli",
+    });
+
+    let menu = json!({
+        "name": if english { "Menu" } else { "Speiseplan" },
+        "unique_id": format!("{id}_menu"),
+        "state_topic": topic(opts, mensa, "menu"),
+        // States are limited to 255 characters, the full menu is in the attributes
+        "value_template":
+            "{{ value_json.main_dishes | map(attribute='text') | join(', ') | truncate(255) }}",
+        "json_attributes_topic": topic(opts, mensa, "menu"),
+        "icon": "mdi:silverware-fork-knife",
+        "device": device,
+    });
+    let open = json!({
+        "name": if english { "Open" } else { "Geöffnet" },
+        "unique_id": format!("{id}_open"),
+        "state_topic": topic(opts, mensa, "open"),
+        "payload_on": OPEN,
+        "payload_off": CLOSED,
+        "icon": "mdi:store-clock",
+        "device": device,
+    });
+
+    [("sensor", "menu", menu), ("binary_sensor", "open", open)]
+        .into_iter()
+        .map(|(component, name, config)| Publication {
+            topic: format!("{}/{component}/{id}/{name}/config", opts.discovery_prefix),
+            payload: config.to_string(),
+        })
+        .collect()
+}
+
+/// Connects to the broker, publishes `publications` (retained) and disconnects again
+pub async fn publish(opts: &MqttOpts, publications: Vec<Publication>) -> anyhow::Result<()> {
+    let mut options = MqttOptions::new(&opts.client_id, &opts.host, opts.port);
+    options.set_keep_alive(Duration::from_secs(30));
+    options.set_max_packet_size(MAX_PACKET_SIZE, MAX_PACKET_SIZE);
+    if let Some(ref username) = opts.username {
+        options.set_credentials(username, opts.password.clone().unwrap_or_default());
+    }
+
+    // Everything is queued up front, the event loop then does the actual network I/O
+    let total = publications.len();
+    let (client, mut eventloop) = AsyncClient::new(options, total + 1);
+    for Publication { topic, payload } in publications {
+        client
+            .publish(topic, QoS::AtLeastOnce, true, payload)
+            .await?;
+    }
+    if total == 0 {
+        client.disconnect().await?;
+    }
+
+    // Disconnecting right away could drop messages the broker has not acknowledged yet
+    let mut acked = 0;
+    loop {
+        let event = eventloop
+            .poll()
+            .await
+            .map_err(|e| anyhow::anyhow!("{}:{}: {e}", opts.host, opts.port))?;
+        match event {
+            Event::Incoming(Incoming::PubAck(_)) => {
+                acked += 1;
+                if acked == total {
+                    client.disconnect().await?;
+                }
+            }
+            Event::Outgoing(Outgoing::Disconnect) => return Ok(()),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use std::time::Duration;
+
+use chrono::Datelike;
+use libacmensa::{
+    meal::{MealInfo, MealType},
+    mensa::Mensa,
+    scrape::DatedDay,
+};
+use serde::Serialize;
+
+use crate::{
+    args::{MenuOpts, NotifyService},
+    pretty_print::{headline, price_text, weekday_name},
+};
+
+/// Default title of a notification (see `fill` for the placeholders)
+pub const DEFAULT_TITLE: &str = "{mensa}, {weekday} {date}";
+/// Default body of a notification (see `fill` for the placeholders)
+pub const DEFAULT_BODY: &str = "{meals}";
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A rendered notification
+#[derive(Debug)]
+pub struct Message {
+    pub title: String,
+    pub body: String,
+}
+
+/// Meals that match any of `keywords` (see `MealInfo::matches`) or `categories`
+pub fn matches<'a>(
+    day: &'a DatedDay,
+    keywords: &[String],
+    categories: &[MealType],
+) -> Vec<&'a MealInfo> {
+    day.day
+        .main_dishes
+        .iter()
+        .filter(|meal| categories.contains(&meal.typ) || keywords.iter().any(|k| meal.matches(k)))
+        .collect()
+}
+
+/// Replaces the placeholders in `template`:
+/// `{mensa}`, `{date}` (DD.MM.YYYY), `{weekday}`, `{count}` (number of main meals),
+/// `{meals}` (one line per main meal) and `{matches}` (one line per matching meal).
+/// `\n` is replaced by a line break.
+pub fn fill(
+    template: &str,
+    mensa: &Mensa,
+    day: &DatedDay,
+    matching: &[&MealInfo],
+    opts: &MenuOpts,
+) -> String {
+    let lines = |meals: &mut dyn Iterator<Item = &MealInfo>| {
+        meals
+            .map(|meal| {
+                let line = headline(meal, opts).trim().to_string();
+                match price_text(meal, opts) {
+                    price if opts.prices && !price.is_empty() => format!("{line} ({price})"),
+                    _ => line,
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    template
+        .replace("\\n", "\n")
+        .replace("{mensa}", mensa.name())
+        .replace(
+            "{date}",
+            &day.date.format(crate::STDOUT_DATE_FMT).to_string(),
+        )
+        .replace("{weekday}", weekday_name(day.date.weekday(), opts.english))
+        .replace("{count}", &day.day.main_dishes.len().to_string())
+        .replace("{meals}", &lines(&mut day.day.main_dishes.iter()))
+        .replace("{matches}", &lines(&mut matching.iter().copied()))
+}
+
+/// Body of a `post` notification
+#[derive(Serialize)]
+struct Webhook<'a> {
+    title: &'a str,
+    message: &'a str,
+    #[serde(flatten)]
+    day: &'a DatedDay,
+}
+
+/// Sends `message` to `url`:
+/// - ntfy: `url` is the topic (e.g. `https://ntfy.sh/my-mensa`), the token an access token
+/// - Gotify: `url` is the `/message` endpoint, the token an application token
+/// - post: `title`, `message` and the day as JSON
+pub async fn send(
+    service: &NotifyService,
+    url: &str,
+    token: Option<&str>,
+    message: &Message,
+    day: &DatedDay,
+) -> anyhow::Result<()> {
+    let client = reqwest::Client::builder()
+        .timeout(TIMEOUT)
+        .user_agent(crate::fetch::USER_AGENT)
+        .build()?;
+
+    let request = match service {
+        NotifyService::Ntfy => {
+            let request = client
+                .post(url)
+                .query(&[("title", &message.title)])
+                .body(message.body.clone());
+            match token {
+                Some(token) => request.bearer_auth(token),
+                None => request,
+            }
+        }
+        NotifyService::Gotify => {
+            let request = client
+                .post(url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(serde_json::to_string(&serde_json::json!({
+                    "title": message.title,
+                    "message": message.body,
+                }))?);
+            match token {
+                Some(token) => request.header("X-Gotify-Key", token),
+                None => request,
+            }
+        }
+        NotifyService::Post => {
+            let request = client
+                .post(url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(serde_json::to_string(&Webhook {
+                    title: &message.title,
+                    message: &message.body,
+                    day,
+                })?);
+            match token {
+                Some(token) => request.bearer_auth(token),
+                None => request,
+            }
+        }
+    };
+
+    request.send().await?.error_for_status()?;
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use std::{io::Read, path::Path};
+
+use chrono::{Duration, NaiveDate};
+use libacmensa::{
+    Week,
+    day::DayData,
+    mensa::Mensa,
+    scrape::{self, DatedDay, WeekData},
+};
+
+use crate::{args::MenuOpts, fetch};
+
+/// Days exported into `--data-dir` (see `load_export`), `None` without a data directory
+pub fn exported(opts: &MenuOpts) -> anyhow::Result<Option<Vec<DatedDay>>> {
+    #[cfg(feature = "json")]
+    if let Some(ref dir) = opts.data_dir {
+        return crate::load_export(dir).map(Some);
+    }
+
+    #[cfg(not(feature = "json"))]
+    let _ = opts;
+    Ok(None)
+}
+
+/// Days of `mensa` that are known without network access: the ones of `exported` (see
+/// `exported`), or the ones on the cached pages
+pub async fn days(mensa: &Mensa, opts: &MenuOpts, exported: Option<&[DatedDay]>) -> Vec<DatedDay> {
+    if let Some(exported) = exported {
+        // Days without a mensa come from exports of a single one
+        return exported
+            .iter()
+            .filter(|day| day.mensa.as_deref().is_none_or(|slug| slug == mensa.slug()))
+            .cloned()
+            .collect();
+    }
+
+    let mut days = vec![];
+    for week in Week::ALL {
+        match fetch::cached_week(mensa, week, opts.english).await {
+            Ok(week) => days.extend(week.dated_days()),
+            Err(e) => log::debug!("{e}"),
+        }
+    }
+    days
+}
+
+/// The week of `mensa` starting on `monday`, put together from `days`. Days that are not
+/// known are empty, fails if none of them is.
+pub fn week(mensa: &Mensa, monday: NaiveDate, days: &[DatedDay]) -> anyhow::Result<WeekData> {
+    let mut known = false;
+    let week = std::array::from_fn(|n| {
+        let date = monday + Duration::days(n as i64);
+        match days.iter().find(|day| day.date == date) {
+            Some(day) => {
+                known = true;
+                day.day.clone()
+            }
+            None => DayData {
+                main_dishes: vec![],
+                side_dishes: vec![],
+            },
+        }
+    });
+    if !known {
+        anyhow::bail!(
+            "no offline data of {mensa} for the week of {}",
+            monday.format(crate::STDOUT_DATE_FMT)
+        );
+    }
+
+    Ok(WeekData::from_days(week)
+        .with_week_start(monday)
+        .with_mensa(mensa.slug()))
+}
+
+/// Week on a saved menu page of `mensa` (`-` reads it from stdin, see `--from-file`)
+pub async fn page(path: &Path, mensa: &Mensa) -> anyhow::Result<WeekData> {
+    let bytes = if path == Path::new("-") {
+        let mut bytes = vec![];
+        std::io::stdin().read_to_end(&mut bytes)?;
+        bytes
+    } else {
+        std::fs::read(path).map_err(|e| anyhow::anyhow!("{}: {e}", path.display()))?
+    };
+    fetch::parse_page(mensa, &scrape::decode_page(&bytes, None)).await
+}
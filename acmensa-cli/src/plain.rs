@@ -0,0 +1,183 @@
+//! Plain-text rendering for screen readers (`--format plain`)
+
+use std::fmt::Write;
+
+use chrono::{Datelike, NaiveDate};
+use libacmensa::{
+    meal::{Diet, MealInfo},
+    mensa::Mensa,
+    scrape::DayView,
+};
+
+use crate::{
+    args::MenuOpts,
+    pretty_print::{code_lines, price_text, shown_mains, shown_sides, weekday_name},
+};
+
+/// "ja"/"yes" or "nein"/"no"
+fn yes_no(value: bool, english: bool) -> &'static str {
+    match (value, english) {
+        (true, true) => "yes",
+        (false, true) => "no",
+        (true, false) => "ja",
+        (false, false) => "nein",
+    }
+}
+
+/// Collapses whitespace and drops a trailing period, so that fields can be joined by ". "
+fn field(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_end_matches('.')
+        .to_string()
+}
+
+/// Weekday/date line (used when printing multiple days)
+pub fn day_header(out: &mut String, date: NaiveDate, english: bool) {
+    let _ = writeln!(
+        out,
+        "\n{}, {}:",
+        weekday_name(date.weekday(), english),
+        date.format(crate::STDOUT_DATE_FMT)
+    );
+}
+
+/// Mensa line (used when printing multiple mensen)
+pub fn mensa_header(out: &mut String, mensa: &Mensa) {
+    let _ = writeln!(out, "\nMensa: {}.", mensa.name());
+}
+
+/// Labelled fields of a main meal, e.g. "Kategorie: Klassiker", "Vegan: nein"
+fn main_fields(main: &MealInfo, opts: &MenuOpts) -> Vec<String> {
+    let english = opts.english;
+    let (category, dish, description, price) = if english {
+        ("Category", "Dish", "Description", "Price")
+    } else {
+        ("Kategorie", "Gericht", "Beschreibung", "Preis")
+    };
+
+    let mut fields = vec![
+        format!("{category}: {}", field(main.typ.name(english))),
+        format!("{dish}: {}", field(&main.text)),
+    ];
+    if opts.short {
+        return fields;
+    }
+
+    let subtext = main
+        .subtext
+        .trim_start_matches(|c: char| c == '|' || c.is_whitespace());
+    if !subtext.is_empty() {
+        fields.push(format!("{description}: {}", field(subtext)));
+    }
+
+    let (vegan, vegetarian) = match main.diet {
+        Diet::Unknown => {
+            let unknown = if english { "unknown" } else { "unbekannt" };
+            (unknown, unknown)
+        }
+        diet => (
+            yes_no(diet.is_vegan(), english),
+            yes_no(diet.is_vegetarian(), english),
+        ),
+    };
+    fields.push(format!("Vegan: {vegan}"));
+    fields.push(format!(
+        "{}: {vegetarian}",
+        if english { "Vegetarian" } else { "Vegetarisch" }
+    ));
+
+    if opts.prices {
+        fields.push(format!("{price}: {}", field(&price_text(main, opts))));
+    }
+    fields.extend(
+        code_lines(&main.allergens, &main.additives, opts)
+            .iter()
+            .map(|line| field(line)),
+    );
+    fields
+}
+
+/// Renders a day for screen readers: a sentence of labelled fields per meal and side,
+/// without emojis, colours or layout characters. Respects the same filters and options as
+/// the pretty printer.
+pub fn day(out: &mut String, day: DayView<'_>, opts: &MenuOpts) {
+    let mains = shown_mains(&day, opts);
+    if mains.is_empty() {
+        let _ = writeln!(
+            out,
+            "{}",
+            if opts.english {
+                "No meals."
+            } else {
+                "Keine Gerichte."
+            }
+        );
+    }
+    for main in mains {
+        let _ = writeln!(out, "{}.", main_fields(main, opts).join(". "));
+    }
+
+    let or = if opts.english { " or " } else { " oder " };
+    for side in shown_sides(&day, opts) {
+        let alternatives = side
+            .alternatives
+            .iter()
+            .filter(|a| !a.text.trim().is_empty())
+            .map(|a| {
+                let codes = code_lines(&a.allergens, &a.additives, opts);
+                if codes.is_empty() {
+                    field(&a.text)
+                } else {
+                    format!("{} ({})", field(&a.text), codes.join("; "))
+                }
+            })
+            .collect::<Vec<_>>();
+        // Nothing to read out
+        if alternatives.is_empty() {
+            continue;
+        }
+        let _ = writeln!(
+            out,
+            "{}: {}.",
+            field(side.display_name(opts.english)),
+            alternatives.join(or)
+        );
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::pretty_print::preview_day;
+
+    #[test]
+    fn plain_day() {
+        let mut preview = preview_day(true);
+        // Sides without any text are left out
+        preview.side_dishes[0].alternatives[0].text = " ".into();
+        let opts = MenuOpts {
+            english: true,
+            prices: true,
+            ..Default::default()
+        };
+        let mut out = String::new();
+        day(&mut out, preview.view(), &opts);
+
+        assert_eq!(
+            out.lines().next(),
+            Some(
+                "Category: Classics. Dish: Classics. Description: Sauce. Vegan: no. \
+                 Vegetarian: no. Price: 2,50 €."
+            )
+        );
+        // No emojis, box-drawing characters or escape sequences
+        assert!(
+            out.chars()
+                .all(|c| c.is_alphanumeric() || " .,:()€\n".contains(c))
+        );
+        assert!(!out.contains("Main side dish"));
+        assert!(out.contains("Secondary side dish: Lorem ipsum."));
+    }
+}
@@ -1,24 +1,69 @@
-use std::collections::HashMap;
+use std::{borrow::Cow, collections::HashMap, path::Path, sync::OnceLock};
 
+use chrono::{Datelike, NaiveDate};
 use lazy_static::lazy_static;
 use libacmensa::{
-    meal::{MealInfo, MealType, SideInfo},
-    scrape::DayView,
+    meal::{
+        AllergenList, Diet, MealInfo, MealType, SideAlternative, SideInfo, SideType, format_price,
+    },
+    mensa::{Mensa, MensaInfo},
+    scrape::{DatedDay, DayData, DayView},
+    times::{OpeningHours, OpeningTimes},
 };
 use serde::{Deserialize, Serialize};
+use unicode_width::UnicodeWidthStr;
 
-use crate::args::MenuOpts;
+use crate::{
+    args::{ColorChoice, MenuOpts},
+    config::config_dir,
+};
+
+pub static CONFIG_TOML: &str = include_str!("../../res/pretty-print.toml");
+
+/// File in the config directory holding a user theme
+const THEME_FILE: &str = "theme.toml";
+
+/// Theme loaded by `load_theme`. The embedded `CONFIG_TOML` is used if unset.
+static THEME_TOML: OnceLock<String> = OnceLock::new();
 
-static CONFIG_TOML: &str = include_str!("../../res/pretty-print.toml");
+/// Whether `Output::flush` prints colours, set by `set_colour`. Falls back to `--color auto`
+/// if unset.
+static COLOUR: OnceLock<bool> = OnceLock::new();
 
 const RST: &str = "\x1b[0m";
 
+/// Columns a tab advances (assuming the cursor is at the start of a line)
+const TAB_WIDTH: usize = 8;
+
+const WEEKDAYS_DE: [&str; 7] = [
+    "Montag",
+    "Dienstag",
+    "Mittwoch",
+    "Donnerstag",
+    "Freitag",
+    "Samstag",
+    "Sonntag",
+];
+const WEEKDAYS_EN: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct FormatSet {
     pub meal_map: Vec<FormatMealMap>,
     pub meal_def: FormatMeal,
     pub meal_subtext_colour: String,
     pub side_style: StyleSide,
+    /// Order in which side types are printed. Types not listed here are printed
+    /// afterwards, in their default order (see `SideType::display_order`).
+    #[serde(default)]
+    pub side_order: Vec<SideType>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -46,11 +91,23 @@ pub struct StyleSide {
     pub subtext_colour: String,
 }
 
+/// Appends a line to an `Output` (like `println!`)
+macro_rules! outln {
+    ($out:expr) => {
+        $out.line("")
+    };
+    ($out:expr, $($arg:tt)*) => {
+        $out.line(format!($($arg)*))
+    };
+}
+
 lazy_static! {
-    pub static ref CONFIG: FormatSet = toml::from_str(CONFIG_TOML).unwrap();
+    pub static ref CONFIG: FormatSet = toml::from_str(theme_toml()).unwrap();
     pub static ref FORMAT_MEAL_DEFAULT: &'static FormatMeal = &CONFIG.meal_def;
     pub static ref FORMAT_SIDE: &'static StyleSide = &CONFIG.side_style;
     pub static ref SUBTEXT_COLOUR: &'static str = &CONFIG.meal_subtext_colour;
+    /// Width of the terminal, if stdout is one
+    static ref TERM_WIDTH: Option<usize> = term_width();
     pub static ref FORMAT: HashMap<MealType, FormatMeal> = {
         CONFIG
             .meal_map
@@ -60,105 +117,950 @@ lazy_static! {
     };
 }
 
-/// Prints a single main meal info
-fn print_main(main: &MealInfo, opts: &MenuOpts) {
-    let fmt = FORMAT.get(&main.typ).unwrap_or(&FORMAT_MEAL_DEFAULT);
-    let fmt_style = if main.vegan && fmt.alt_veg.is_some() {
-        unsafe { &fmt.alt_veg.clone().unwrap_unchecked() }
-    } else {
-        &fmt.main
+/// Source of the active theme
+pub fn theme_toml() -> &'static str {
+    THEME_TOML.get().map(String::as_str).unwrap_or(CONFIG_TOML)
+}
+
+/// Loads the theme from `path`, or from `theme.toml` in the config directory if it exists.
+/// Falls back to the embedded theme otherwise. Must be called before anything is printed.
+pub fn load_theme(path: Option<&Path>) -> anyhow::Result<()> {
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => match config_dir().map(|dir| dir.join(THEME_FILE)) {
+            Some(path) if path.exists() => path,
+            _ => return Ok(()),
+        },
+    };
+
+    log::info!("Loading theme from {}", path.display());
+    let theme = std::fs::read_to_string(&path)
+        .map_err(|e| anyhow::anyhow!("could not read theme {}: {e}", path.display()))?;
+    toml::from_str::<FormatSet>(&theme)
+        .map_err(|e| anyhow::anyhow!("invalid theme {}: {e}", path.display()))?;
+    let _ = THEME_TOML.set(theme);
+    Ok(())
+}
+
+/// Enables or disables colours in everything printed via `Output::flush` (see `--color`).
+/// Must be called before anything is printed.
+pub fn set_colour(enabled: bool) {
+    let _ = COLOUR.set(enabled);
+}
+
+/// Whether colours are printed (see `set_colour`)
+pub fn colour_enabled() -> bool {
+    *COLOUR.get_or_init(|| ColorChoice::Auto.enabled())
+}
+
+#[cfg(feature = "wrap")]
+fn term_width() -> Option<usize> {
+    terminal_size::terminal_size().map(|(terminal_size::Width(w), _)| w as usize)
+}
+
+#[cfg(not(feature = "wrap"))]
+fn term_width() -> Option<usize> {
+    None
+}
+
+/// Wraps `text` at the terminal width (no-op if unknown, e.g. when piped). `indent` is
+/// the number of columns already taken on the first line, continuation lines are prefixed
+/// with `hang`.
+fn wrap(text: &str, indent: usize, hang: &str) -> String {
+    let Some(width) = *TERM_WIDTH else {
+        return text.to_string();
+    };
+    let hang_width = hang
+        .chars()
+        .map(|c| if c == '\t' { TAB_WIDTH } else { 1 })
+        .sum::<usize>();
+
+    let mut res = String::new();
+    let (mut col, mut line_empty) = (indent, true);
+    for word in text.split_whitespace() {
+        let len = word.width();
+        if !line_empty && col + 1 + len > width {
+            res.push('\n');
+            res.push_str(hang);
+            col = hang_width;
+        } else if !line_empty {
+            res.push(' ');
+            col += 1;
+        }
+
+        res.push_str(word);
+        col += len;
+        line_empty = false;
+    }
+
+    res
+}
+
+/// Rendered (coloured) output, printed in one go by `Output::flush`
+#[derive(Clone, Debug, Default)]
+pub struct Output(String);
+
+impl Output {
+    fn line(&mut self, line: impl AsRef<str>) {
+        self.0.push_str(line.as_ref());
+        self.0.push('\n');
+    }
+
+    /// Rendered lines (still containing colours)
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        self.0.lines()
+    }
+
+    /// The output as printed, with or without colours
+    pub fn text(&self, colour: bool) -> Cow<'_, str> {
+        if colour {
+            Cow::Borrowed(&self.0)
+        } else {
+            Cow::Owned(to_plain_line(&self.0))
+        }
+    }
+
+    /// Rendered lines without colours
+    pub fn plain(&self) -> String {
+        let plain = self.0.lines().map(to_plain_line).collect::<Vec<_>>();
+        plain.join("\n") + "\n"
+    }
+
+    /// Prints the output to stdout (without colours if disabled, see `set_colour`) and, if
+    /// `tee` is given, writes a copy without colours to that file.
+    pub fn flush(self, tee: Option<&Path>) -> anyhow::Result<()> {
+        print!("{}", self.text(colour_enabled()));
+        if let Some(path) = tee {
+            std::fs::write(path, self.plain())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl From<Output> for String {
+    fn from(out: Output) -> Self {
+        out.0
+    }
+}
+
+impl From<String> for Output {
+    fn from(text: String) -> Self {
+        Self(text)
+    }
+}
+
+/// Removes ANSI escape sequences (colours/styles) from a line (or several, line breaks are
+/// kept)
+pub fn to_plain_line(line: &str) -> String {
+    let mut plain = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            plain.push(c);
+            continue;
+        }
+
+        // CSI sequences (`ESC [ ... <final byte>`), final byte is in @..~
+        if chars.next() == Some('[') {
+            chars.find(|c| ('@'..='~').contains(c));
+        }
+    }
+
+    plain
+}
+
+/// Appends `lines` to `out` behind a gutter: a bar in the colour `mark` (an SGR code), or
+/// blanks to keep the lines aligned. Empty lines are left as they are.
+fn with_gutter(out: &mut Output, lines: Output, mark: Option<&str>) {
+    for line in lines.lines() {
+        match mark {
+            _ if to_plain_line(line).trim().is_empty() => outln!(out, "{line}"),
+            Some(colour) => outln!(out, "\x1b[1;{colour}m▌{RST} {line}"),
+            None => outln!(out, "  {line}"),
+        }
+    }
+}
+
+/// Prints the days like the pretty format, marking the main dishes that were added
+/// (green) or changed (yellow) since `previous`, the days of the previous fetch, and
+/// listing the removed ones (red). Dishes are matched by `MealInfo::dish_hash` (see
+/// `DayData::diff`), sides by their contents. Days that were not fetched before are left
+/// unmarked (see `menu --watch`).
+pub fn print_changes(
+    out: &mut Output,
+    days: &[(Mensa, Vec<DatedDay>)],
+    previous: Option<&[(Mensa, Vec<DatedDay>)]>,
+    opts: &MenuOpts,
+) {
+    for (mensa, mensa_days) in days {
+        let mut header = Output::default();
+        if opts.all_mensen {
+            print_mensa_header(&mut header, mensa);
+        }
+        with_gutter(out, header, None);
+
+        for day in mensa_days {
+            if opts.whole_week() {
+                let mut header = Output::default();
+                print_day_header(&mut header, day.date, opts.english);
+                with_gutter(out, header, None);
+            }
+            let before = previous
+                .into_iter()
+                .flatten()
+                .filter(|(before, _)| before == mensa)
+                .flat_map(|(_, days)| days)
+                .find(|before| before.date == day.date);
+            print_day_changes(out, day.day.view(), before.map(|before| &before.day), opts);
+        }
+    }
+}
+
+/// Prints a day for `print_changes`
+fn print_day_changes(
+    out: &mut Output,
+    day: DayView<'_>,
+    previous: Option<&DayData>,
+    opts: &MenuOpts,
+) {
+    let mains = shown_mains(&day, opts);
+    // Only the shown dishes are compared, so that filtered ones do not count as removed
+    let shown = |mains: Vec<&MealInfo>| DayData {
+        main_dishes: mains.into_iter().cloned().collect(),
+        side_dishes: vec![],
     };
-    let (emoji, colour) = (&fmt_style.emoji, &fmt_style.colour);
+    let diff = previous
+        .map(|previous| shown(mains.clone()).diff(&shown(shown_mains(&previous.view(), opts))));
+
+    let align = opts.aligned.then(|| PriceColumn::of(&mains, opts));
+    for main in mains {
+        let hash = main.dish_hash();
+        let mark = diff.as_ref().and_then(|diff| {
+            if diff.added.iter().any(|added| added.dish_hash() == hash) {
+                Some("32")
+            } else if diff.changed.iter().any(|c| c.after.dish_hash() == hash) {
+                Some("33")
+            } else {
+                None
+            }
+        });
+        let mut lines = Output::default();
+        print_main(&mut lines, main, opts, align);
+        with_gutter(out, lines, mark);
+    }
+    for removed in diff.iter().flat_map(|diff| &diff.removed) {
+        outln!(
+            out,
+            "\x1b[1;31m▌{RST} \x1b[9;31m {} ({}){RST}",
+            removed.text.trim(),
+            removed.typ.name(opts.english)
+        );
+    }
 
-    // Print headline
-    println!(
-        "\x1b[38;5;{colour}m {emoji} {}{}{RST}",
-        main.text,
-        if main.vegan { " 🌱" } else { "" }
+    let sides = shown_sides(&day, opts);
+    if sides.is_empty() {
+        return;
+    }
+
+    outln!(out);
+    let previous_sides = previous
+        .map(|previous| shown_sides(&previous.view(), opts))
+        .unwrap_or_default();
+    for side in sides {
+        let mark = previous.and_then(|_| {
+            if previous_sides.contains(&side) {
+                None
+            } else if previous_sides.iter().any(|before| before.typ == side.typ) {
+                Some("33")
+            } else {
+                Some("32")
+            }
+        });
+        let mut lines = Output::default();
+        print_side(&mut lines, side, opts);
+        with_gutter(out, lines, mark);
+    }
+}
+
+/// Prints when the menu was fetched and when it is fetched next (see `menu --watch`)
+pub fn print_watch_status<Tz: chrono::TimeZone>(
+    out: &mut Output,
+    fetched: chrono::DateTime<Tz>,
+    next: chrono::DateTime<Tz>,
+    english: bool,
+) where
+    Tz::Offset: std::fmt::Display,
+{
+    let (label, next_label) = if english {
+        ("Updated", "next update")
+    } else {
+        ("Stand", "nächste Aktualisierung")
+    };
+    outln!(
+        out,
+        "\x1b[3;38;5;{}m{label}: {}, {next_label}: {}{RST}",
+        *SUBTEXT_COLOUR,
+        fetched.format("%d.%m.%Y %H:%M"),
+        next.format("%H:%M")
     );
+}
+
+/// Style (emoji/colour) for a main meal
+pub fn main_style(main: &MealInfo) -> &'static StyleMeal {
+    let fmt = FORMAT.get(&main.typ).unwrap_or(*FORMAT_MEAL_DEFAULT);
+    match fmt.alt_veg {
+        Some(ref alt_veg) if main.diet.is_vegetarian() => alt_veg,
+        _ => &fmt.main,
+    }
+}
+
+/// Layout of the price column for `--aligned`
+#[derive(Clone, Copy, Debug)]
+struct PriceColumn {
+    /// Width of the widest headline (emoji + text)
+    headline_width: usize,
+    /// Width of the widest price
+    price_width: usize,
+}
+
+/// Dish name as printed in the headline (with the subtext if `--inline-subtext`)
+fn headline_text(main: &MealInfo, opts: &MenuOpts) -> String {
+    let subtext = main
+        .subtext
+        .trim_start_matches(|c: char| c == '|' || c.is_whitespace());
+    if opts.inline_subtext && !opts.short && !subtext.is_empty() {
+        format!("{} ({subtext})", main.text)
+    } else {
+        main.text.clone()
+    }
+}
+
+/// Price as printed: the raw string from the page, or the parsed value reformatted if
+/// `--normalized-prices` (falls back to the raw string if it could not be parsed)
+pub fn price_text(main: &MealInfo, opts: &MenuOpts) -> String {
+    match main.price_value {
+        Some(value) if opts.normalized_prices => format_price(value, opts.english),
+        _ => main.price.trim().to_string(),
+    }
+}
+
+/// Suffix of the headline marking vegan (🌱) and vegetarian (🥕) meals
+pub fn diet_marker(main: &MealInfo) -> &'static str {
+    match main.diet {
+        Diet::Vegan => " 🌱",
+        Diet::Vegetarian => " 🥕",
+        _ => "",
+    }
+}
+
+/// Full headline (without colours/wrapping)
+pub fn headline(main: &MealInfo, opts: &MenuOpts) -> String {
+    let StyleMeal { emoji, .. } = main_style(main);
+    format!(
+        " {emoji} {}{}",
+        headline_text(main, opts),
+        diet_marker(main)
+    )
+}
+
+impl PriceColumn {
+    fn of(mains: &[&MealInfo], opts: &MenuOpts) -> Self {
+        Self {
+            headline_width: mains
+                .iter()
+                .map(|m| headline(m, opts).width())
+                .max()
+                .unwrap_or(0),
+            price_width: mains
+                .iter()
+                .map(|m| price_text(m, opts).width())
+                .max()
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// Prints a single main meal info
+fn print_main(out: &mut Output, main: &MealInfo, opts: &MenuOpts, align: Option<PriceColumn>) {
+    let StyleMeal { emoji, colour } = main_style(main);
+    let diet = diet_marker(main);
+
+    // Print headline (with the price in its column if aligned)
+    if let Some(col) = align {
+        let headline = headline(main, opts);
+        let price = price_text(main, opts);
+        let pad = col.headline_width - headline.width() + 2 + col.price_width - price.width();
+        outln!(out, "\x1b[38;5;{colour}m{headline}{:pad$}{price}{RST}", "");
+    } else {
+        outln!(
+            out,
+            "\x1b[38;5;{colour}m {emoji} {}{diet}{RST}",
+            wrap(&headline_text(main, opts), 4, "    ")
+        );
+    }
 
     // Short output -> skip subtext etc.
     if opts.short {
         return;
     }
 
-    // Print subtext
-    if !main.subtext.is_empty() {
-        println!("\t\x1b[3;38;5;{}m{}{RST}", *SUBTEXT_COLOUR, main.subtext);
+    // Print subtext (unless it's already in the headline)
+    if !main.subtext.is_empty() && !opts.inline_subtext {
+        outln!(
+            out,
+            "\t\x1b[3;38;5;{}m{}{RST}",
+            *SUBTEXT_COLOUR,
+            wrap(&main.subtext, TAB_WIDTH, "\t")
+        );
     }
 
-    if opts.allergens && !main.allergens.is_empty() {
-        println!(
-            "\t\x1b[3;38;5;{}m{}: {}{RST}",
+    for line in code_lines(&main.allergens, &main.additives, opts) {
+        outln!(
+            out,
+            "\t\x1b[3;38;5;{}m{}{RST}",
             *SUBTEXT_COLOUR,
-            if opts.english {
-                "Allergens"
-            } else {
-                "Allergene"
-            },
-            main.allergens
+            wrap(&line, TAB_WIDTH, "\t")
         );
     }
 
-    // If needed, print price as well
-    if opts.prices {
-        println!("\t\x1b[3;38;5;{}m{}{RST}", *SUBTEXT_COLOUR, main.price);
+    // If needed, print price as well (unless it's already in the headline)
+    if opts.prices && align.is_none() {
+        outln!(
+            out,
+            "\t\x1b[3;38;5;{}m{}{RST}",
+            *SUBTEXT_COLOUR,
+            price_text(main, opts)
+        );
+    }
+}
+
+/// "Allergene: ..." line of a meal (codes or names, see `--allergen-names`)
+pub fn allergen_text(allergens: &AllergenList, opts: &MenuOpts) -> String {
+    let label = if opts.english {
+        "Allergens"
+    } else {
+        "Allergene"
+    };
+    format!("{label}: {}", code_text(allergens, opts))
+}
+
+/// "Zusatzstoffe: ..." line of a meal (see `allergen_text`)
+pub fn additive_text(additives: &AllergenList, opts: &MenuOpts) -> String {
+    let label = if opts.english {
+        "Additives"
+    } else {
+        "Zusatzstoffe"
+    };
+    format!("{label}: {}", code_text(additives, opts))
+}
+
+/// Codes or names of a list, see `--allergen-names`
+pub fn code_text(list: &AllergenList, opts: &MenuOpts) -> String {
+    if opts.allergen_names {
+        list.describe(opts.english)
+    } else {
+        list.to_string()
+    }
+}
+
+/// Allergen/additive lines of a dish as requested by `--allergens`/`--additives`, leaving
+/// out empty lists
+pub fn code_lines(
+    allergens: &AllergenList,
+    additives: &AllergenList,
+    opts: &MenuOpts,
+) -> Vec<String> {
+    let mut lines = vec![];
+    if opts.allergens && !allergens.is_empty() {
+        lines.push(allergen_text(allergens, opts));
     }
+    if opts.additives && !additives.is_empty() {
+        lines.push(additive_text(additives, opts));
+    }
+    lines
+}
+
+/// Position of a side type according to the configured `side_order`
+fn side_position(typ: &SideType) -> usize {
+    CONFIG
+        .side_order
+        .iter()
+        .position(|t| t == typ)
+        .unwrap_or_else(|| CONFIG.side_order.len() + typ.display_order())
 }
 
 /// Prints single side meal info
-fn print_side(side: &SideInfo, opts: &MenuOpts) {
+fn print_side(out: &mut Output, side: &SideInfo, opts: &MenuOpts) {
     let StyleSide {
         head_colour: colour,
         subtext_colour: colour_subtext,
     } = &*FORMAT_SIDE;
 
     // Category name
-    println!("\x1b[38;5;{colour}m {}{RST}", side.typ.name(opts.english));
+    outln!(
+        out,
+        "\x1b[38;5;{colour}m {}{RST}",
+        side.display_name(opts.english)
+    );
 
     // Print all alternatives in a list
     for alternative in &side.alternatives {
-        println!("\x1b[38;5;{colour_subtext}m\t– {}{RST}", alternative.text);
-        if opts.allergens && !alternative.allergens.is_empty() {
-            println!(
-                "\t\x1b[3;38;5;{}m  {}: {}{RST}",
-                *SUBTEXT_COLOUR,
-                if opts.english {
-                    "Allergens"
-                } else {
-                    "Allergene"
-                },
-                alternative.allergens
-            );
+        outln!(
+            out,
+            "\x1b[38;5;{colour_subtext}m\t– {}{RST}",
+            wrap(&alternative.text, TAB_WIDTH + 2, "\t  ")
+        );
+        for line in code_lines(&alternative.allergens, &alternative.additives, opts) {
+            outln!(out, "\t\x1b[3;38;5;{}m  {line}{RST}", *SUBTEXT_COLOUR);
         }
     }
 }
 
-/// Prints all main/side meals in a day
-pub async fn pretty_print_all(day: DayView<'_>, opts: &MenuOpts) {
-    for main in day.main_dishes {
-        // Skip vegal meals if requested
-        if opts.skip_vegan && main.vegan {
-            continue;
+/// Localised name of a weekday
+pub fn weekday_name(weekday: chrono::Weekday, english: bool) -> &'static str {
+    let weekdays = if english { WEEKDAYS_EN } else { WEEKDAYS_DE };
+    weekdays[weekday.num_days_from_monday() as usize]
+}
+
+/// Weekday of a (German or English, possibly abbreviated) name
+pub fn parse_weekday(word: &str) -> Option<chrono::Weekday> {
+    let word = word.to_lowercase();
+    if word.len() < 2 {
+        return None;
+    }
+
+    (0..7u8)
+        .filter_map(|n| chrono::Weekday::try_from(n).ok())
+        .find(|day| {
+            [true, false].into_iter().any(|english| {
+                weekday_name(*day, english)
+                    .to_lowercase()
+                    .starts_with(&word)
+            })
+        })
+}
+
+/// Prints a weekday/date headline (used when printing multiple days)
+pub fn print_day_header(out: &mut Output, date: NaiveDate, english: bool) {
+    outln!(
+        out,
+        "\n\x1b[1m{}, {}{RST}",
+        weekday_name(date.weekday(), english),
+        date.format(crate::STDOUT_DATE_FMT)
+    );
+}
+
+/// Prints a mensa headline (used when printing multiple mensen)
+pub fn print_mensa_header(out: &mut Output, mensa: &Mensa) {
+    outln!(out, "\n\x1b[1;4m{}{RST}", mensa.name());
+}
+
+/// Opening intervals joined by ", " ("geschlossen"/"closed" if there are none)
+fn hours_text(hours: &[OpeningHours], english: bool) -> String {
+    if hours.is_empty() {
+        return if english { "closed" } else { "geschlossen" }.to_string();
+    }
+
+    hours
+        .iter()
+        .map(|h| format!("{}–{}", h.open, h.close))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Prints the opening times of a mensa (see `times`)
+pub fn print_times(out: &mut Output, times: &OpeningTimes, english: bool) {
+    let weekdays = if english { WEEKDAYS_EN } else { WEEKDAYS_DE };
+    let width = weekdays.iter().map(|d| d.width()).max().unwrap_or(0);
+
+    for day in &times.regular {
+        let name = weekdays[day.weekday as usize];
+        outln!(
+            out,
+            " \x1b[1m{name}{RST}{:pad$}  {}",
+            "",
+            hours_text(&day.hours, english),
+            pad = width - name.width()
+        );
+    }
+
+    if times.exceptions.is_empty() {
+        return;
+    }
+    outln!(
+        out,
+        "\n\x1b[1m{}{RST}",
+        if english { "Exceptions" } else { "Ausnahmen" }
+    );
+    for exception in &times.exceptions {
+        let dates = if exception.from == exception.until {
+            exception.from.clone()
+        } else {
+            format!("{} – {}", exception.from, exception.until)
+        };
+        outln!(
+            out,
+            " {dates}  {}\t\x1b[3;38;5;{}m{}{RST}",
+            hours_text(&exception.hours, english),
+            *SUBTEXT_COLOUR,
+            exception.note
+        );
+    }
+}
+
+/// Prints the supported mensen with slug, address and location (see `mensen`)
+pub fn print_mensen(out: &mut Output, mensen: &[MensaInfo]) {
+    let width = mensen.iter().map(|m| m.name.width()).max().unwrap_or(0);
+
+    for mensa in mensen {
+        outln!(
+            out,
+            " \x1b[1m{}{RST}{:pad$}  {}",
+            mensa.name,
+            "",
+            mensa.slug,
+            pad = width - mensa.name.width()
+        );
+        outln!(
+            out,
+            "\t{}\t\x1b[3;38;5;{}m{:.4}, {:.4}{RST}",
+            mensa.address,
+            *SUBTEXT_COLOUR,
+            mensa.latitude,
+            mensa.longitude
+        );
+    }
+}
+
+/// Prints the changes of the days (see `diff`)
+#[cfg(feature = "json")]
+pub fn print_diff(out: &mut Output, diffs: &[libacmensa::diff::DayDiff], english: bool) {
+    if diffs.is_empty() {
+        outln!(
+            out,
+            "{}",
+            if english {
+                "No changes"
+            } else {
+                "Keine Änderungen"
+            }
+        );
+        return;
+    }
+
+    let name = |meal: &MealInfo| format!("{} ({})", meal.text.trim(), meal.typ.name(english));
+    for day in diffs {
+        print_day_header(out, day.date, english);
+        for meal in &day.diff.added {
+            outln!(out, "\x1b[32m + {}{RST}", name(meal));
         }
+        for meal in &day.diff.removed {
+            outln!(out, "\x1b[31m - {}{RST}", name(meal));
+        }
+        for change in &day.diff.changed {
+            outln!(out, "\x1b[33m ~ {}{RST}", name(&change.after));
+            let (before, after) = (&change.before, &change.after);
+            for field in change.fields() {
+                let (label, before, after) = match field {
+                    "text" => ("Text", before.text.clone(), after.text.clone()),
+                    "subtext" => (
+                        if english {
+                            "Description"
+                        } else {
+                            "Beschreibung"
+                        },
+                        before.subtext.clone(),
+                        after.subtext.clone(),
+                    ),
+                    "price" => (
+                        if english { "Price" } else { "Preis" },
+                        before.price.clone(),
+                        after.price.clone(),
+                    ),
+                    "allergens" => (
+                        if english { "Allergens" } else { "Allergene" },
+                        before.allergens.to_string(),
+                        after.allergens.to_string(),
+                    ),
+                    "additives" => (
+                        if english { "Additives" } else { "Zusatzstoffe" },
+                        before.additives.to_string(),
+                        after.additives.to_string(),
+                    ),
+                    "contains" => (
+                        if english { "Contains" } else { "Enthält" },
+                        before.contains.describe(english),
+                        after.contains.describe(english),
+                    ),
+                    _ => (
+                        if english { "Diet" } else { "Ernährung" },
+                        format!("{:?}", before.diet),
+                        format!("{:?}", after.diet),
+                    ),
+                };
+                outln!(
+                    out,
+                    "\t\x1b[3;38;5;{}m{label}: {} → {}{RST}",
+                    *SUBTEXT_COLOUR,
+                    before.trim(),
+                    after.trim()
+                );
+            }
+        }
+    }
+}
 
-        // If a category filter is available, apply it
-        if let Some(ref only) = opts.only
-            && &main.typ != only
-        {
-            continue;
+/// Prints statistics over archived days (see `stats`)
+#[cfg(feature = "archive")]
+pub fn print_stats(out: &mut Output, stats: &libacmensa::stats::Stats, english: bool) {
+    let (days, meals) = if english {
+        ("days", "meals")
+    } else {
+        ("Tage", "Gerichte")
+    };
+    outln!(out, " {} {days}, {} {meals}", stats.days, stats.meals);
+    if stats.meals == 0 {
+        return;
+    }
+
+    outln!(
+        out,
+        "\n\x1b[1m{}{RST}",
+        if english {
+            "Most frequent meals"
+        } else {
+            "Häufigste Gerichte"
+        }
+    );
+    let width = stats
+        .top_meals
+        .iter()
+        .map(|m| m.text.width())
+        .max()
+        .unwrap_or(0);
+    for meal in &stats.top_meals {
+        outln!(
+            out,
+            " {:>3}×  {}{:pad$}  \x1b[3;38;5;{}m{}, {}{RST}",
+            meal.count,
+            meal.text,
+            "",
+            *SUBTEXT_COLOUR,
+            meal.category.name(english),
+            meal.last_served.format(crate::STDOUT_DATE_FMT),
+            pad = width - meal.text.width()
+        );
+    }
+
+    outln!(
+        out,
+        "\n\x1b[1m{}{RST}",
+        if english { "Categories" } else { "Kategorien" }
+    );
+    let width = stats
+        .categories
+        .iter()
+        .map(|c| c.category.name(english).width())
+        .max()
+        .unwrap_or(0);
+    for category in &stats.categories {
+        let name = category.category.name(english);
+        let average = category
+            .average_price
+            .map(|price| format!("Ø {}", format_price(price, english)))
+            .unwrap_or_default();
+        outln!(
+            out,
+            " {name}{:pad$}  {:>4}×  {average}",
+            "",
+            category.count,
+            pad = width - name.width()
+        );
+        if category.monthly_prices.len() > 1 {
+            for month in &category.monthly_prices {
+                outln!(
+                    out,
+                    "\t\x1b[3;38;5;{}m{}  {}{RST}",
+                    *SUBTEXT_COLOUR,
+                    month.month,
+                    format_price(month.average_price, english)
+                );
+            }
+        }
+    }
+
+    outln!(
+        out,
+        "\n\x1b[1m{}{RST}",
+        if english {
+            "Vegan share per week"
+        } else {
+            "Veganer Anteil pro Woche"
+        }
+    );
+    for week in &stats.weeks {
+        let share = week.vegan_share * 100.0;
+        outln!(
+            out,
+            " {}  {:>3.0} %  {}\t\x1b[3;38;5;{}m{}/{}{RST}",
+            week.week_start.format(crate::STDOUT_DATE_FMT),
+            share,
+            "█".repeat((share / 10.0).round() as usize),
+            *SUBTEXT_COLOUR,
+            week.vegan,
+            week.meals
+        );
+    }
+}
+
+/// Prints a mensa × dish × price table (see `compare`)
+pub fn print_price_table(out: &mut Output, rows: &[(Mensa, MealInfo)]) {
+    let mensa_width = rows
+        .iter()
+        .map(|(mensa, _)| mensa.name().width())
+        .max()
+        .unwrap_or(0);
+    let text_width = rows
+        .iter()
+        .map(|(_, meal)| meal.text.width())
+        .max()
+        .unwrap_or(0);
+
+    for (mensa, meal) in rows {
+        let StyleMeal { colour, .. } = main_style(meal);
+        let mensa = mensa.name();
+        outln!(
+            out,
+            "\x1b[1m{mensa}{:pad_m$}{RST}  \x1b[38;5;{colour}m{}{:pad_t$}{RST}  {}",
+            "",
+            meal.text,
+            "",
+            meal.price.trim(),
+            pad_m = mensa_width - mensa.width(),
+            pad_t = text_width - meal.text.width(),
+        );
+    }
+}
+
+/// Placeholder day with every meal/side type (and the vegan variants of meal types that
+/// have an alternative style), used to preview the format.
+pub fn preview_day(english: bool) -> DayData {
+    let meal = |typ: MealType, vegan: bool| {
+        let text = format!(
+            "{}{}",
+            typ.name(english),
+            if vegan { " (vegan)" } else { "" }
+        );
+        MealInfo {
+            subtext: "| Sauce".into(),
+            diet: if vegan { Diet::Vegan } else { Diet::Omnivore },
+            ..MealInfo::new(typ, text, "2,50 €")
         }
+    };
+
+    let main_dishes = [
+        MealType::Klassiker,
+        MealType::Tellergericht,
+        MealType::Empfehlung,
+        MealType::Wok,
+        MealType::BurgerClassics,
+        MealType::BurgerWoche,
+        MealType::PizzaTag,
+        MealType::Vegetarisch,
+        MealType::Unbekannt,
+    ]
+    .into_iter()
+    .flat_map(|typ| {
+        let has_alt = FORMAT.get(&typ).is_some_and(|f| f.alt_veg.is_some());
+        let vegan = has_alt.then(|| meal(typ.clone(), true));
+        [Some(meal(typ, false)), vegan]
+    })
+    .flatten()
+    .collect();
 
-        print_main(main, opts);
+    let side_dishes = [
+        SideType::Main,
+        SideType::Secondary,
+        SideType::Salad,
+        SideType::Dessert,
+    ]
+    .into_iter()
+    .map(|typ| SideInfo {
+        label: typ.name(english).to_string(),
+        alternatives: vec![SideAlternative {
+            text: "Lorem ipsum".into(),
+            allergens: Default::default(),
+            additives: Default::default(),
+        }],
+        typ,
+    })
+    .collect();
+
+    DayData {
+        main_dishes,
+        side_dishes,
     }
+}
 
-    // Category filter OR skip_sides => skip sides
-    if opts.only.is_some() || opts.skip_sides {
+/// Main meals of a day that are printed with the given options
+pub fn shown_mains<'a>(day: &DayView<'a>, opts: &MenuOpts) -> Vec<&'a MealInfo> {
+    day.main_dishes
+        .iter()
+        // Skip vegan meals if requested
+        .filter(|main| !(opts.skip_vegan && main.diet.is_vegan()))
+        // If a category filter is available, apply it
+        .filter(|main| opts.only.as_ref().is_none_or(|only| &main.typ == only))
+        // Special offers only/hidden
+        .filter(|main| !opts.specials || main.typ.is_special())
+        .filter(|main| !(opts.no_specials && main.typ.is_special()))
+        .collect()
+}
+
+/// Side dishes of a day that are printed with the given options, in `side_order`
+pub fn shown_sides<'a>(day: &DayView<'a>, opts: &MenuOpts) -> Vec<&'a SideInfo> {
+    // Category filter OR specials only OR skip_sides => skip sides
+    if opts.only.is_some() || opts.specials || opts.skip_sides {
+        return vec![];
+    }
+
+    let mut sides = day.side_dishes.iter().collect::<Vec<_>>();
+    sides.sort_by_key(|side| side_position(&side.typ));
+    sides
+}
+
+/// Prints all main/side meals in a day
+pub fn pretty_print_all(out: &mut Output, day: DayView<'_>, opts: &MenuOpts) {
+    let mains = shown_mains(&day, opts);
+
+    // Aligned prices need the widths of all lines first
+    let align = opts.aligned.then(|| PriceColumn::of(&mains, opts));
+    for main in mains {
+        print_main(out, main, opts, align);
+    }
+
+    let sides = shown_sides(&day, opts);
+    if sides.is_empty() {
         return;
     }
 
-    println!();
-    for side in day.side_dishes {
-        print_side(side, opts);
+    outln!(out);
+    for side in sides {
+        print_side(out, side, opts);
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn output_text() {
+        // What `flush` prints with and without colours
+        let out =
+            Output::from("\x1b[1;38;5;208mSchnitzel\x1b[0m\n\x1b[3m2,50 €\x1b[0m\n".to_string());
+        assert_eq!(out.text(true), String::from(out.clone()));
+        assert_eq!(out.text(false), "Schnitzel\n2,50 €\n");
+        assert_eq!(out.text(false), out.plain());
     }
 }
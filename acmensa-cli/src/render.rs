@@ -0,0 +1,222 @@
+use std::path::{Path, PathBuf};
+
+use ab_glyph::{Font, FontVec, GlyphId, PxScale, ScaleFont, point};
+use image::{Rgb, RgbImage};
+
+/// Fonts tried (in order) if none is given via `--font`
+const DEFAULT_FONTS: [&str; 3] = [
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/TTF/DejaVuSans.ttf",
+    "/usr/share/fonts/dejavu/DejaVuSans.ttf",
+];
+
+const FONT_SIZE: f32 = 22.0;
+const PADDING: u32 = 24;
+const TAB: &str = "    ";
+const DEFAULT_FG: Rgb<u8> = Rgb([220, 220, 220]);
+
+/// Settings for `render --format png`
+pub struct PngOpts<'a> {
+    pub width: u32,
+    pub background: Rgb<u8>,
+    pub font: Option<&'a Path>,
+}
+
+/// A piece of a line in a single colour
+struct Segment {
+    colour: Rgb<u8>,
+    text: String,
+}
+
+/// Parses a "#rrggbb" colour
+pub fn parse_colour(s: &str) -> anyhow::Result<Rgb<u8>> {
+    let hex = s.trim_start_matches('#');
+    if hex.len() != 6 {
+        anyhow::bail!("invalid colour \"{s}\", expected #rrggbb");
+    }
+
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16);
+    Ok(Rgb([channel(0)?, channel(2)?, channel(4)?]))
+}
+
+/// RGB value of an xterm 256-colour palette entry
+fn ansi256(n: u8) -> Rgb<u8> {
+    const BASE: [[u8; 3]; 16] = [
+        [0, 0, 0],
+        [205, 0, 0],
+        [0, 205, 0],
+        [205, 205, 0],
+        [0, 0, 238],
+        [205, 0, 205],
+        [0, 205, 205],
+        [229, 229, 229],
+        [127, 127, 127],
+        [255, 0, 0],
+        [0, 255, 0],
+        [255, 255, 0],
+        [92, 92, 255],
+        [255, 0, 255],
+        [0, 255, 255],
+        [255, 255, 255],
+    ];
+
+    match n {
+        0..=15 => Rgb(BASE[n as usize]),
+        16..=231 => {
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            let n = n - 16;
+            Rgb([level(n / 36), level(n / 6 % 6), level(n % 6)])
+        }
+        _ => {
+            let grey = 8 + (n - 232) * 10;
+            Rgb([grey, grey, grey])
+        }
+    }
+}
+
+/// Splits a line of the pretty-printed output into coloured segments (only 256-colour
+/// foregrounds and resets are interpreted, other styles are dropped)
+fn segments(line: &str) -> Vec<Segment> {
+    let mut res = vec![];
+    let (mut colour, mut text) = (DEFAULT_FG, String::new());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            text.push(c);
+            continue;
+        }
+        if chars.next() != Some('[') {
+            continue;
+        }
+
+        let params = chars.by_ref().take_while(|c| *c != 'm').collect::<String>();
+        res.push(Segment {
+            colour,
+            text: std::mem::take(&mut text),
+        });
+
+        let params = params.split(';').collect::<Vec<_>>();
+        colour = match params.windows(3).find(|w| w[..2] == ["38", "5"]) {
+            Some(w) => w[2].parse().map(ansi256).unwrap_or(DEFAULT_FG),
+            None if params.contains(&"0") => DEFAULT_FG,
+            None => colour,
+        };
+    }
+    res.push(Segment { colour, text });
+
+    res.into_iter()
+        .filter(|s| !s.text.is_empty())
+        .map(|s| Segment {
+            text: s.text.replace('\t', TAB),
+            ..s
+        })
+        .collect()
+}
+
+fn load_font(path: Option<&Path>) -> anyhow::Result<FontVec> {
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => DEFAULT_FONTS
+            .iter()
+            .map(PathBuf::from)
+            .find(|p| p.exists())
+            .ok_or_else(|| anyhow::anyhow!("no default font found, please pass --font"))?,
+    };
+
+    Ok(FontVec::try_from_vec(std::fs::read(path)?)?)
+}
+
+/// Glyph of `c`, `None` if the font has none (e.g. for emoji). Such glyphs are skipped.
+fn glyph_of<F: Font>(font: &impl ScaleFont<F>, c: char) -> Option<GlyphId> {
+    let id = font.glyph_id(c);
+    (id.0 != 0 || c.is_whitespace()).then_some(id)
+}
+
+/// Breaks the segments of a line into rows fitting into `max_width` pixels
+fn layout<F: Font>(
+    font: &impl ScaleFont<F>,
+    line: Vec<Segment>,
+    max_width: f32,
+) -> Vec<Vec<Segment>> {
+    let width = |s: &str| {
+        s.chars()
+            .filter_map(|c| glyph_of(font, c))
+            .map(|id| font.h_advance(id))
+            .sum::<f32>()
+    };
+    let mut rows = vec![vec![]];
+    let mut x = 0.0;
+
+    for seg in line {
+        // Keep whitespace attached to the following word to preserve indentation
+        for word in seg.text.split_inclusive(' ') {
+            let w = width(word);
+            if x > 0.0 && x + width(word.trim_end()) > max_width {
+                rows.push(vec![]);
+                x = 0.0;
+            }
+
+            rows.last_mut().unwrap().push(Segment {
+                colour: seg.colour,
+                text: word.to_string(),
+            });
+            x += w;
+        }
+    }
+
+    rows
+}
+
+/// Rasterises the (ANSI-coloured) lines of a rendered day into a PNG at `path`
+pub fn write_png(lines: &[String], opts: &PngOpts, path: &Path) -> anyhow::Result<()> {
+    let font = load_font(opts.font)?;
+    let scaled = font.as_scaled(PxScale::from(FONT_SIZE));
+    let line_height = (scaled.height() + scaled.line_gap()).ceil();
+    let max_width = opts.width.saturating_sub(2 * PADDING) as f32;
+
+    let rows = lines
+        .iter()
+        .flat_map(|line| layout(&scaled, segments(line), max_width))
+        .collect::<Vec<_>>();
+    let height = 2 * PADDING + (rows.len() as f32 * line_height) as u32;
+
+    let mut img = RgbImage::from_pixel(opts.width, height, opts.background);
+    for (i, row) in rows.iter().enumerate() {
+        let baseline = PADDING as f32 + i as f32 * line_height + scaled.ascent();
+        let mut x = PADDING as f32;
+
+        for seg in row {
+            for c in seg.text.chars() {
+                let Some(id) = glyph_of(&scaled, c) else {
+                    continue;
+                };
+
+                let glyph = id.with_scale_and_position(scaled.scale(), point(x, baseline));
+                x += scaled.h_advance(id);
+                let Some(outline) = font.outline_glyph(glyph) else {
+                    continue;
+                };
+
+                let bounds = outline.px_bounds();
+                outline.draw(|gx, gy, coverage| {
+                    let (px, py) = (
+                        bounds.min.x as i32 + gx as i32,
+                        bounds.min.y as i32 + gy as i32,
+                    );
+                    if px < 0 || py < 0 || px >= img.width() as i32 || py >= img.height() as i32 {
+                        return;
+                    }
+
+                    let bg = img.get_pixel(px as u32, py as u32).0;
+                    let blend = |c: usize| {
+                        (bg[c] as f32 * (1.0 - coverage) + seg.colour.0[c] as f32 * coverage) as u8
+                    };
+                    img.put_pixel(px as u32, py as u32, Rgb([blend(0), blend(1), blend(2)]));
+                });
+            }
+        }
+    }
+
+    img.save(path)?;
+    Ok(())
+}
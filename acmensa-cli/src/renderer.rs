@@ -0,0 +1,374 @@
+use libacmensa::{
+    mensa::Mensa,
+    records::{self, BoolStyle, MealRecord},
+    scrape::DatedDay,
+};
+
+#[cfg(feature = "json")]
+use crate::{args::ExportFormat, feed};
+use crate::{
+    args::{MenuFormat, MenuOpts},
+    html, markdown, plain,
+    pretty_print::{Output, pretty_print_all, print_day_header, print_mensa_header},
+};
+
+/// Renders the days printed by `menu` (or written by `export`) in one output format.
+/// Days are fed in one by one, so a new format only has to implement this trait and be
+/// returned by `MenuFormat::renderer`/`ExportFormat::renderer`.
+pub trait Renderer {
+    /// Starts the days of another mensa (only called when rendering several mensen)
+    fn mensa(&mut self, _mensa: &Mensa) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Renders a day (already filtered according to `opts`)
+    fn day(&mut self, day: &DatedDay, opts: &MenuOpts) -> anyhow::Result<()>;
+
+    /// Returns the rendered output
+    fn finish(self: Box<Self>) -> anyhow::Result<String>;
+}
+
+impl MenuFormat {
+    /// Renderer for this format. With `whole_week`, several days of a mensa are rendered.
+    /// Booleans of csv/tsv are written in `bools`.
+    pub fn renderer(&self, whole_week: bool, bools: BoolStyle) -> Box<dyn Renderer> {
+        match self {
+            MenuFormat::Pretty => Box::new(Pretty {
+                out: Output::default(),
+                whole_week,
+            }),
+            MenuFormat::Plain => Box::new(Plain {
+                out: String::new(),
+                whole_week,
+            }),
+            #[cfg(feature = "json")]
+            MenuFormat::Json => Box::new(Json {
+                days: vec![],
+                multiple: whole_week,
+            }),
+            #[cfg(feature = "json")]
+            MenuFormat::JsonLines => Box::new(JsonLines(String::new())),
+            #[cfg(feature = "yaml")]
+            MenuFormat::Yaml => Box::new(Yaml {
+                days: vec![],
+                multiple: whole_week,
+            }),
+            MenuFormat::Csv => Box::new(Dsv::new(',', bools)),
+            MenuFormat::Tsv => Box::new(Dsv::new('\t', bools)),
+            MenuFormat::Markdown => Box::new(Markdown {
+                out: String::new(),
+                whole_week,
+            }),
+            MenuFormat::Html => Box::new(Html {
+                out: String::new(),
+                whole_week,
+            }),
+            #[cfg(feature = "json")]
+            MenuFormat::Waybar => Box::new(Waybar(None)),
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl ExportFormat {
+    /// Renderer and file extension for formats written into a single `<mensa>.<ext>` file.
+    /// `None` for formats with their own layout (JSON files per day, OpenMensa, the site).
+    pub fn renderer(&self, bools: BoolStyle) -> Option<(Box<dyn Renderer>, &'static str)> {
+        match self {
+            ExportFormat::Json => None,
+            #[cfg(feature = "openmensa")]
+            ExportFormat::Openmensa => None,
+            #[cfg(feature = "site")]
+            ExportFormat::Html => None,
+            ExportFormat::JsonLines => Some((Box::new(JsonLines(String::new())), "jsonl")),
+            #[cfg(feature = "yaml")]
+            ExportFormat::Yaml => Some((
+                Box::new(Yaml {
+                    days: vec![],
+                    multiple: true,
+                }),
+                "yaml",
+            )),
+            ExportFormat::Csv => Some((Box::new(Dsv::new(',', bools)), "csv")),
+            ExportFormat::Tsv => Some((Box::new(Dsv::new('\t', bools)), "tsv")),
+            ExportFormat::Markdown => Some((
+                Box::new(Markdown {
+                    out: String::new(),
+                    whole_week: true,
+                }),
+                "md",
+            )),
+            ExportFormat::Rss => Some((
+                Box::new(Rss {
+                    mensa: None,
+                    items: String::new(),
+                    english: false,
+                }),
+                "rss",
+            )),
+        }
+    }
+}
+
+/// Coloured output for the terminal (see `pretty_print`)
+struct Pretty {
+    out: Output,
+    whole_week: bool,
+}
+
+impl Renderer for Pretty {
+    fn mensa(&mut self, mensa: &Mensa) -> anyhow::Result<()> {
+        print_mensa_header(&mut self.out, mensa);
+        Ok(())
+    }
+
+    fn day(&mut self, day: &DatedDay, opts: &MenuOpts) -> anyhow::Result<()> {
+        if self.whole_week {
+            print_day_header(&mut self.out, day.date, opts.english);
+        }
+        pretty_print_all(&mut self.out, day.day.view(), opts);
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> anyhow::Result<String> {
+        Ok(self.out.into())
+    }
+}
+
+/// Labelled sentences for screen readers (see `plain`)
+struct Plain {
+    out: String,
+    whole_week: bool,
+}
+
+impl Renderer for Plain {
+    fn mensa(&mut self, mensa: &Mensa) -> anyhow::Result<()> {
+        plain::mensa_header(&mut self.out, mensa);
+        Ok(())
+    }
+
+    fn day(&mut self, day: &DatedDay, opts: &MenuOpts) -> anyhow::Result<()> {
+        if self.whole_week {
+            plain::day_header(&mut self.out, day.date, opts.english);
+        }
+        plain::day(&mut self.out, day.day.view(), opts);
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> anyhow::Result<String> {
+        Ok(self.out)
+    }
+}
+
+/// A single JSON object, or an array if several days are rendered
+#[cfg(feature = "json")]
+struct Json {
+    days: Vec<DatedDay>,
+    multiple: bool,
+}
+
+#[cfg(feature = "json")]
+impl Renderer for Json {
+    fn mensa(&mut self, _mensa: &Mensa) -> anyhow::Result<()> {
+        self.multiple = true;
+        Ok(())
+    }
+
+    fn day(&mut self, day: &DatedDay, _opts: &MenuOpts) -> anyhow::Result<()> {
+        self.days.push(day.clone());
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> anyhow::Result<String> {
+        // Several days are rendered as one array so that the output stays parseable
+        let json = match self.days.first() {
+            Some(day) if !self.multiple => serde_json::to_string_pretty(day)?,
+            _ => serde_json::to_string_pretty(&self.days)?,
+        };
+        Ok(json + "\n")
+    }
+}
+
+/// One JSON object per line and day
+#[cfg(feature = "json")]
+struct JsonLines(String);
+
+#[cfg(feature = "json")]
+impl Renderer for JsonLines {
+    fn day(&mut self, day: &DatedDay, _opts: &MenuOpts) -> anyhow::Result<()> {
+        self.0.push_str(&serde_json::to_string(day)?);
+        self.0.push('\n');
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> anyhow::Result<String> {
+        Ok(self.0)
+    }
+}
+
+/// Like `Json`, but YAML
+#[cfg(feature = "yaml")]
+struct Yaml {
+    days: Vec<DatedDay>,
+    multiple: bool,
+}
+
+#[cfg(feature = "yaml")]
+impl Renderer for Yaml {
+    fn mensa(&mut self, _mensa: &Mensa) -> anyhow::Result<()> {
+        self.multiple = true;
+        Ok(())
+    }
+
+    fn day(&mut self, day: &DatedDay, _opts: &MenuOpts) -> anyhow::Result<()> {
+        self.days.push(day.clone());
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> anyhow::Result<String> {
+        Ok(match self.days.first() {
+            Some(day) if !self.multiple => serde_yaml::to_string(day)?,
+            _ => serde_yaml::to_string(&self.days)?,
+        })
+    }
+}
+
+/// Delimiter-separated values, one row per dish (see `records::to_dsv`)
+struct Dsv {
+    records: Vec<MealRecord>,
+    delimiter: char,
+    bools: BoolStyle,
+}
+
+impl Dsv {
+    fn new(delimiter: char, bools: BoolStyle) -> Self {
+        Self {
+            records: vec![],
+            delimiter,
+            bools,
+        }
+    }
+}
+
+impl Renderer for Dsv {
+    fn day(&mut self, day: &DatedDay, opts: &MenuOpts) -> anyhow::Result<()> {
+        self.records.extend(day.to_records(opts.english));
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> anyhow::Result<String> {
+        Ok(records::to_dsv(self.records, self.delimiter, self.bools))
+    }
+}
+
+/// GitHub-flavored Markdown (see `markdown`)
+struct Markdown {
+    out: String,
+    whole_week: bool,
+}
+
+impl Renderer for Markdown {
+    fn mensa(&mut self, mensa: &Mensa) -> anyhow::Result<()> {
+        markdown::mensa_header(&mut self.out, mensa);
+        Ok(())
+    }
+
+    fn day(&mut self, day: &DatedDay, opts: &MenuOpts) -> anyhow::Result<()> {
+        if self.whole_week {
+            markdown::day_header(&mut self.out, day.date, opts.english);
+        }
+        markdown::day(&mut self.out, day.day.view(), opts);
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> anyhow::Result<String> {
+        Ok(self.out)
+    }
+}
+
+/// HTML fragment (see `html`)
+struct Html {
+    out: String,
+    whole_week: bool,
+}
+
+impl Renderer for Html {
+    fn mensa(&mut self, mensa: &Mensa) -> anyhow::Result<()> {
+        html::mensa_header(&mut self.out, mensa);
+        Ok(())
+    }
+
+    fn day(&mut self, day: &DatedDay, opts: &MenuOpts) -> anyhow::Result<()> {
+        if self.whole_week {
+            html::day_header(&mut self.out, day.date, opts.english);
+        }
+        html::day(&mut self.out, day.day.view(), opts);
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> anyhow::Result<String> {
+        Ok(self.out)
+    }
+}
+
+/// RSS 2.0 feed with an item per day (see `feed`). Only a single mensa per feed.
+#[cfg(feature = "json")]
+struct Rss {
+    mensa: Option<Mensa>,
+    items: String,
+    english: bool,
+}
+
+#[cfg(feature = "json")]
+impl Renderer for Rss {
+    fn mensa(&mut self, _mensa: &Mensa) -> anyhow::Result<()> {
+        anyhow::bail!("a feed holds the days of a single mensa")
+    }
+
+    fn day(&mut self, day: &DatedDay, opts: &MenuOpts) -> anyhow::Result<()> {
+        let Some(mensa) = day.mensa.as_deref().and_then(Mensa::from_slug) else {
+            anyhow::bail!("the mensa of {} is unknown", day.date);
+        };
+        if self.mensa.is_some_and(|m| m != mensa) {
+            anyhow::bail!("a feed holds the days of a single mensa");
+        }
+        self.mensa = Some(mensa);
+        self.english = opts.english;
+
+        // Closed days would only be empty entries
+        if !day.day.main_dishes.is_empty() {
+            feed::item(&mut self.items, &mensa, day, opts);
+        }
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> anyhow::Result<String> {
+        match self.mensa {
+            Some(mensa) => Ok(feed::channel(&mensa, &self.items, self.english)),
+            None => anyhow::bail!("no days to put into a feed"),
+        }
+    }
+}
+
+/// Status bar line (see `waybar`). Only a single day of a single mensa can be rendered.
+#[cfg(feature = "json")]
+struct Waybar(Option<crate::waybar::StatusLine>);
+
+#[cfg(feature = "json")]
+impl Renderer for Waybar {
+    fn mensa(&mut self, _mensa: &Mensa) -> anyhow::Result<()> {
+        anyhow::bail!("--format waybar prints a single day of a single mensa")
+    }
+
+    fn day(&mut self, day: &DatedDay, opts: &MenuOpts) -> anyhow::Result<()> {
+        if self.0.is_some() {
+            anyhow::bail!("--format waybar prints a single day of a single mensa");
+        }
+        self.0 = Some(crate::waybar::status_line(day.day.view(), opts));
+        Ok(())
+    }
+
+    fn finish(self: Box<Self>) -> anyhow::Result<String> {
+        Ok(serde_json::to_string(&self.0)? + "\n")
+    }
+}
@@ -0,0 +1,168 @@
+use chrono::{Datelike, NaiveDate};
+use libacmensa::{mensa::Mensa, scrape::WeekData};
+use minijinja::{Environment, Value, context};
+use serde::Serialize;
+
+use crate::{args::MenuOpts, html, pretty_print::weekday_name};
+
+/// Templates of the site, `.html` ones are auto-escaped
+const TEMPLATES: [(&str, &str); 4] = [
+    ("layout.html", include_str!("../../res/site/layout.html")),
+    ("index.html", include_str!("../../res/site/index.html")),
+    ("mensa.html", include_str!("../../res/site/mensa.html")),
+    ("week.html", include_str!("../../res/site/week.html")),
+];
+
+/// Name and target of a link
+#[derive(Serialize)]
+struct Link {
+    title: String,
+    link: String,
+}
+
+#[derive(Serialize)]
+struct Day {
+    title: String,
+    /// HTML of the menu, empty if nothing is served
+    menu: Value,
+}
+
+/// Renders the static site (see `export --format html`)
+pub struct Site {
+    env: Environment<'static>,
+    english: bool,
+}
+
+/// File name of the page of a week
+fn week_file(week_start: NaiveDate) -> String {
+    format!("{week_start}.html")
+}
+
+impl Site {
+    pub fn new(english: bool) -> anyhow::Result<Self> {
+        let mut env = Environment::new();
+        env.set_keep_trailing_newline(true);
+        for (name, source) in TEMPLATES {
+            env.add_template(name, source)?;
+        }
+        Ok(Self { env, english })
+    }
+
+    fn text<'a>(&self, english: &'a str, german: &'a str) -> &'a str {
+        if self.english { english } else { german }
+    }
+
+    fn render(&self, template: &str, ctx: Value) -> anyhow::Result<String> {
+        let common = context! {
+            lang => self.text("en", "de"),
+            generated => format!(
+                "{} {}",
+                self.text("Generated by acmensa-cli on", "Erstellt von acmensa-cli am"),
+                chrono::Local::now().format("%d.%m.%Y %H:%M"),
+            ),
+        };
+        Ok(self
+            .env
+            .get_template(template)?
+            .render(context! { ..ctx, ..common })?)
+    }
+
+    /// Heading of a week, e.g. "KW 42: 12.10. – 16.10.2026"
+    fn week_title(&self, week_start: NaiveDate) -> String {
+        // Mensen are open from Monday to Friday
+        let friday = week_start + chrono::Days::new(4);
+        format!(
+            "{} {}: {} – {}",
+            self.text("Week", "KW"),
+            week_start.iso_week().week(),
+            week_start.format("%d.%m."),
+            friday.format(crate::STDOUT_DATE_FMT)
+        )
+    }
+
+    /// Pages of a mensa as (file name, content): a page per week and `index.html` linking
+    /// them. `parent` is the link back to the index of all mensen, if there is one.
+    pub fn mensa_pages(
+        &self,
+        mensa: &Mensa,
+        weeks: &[WeekData],
+        opts: &MenuOpts,
+        parent: Option<&str>,
+    ) -> anyhow::Result<Vec<(String, String)>> {
+        let week_starts = weeks
+            .iter()
+            .map(|week| {
+                week.week_start
+                    .ok_or_else(|| anyhow::anyhow!("week of {mensa} without a start date"))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let links = week_starts
+            .iter()
+            .map(|start| Link {
+                title: self.week_title(*start),
+                link: week_file(*start),
+            })
+            .collect::<Vec<_>>();
+
+        let mut pages = vec![];
+        for (week, start) in weeks.iter().zip(week_starts) {
+            let days = week
+                .dated_days()
+                .into_iter()
+                .map(|day| {
+                    let mut menu = String::new();
+                    html::day(&mut menu, day.day.view(), opts);
+                    Day {
+                        title: format!(
+                            "{}, {}",
+                            weekday_name(day.date.weekday(), self.english),
+                            day.date.format(crate::STDOUT_DATE_FMT)
+                        ),
+                        menu: Value::from_safe_string(menu),
+                    }
+                })
+                .collect::<Vec<_>>();
+            let page = self.render(
+                "week.html",
+                context! {
+                    mensa => mensa.name(),
+                    week => self.week_title(start),
+                    weeks => links,
+                    days,
+                    closed => self.text("Closed", "Geschlossen"),
+                },
+            )?;
+            pages.push((week_file(start), page));
+        }
+
+        let index = self.render(
+            "mensa.html",
+            context! {
+                mensa => mensa.name(),
+                weeks => links,
+                parent,
+                back => self.text("All mensen", "Alle Mensen"),
+            },
+        )?;
+        pages.push(("index.html".to_string(), index));
+        Ok(pages)
+    }
+
+    /// Index of all mensen, linking to the `index.html` of their subdirectories
+    pub fn index(&self, mensen: &[Mensa]) -> anyhow::Result<String> {
+        let mensen = mensen
+            .iter()
+            .map(|mensa| Link {
+                title: mensa.name().to_string(),
+                link: format!("{}/index.html", mensa.slug()),
+            })
+            .collect::<Vec<_>>();
+        self.render(
+            "index.html",
+            context! {
+                title => self.text("Mensen of Studierendenwerk Aachen", "Mensen des Studierendenwerks Aachen"),
+                mensen,
+            },
+        )
+    }
+}
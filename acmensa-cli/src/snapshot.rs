@@ -0,0 +1,65 @@
+use std::path::PathBuf;
+
+use chrono::NaiveDate;
+use libacmensa::scrape::DayData;
+
+use libacmensa::mensa::Mensa;
+
+/// Who remembers the fetched days. Each has its own snapshots, so that e.g. `menu` (which
+/// runs far more often, see `--watch`) does not move the point `diff` compares against.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Store {
+    /// `menu --since last-fetch`
+    Menu,
+    /// `diff`
+    Diff,
+}
+
+impl Store {
+    /// Directory of the snapshots in the cache directory
+    fn dir(&self) -> &'static str {
+        match self {
+            Store::Menu => "last-fetch",
+            Store::Diff => "last-diff",
+        }
+    }
+}
+
+fn path(store: Store, mensa: &Mensa, date: NaiveDate, english: bool) -> Option<PathBuf> {
+    libacmensa::cache::default_dir().map(|dir| {
+        dir.join(store.dir()).join(format!(
+            "{mensa}_{}_{}.json",
+            date.format("%Y%m%d"),
+            if english { "en" } else { "de" }
+        ))
+    })
+}
+
+/// Loads the day as `store` fetched it last time, if available
+pub fn load(store: Store, mensa: &Mensa, date: NaiveDate, english: bool) -> Option<DayData> {
+    let path = path(store, mensa, date, english)?;
+    let json = std::fs::read_to_string(path).ok()?;
+
+    DayData::from_json_str(&json)
+        .inspect_err(|e| log::warn!("ignoring unreadable snapshot: {e}"))
+        .ok()
+}
+
+/// Remembers a freshly fetched (unfiltered) day for the next `load` of `store`
+pub fn store(
+    store: Store,
+    mensa: &Mensa,
+    date: NaiveDate,
+    english: bool,
+    day: &DayData,
+) -> anyhow::Result<()> {
+    let Some(path) = path(store, mensa, date, english) else {
+        anyhow::bail!("could not determine cache directory");
+    };
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, serde_json::to_string(day)?)?;
+    Ok(())
+}
@@ -0,0 +1,356 @@
+//! Interactive browser of the menus (`tui` subcommand)
+
+use std::collections::HashMap;
+
+use chrono::{Datelike, Duration, NaiveDate};
+use libacmensa::{meal::MealType, mensa::Mensa, scrape::WeekData};
+use ratatui::{
+    DefaultTerminal, Frame,
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Color, Style, Stylize},
+    text::Line,
+    widgets::{Block, List, ListState, Paragraph, Tabs, Wrap},
+};
+
+use crate::{
+    DateCtx,
+    args::{self, MenuOpts},
+    fetch,
+    pretty_print::{
+        FORMAT_SIDE, SUBTEXT_COLOUR, code_lines, headline, main_style, price_text, shown_mains,
+        shown_sides, weekday_name,
+    },
+};
+
+/// Days with a menu per week (Monday to Friday)
+const WEEK_DAYS: usize = 5;
+
+/// This and next week of a mensa (or why they could not be fetched)
+type Fortnight = [Result<WeekData, String>; 2];
+
+struct App {
+    mensen: &'static [Mensa],
+    mensa: ListState,
+    /// Selected day, counted from Monday of this week (weekends skipped)
+    day: usize,
+    monday: NaiveDate,
+    /// Toggled by the keybindings (language, allergens, prices)
+    opts: MenuOpts,
+    hidden: Vec<MealType>,
+    /// Fetched weeks by mensa and language (`true` for English)
+    weeks: HashMap<(Mensa, bool), Fortnight>,
+    scroll: u16,
+}
+
+/// Runs the browser until it is quit. Logging is turned off meanwhile, since it would
+/// garble the screen; fetch errors are shown in place of the menu.
+pub async fn run(args: &args::Args, opts: &args::TuiOpts, datectx: &DateCtx) -> anyhow::Result<()> {
+    let (week, idx) = crate::resolve_day(None, &args::MenuDate::Today, datectx, true)?;
+    let mensen = Mensa::all();
+    let mut app = App {
+        mensen,
+        mensa: ListState::default().with_selected(mensen.iter().position(|m| *m == args.mensa)),
+        day: week.offset() as usize * WEEK_DAYS + idx as usize,
+        monday: datectx.first_avail_date,
+        opts: MenuOpts {
+            english: args.english,
+            allergens: opts.allergens,
+            prices: opts.prices,
+            ..Default::default()
+        },
+        hidden: args.hidden(None),
+        weeks: HashMap::new(),
+        scroll: 0,
+    };
+
+    let level = log::max_level();
+    log::set_max_level(log::LevelFilter::Off);
+    let mut terminal = ratatui::init();
+    let result = app.run(&mut terminal).await;
+    ratatui::restore();
+    log::set_max_level(level);
+    result
+}
+
+impl App {
+    async fn run(&mut self, terminal: &mut DefaultTerminal) -> anyhow::Result<()> {
+        loop {
+            self.load(terminal).await?;
+            terminal.draw(|frame| self.draw(frame))?;
+
+            // Reading blocks until a key is pressed, so keep it off the async workers
+            let Event::Key(key) = tokio::task::spawn_blocking(event::read).await?? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Up | KeyCode::Char('k') => self.select_mensa(-1),
+                KeyCode::Down | KeyCode::Char('j') => self.select_mensa(1),
+                KeyCode::Left | KeyCode::Char('h') => self.select_day(-1),
+                KeyCode::Right | KeyCode::Char('l') | KeyCode::Tab => self.select_day(1),
+                KeyCode::PageDown | KeyCode::Char(' ') => self.scroll += 5,
+                KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(5),
+                KeyCode::Char('a') => self.opts.allergens ^= true,
+                KeyCode::Char('p') => self.opts.prices ^= true,
+                KeyCode::Char('e') => self.opts.english ^= true,
+                _ => {}
+            }
+        }
+    }
+
+    /// Fetches the weeks of every mensa in the current language, unless already done
+    async fn load(&mut self, terminal: &mut DefaultTerminal) -> anyhow::Result<()> {
+        let english = self.opts.english;
+        if self.weeks.keys().any(|(_, e)| *e == english) {
+            return Ok(());
+        }
+
+        terminal.draw(|frame| {
+            let text = if english { "Loading…" } else { "Lade…" };
+            frame.render_widget(Paragraph::new(text).centered(), frame.area());
+        })?;
+
+        let hidden = self.hidden.clone();
+        let fetched = fetch::limited(self.mensen.iter().map(|&mensa| {
+            let hidden = hidden.clone();
+            async move {
+                let (this_week, next_week) = fetch::fetch_fortnight(&mensa, english).await;
+                let fortnight = [this_week, next_week].map(|week| {
+                    week.map(|w| w.without_types(&hidden))
+                        .map_err(|e| e.to_string())
+                });
+                (mensa, fortnight)
+            }
+        }))
+        .await;
+
+        self.weeks.extend(
+            fetched
+                .into_iter()
+                .map(|(mensa, fortnight)| ((mensa, english), fortnight)),
+        );
+        Ok(())
+    }
+
+    fn select_mensa(&mut self, step: isize) {
+        let selected = self.mensa.selected().unwrap_or(0) as isize + step;
+        let selected = selected.clamp(0, self.mensen.len() as isize - 1);
+        self.mensa.select(Some(selected as usize));
+        self.scroll = 0;
+    }
+
+    fn select_day(&mut self, step: isize) {
+        let day = (self.day as isize + step).clamp(0, 2 * WEEK_DAYS as isize - 1);
+        self.day = day as usize;
+        self.scroll = 0;
+    }
+
+    fn selected_mensa(&self) -> Mensa {
+        self.mensen[self.mensa.selected().unwrap_or(0)]
+    }
+
+    /// Date of a day, counted like `App::day`
+    fn date(&self, day: usize) -> NaiveDate {
+        self.monday + Duration::days((day / WEEK_DAYS * 7 + day % WEEK_DAYS) as i64)
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let english = self.opts.english;
+        let [tabs_area, body, help_area] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let names_width = self
+            .mensen
+            .iter()
+            .map(|m| m.name().chars().count())
+            .max()
+            .unwrap_or(0);
+        let [list_area, detail_area] = Layout::horizontal([
+            Constraint::Length(names_width as u16 + 4),
+            Constraint::Min(0),
+        ])
+        .areas(body);
+
+        let tabs = (0..2 * WEEK_DAYS).map(|day| {
+            let date = self.date(day);
+            let weekday = weekday_name(date.weekday(), english);
+            let short = weekday.chars().take(2).collect::<String>();
+            format!("{short} {}", date.day())
+        });
+        frame.render_widget(
+            Tabs::new(tabs)
+                .select(self.day)
+                .highlight_style(Style::new().reversed())
+                .block(Block::bordered()),
+            tabs_area,
+        );
+
+        let list = List::new(self.mensen.iter().map(|m| m.name()))
+            .highlight_style(Style::new().reversed())
+            .block(Block::bordered().title(" Mensen "));
+        frame.render_stateful_widget(list, list_area, &mut self.mensa);
+
+        let date = self.date(self.day);
+        let title = format!(
+            " {}, {} ",
+            weekday_name(date.weekday(), english),
+            date.format("%d.%m.%Y")
+        );
+        frame.render_widget(
+            Paragraph::new(self.day_lines())
+                .wrap(Wrap { trim: false })
+                .scroll((self.scroll, 0))
+                .block(Block::bordered().title(title)),
+            detail_area,
+        );
+
+        let help = if english {
+            "↑↓ mensa  ←→ day  a allergens  p prices  e Deutsch  PgUp/PgDn scroll  q quit"
+        } else {
+            "↑↓ Mensa  ←→ Tag  a Allergene  p Preise  e English  Bild↑/↓ scrollen  q beenden"
+        };
+        frame.render_widget(Line::from(help).dim(), help_area);
+    }
+
+    /// The selected day of the selected mensa, styled like the pretty-print output
+    fn day_lines(&self) -> Vec<Line<'static>> {
+        let opts = &self.opts;
+        let Some(fortnight) = self.weeks.get(&(self.selected_mensa(), opts.english)) else {
+            return vec![];
+        };
+        let week = match &fortnight[self.day / WEEK_DAYS] {
+            Ok(week) => week,
+            Err(e) => return vec![Line::from(e.clone()).red()],
+        };
+        let Ok(day) = week.get_day(self.day % WEEK_DAYS) else {
+            return vec![];
+        };
+
+        let subtext = Style::new().fg(colour(&SUBTEXT_COLOUR)).italic();
+        let mut lines = vec![];
+        for main in shown_mains(&day, opts) {
+            let style = Style::new().fg(colour(&main_style(main).colour));
+            lines.push(Line::styled(headline(main, opts), style.bold()));
+
+            let text = main
+                .subtext
+                .trim_start_matches(|c: char| c == '|' || c.is_whitespace());
+            if !text.is_empty() {
+                lines.push(Line::styled(format!("    {text}"), subtext));
+            }
+            if opts.prices {
+                lines.push(Line::styled(
+                    format!("    {}", price_text(main, opts)),
+                    subtext,
+                ));
+            }
+            for line in code_lines(&main.allergens, &main.additives, opts) {
+                lines.push(Line::styled(format!("    {line}"), subtext));
+            }
+        }
+
+        let side_head = Style::new().fg(colour(&FORMAT_SIDE.head_colour));
+        let side_text = Style::new().fg(colour(&FORMAT_SIDE.subtext_colour));
+        for side in shown_sides(&day, opts) {
+            lines.push(Line::default());
+            lines.push(Line::styled(
+                format!(" {}", side.display_name(opts.english)),
+                side_head,
+            ));
+            for alternative in &side.alternatives {
+                lines.push(Line::styled(
+                    format!("   – {}", alternative.text),
+                    side_text,
+                ));
+                for line in code_lines(&alternative.allergens, &alternative.additives, opts) {
+                    lines.push(Line::styled(format!("     {line}"), subtext));
+                }
+            }
+        }
+
+        if lines.is_empty() {
+            let empty = if opts.english {
+                "No menu on this day"
+            } else {
+                "Kein Speiseplan an diesem Tag"
+            };
+            lines.push(Line::from(empty).dim());
+        }
+        lines
+    }
+}
+
+/// Colour of the pretty-print format (a 256-colour palette index)
+fn colour(code: &str) -> Color {
+    code.trim().parse().map_or(Color::Reset, Color::Indexed)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use libacmensa::meal::MealInfo;
+
+    use super::*;
+
+    fn app(weeks: HashMap<(Mensa, bool), Fortnight>) -> App {
+        App {
+            mensen: Mensa::all(),
+            mensa: ListState::default().with_selected(Some(0)),
+            day: 0,
+            monday: NaiveDate::from_ymd_opt(2025, 12, 29).unwrap(),
+            opts: MenuOpts::default(),
+            hidden: vec![],
+            weeks,
+            scroll: 0,
+        }
+    }
+
+    fn text(lines: &[Line]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn dates_skip_weekends() {
+        let app = app(HashMap::new());
+        let date = |day| app.date(day).format("%Y-%m-%d").to_string();
+        assert_eq!(date(0), "2025-12-29");
+        assert_eq!(date(4), "2026-01-02");
+        // Next week starts on Monday, across the year boundary
+        assert_eq!(date(5), "2026-01-05");
+        assert_eq!(date(2 * WEEK_DAYS - 1), "2026-01-09");
+    }
+
+    #[test]
+    fn day_lines_by_state() {
+        let mensa = Mensa::all()[0];
+        let mut week = WeekData::empty();
+        week.main_dishes[1] = vec![MealInfo::new(MealType::Klassiker, "Schnitzel", "")];
+        let mut app = app(HashMap::from([(
+            (mensa, false),
+            [Ok(week), Err("Keine Verbindung".to_string())],
+        )]));
+
+        // Not loaded (yet) in this language
+        app.opts.english = true;
+        assert!(app.day_lines().is_empty());
+        app.opts.english = false;
+
+        app.day = 1;
+        let lines = text(&app.day_lines());
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].contains("Schnitzel"));
+
+        app.day = 0;
+        assert_eq!(text(&app.day_lines()), ["Kein Speiseplan an diesem Tag"]);
+
+        // Fetch errors are shown for every day of that week
+        app.day = WEEK_DAYS + 3;
+        assert_eq!(text(&app.day_lines()), ["Keine Verbindung"]);
+    }
+}
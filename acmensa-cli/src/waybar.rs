@@ -0,0 +1,39 @@
+use libacmensa::{meal::Diet, scrape::DayView};
+use serde::Serialize;
+
+use crate::{
+    args::MenuOpts,
+    pretty_print::{Output, headline, pretty_print_all, shown_mains},
+};
+
+/// Output of a waybar/i3status-rs custom module (one JSON object per line)
+#[derive(Serialize, Debug)]
+pub struct StatusLine {
+    /// Shown in the bar: the first printed main meal
+    pub text: String,
+    /// The whole day, as printed by `menu` (without colours)
+    pub tooltip: String,
+    /// `closed` if nothing is served, otherwise the diet of the meal in `text`
+    pub class: &'static str,
+}
+
+/// Renders a day for a status bar. Respects the same filters (`--only`, `--short`, ...) as
+/// the pretty printer.
+pub fn status_line(day: DayView<'_>, opts: &MenuOpts) -> StatusLine {
+    let mut out = Output::default();
+    pretty_print_all(&mut out, day.clone(), opts);
+
+    let first = shown_mains(&day, opts).first().copied();
+    StatusLine {
+        text: first
+            .map(|main| headline(main, opts).trim().to_string())
+            .unwrap_or_default(),
+        tooltip: out.plain().trim_end().to_string(),
+        class: match first.map(|main| main.diet) {
+            None => "closed",
+            Some(Diet::Vegan) => "vegan",
+            Some(Diet::Vegetarian) => "vegetarian",
+            Some(_) => "meal",
+        },
+    }
+}
@@ -0,0 +1,147 @@
+use std::{collections::HashMap, net::SocketAddr, sync::Arc, time::Duration};
+
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use chrono::NaiveDate;
+use clap::Parser;
+use libacmensa::{
+    SchemaVersion, Week,
+    client::ScrapeClient,
+    mensa::Mensa,
+    scrape::{DatedDay, WeekData},
+};
+use serde::Deserialize;
+use tokio::sync::RwLock;
+
+const DEF_LOG_LEVEL: &str = "info";
+const ENV_LOG_LEVEL: &str = "RUST_LOG";
+const USER_AGENT: &str = concat!("acmensa-server/", env!("CARGO_PKG_VERSION"));
+
+/// Periodically scrapes the menus of all Mensen of the Studierendenwerk Aachen and serves
+/// them as JSON.
+#[derive(Parser, Debug)]
+#[clap(author, version)]
+struct Args {
+    /// Address to listen on.
+    #[arg(short, long, default_value = "127.0.0.1:8080")]
+    bind: SocketAddr,
+
+    /// Minutes between two scrapes of all mensen.
+    #[arg(short, long, default_value_t = 30, value_parser = clap::value_parser!(u64).range(1..))]
+    interval: u64,
+}
+
+/// Scraped this and next week by (mensa, english)
+type Store = Arc<RwLock<HashMap<(Mensa, bool), [Option<WeekData>; 2]>>>;
+
+#[derive(Deserialize)]
+struct DayQuery {
+    /// Serve the English menu
+    #[serde(default)]
+    english: bool,
+}
+
+/// Fetches this and next week of every mensa (in both languages) into `store`.
+/// Weeks that could not be fetched keep their previous data.
+async fn scrape_all(client: &ScrapeClient, store: &Store) {
+    for mensa in Mensa::all() {
+        for english in [false, true] {
+            let mut fetched = vec![];
+            for week in Week::ALL {
+                let data = client.fetch_week(mensa.slug(), week, english).await;
+                if let Err(e) = &data {
+                    log::warn!("could not fetch {mensa} ({week} week): {e}");
+                }
+                fetched.push(data);
+            }
+            if !fetched.iter().any(Result::is_ok) {
+                continue;
+            }
+
+            let mut store = store.write().await;
+            let weeks = store.entry((*mensa, english)).or_default();
+            for (stored, data) in weeks.iter_mut().zip(fetched) {
+                if let Ok(data) = data {
+                    *stored = Some(data);
+                }
+            }
+        }
+    }
+    log::info!("scraped all mensen");
+}
+
+/// `GET /api/v1/{mensa}/{date}[?english=true]`
+async fn get_day(
+    State(store): State<Store>,
+    Path((mensa, date)): Path<(String, NaiveDate)>,
+    Query(query): Query<DayQuery>,
+) -> Response {
+    let Some(mensa) = Mensa::from_slug(&mensa) else {
+        return (StatusCode::NOT_FOUND, format!("unknown mensa \"{mensa}\"")).into_response();
+    };
+
+    let store = store.read().await;
+    let Some(weeks) = store.get(&(mensa, query.english)) else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            format!("{mensa} has not been scraped yet"),
+        )
+            .into_response();
+    };
+
+    match weeks
+        .iter()
+        .flatten()
+        .find_map(|week| week.get_day_by_date(date).ok())
+    {
+        Some(day) => Json(DatedDay {
+            schema_version: SchemaVersion::current(),
+            mensa: Some(mensa.slug().to_string()),
+            date,
+            day: day.into(),
+        })
+        .into_response(),
+        None => (
+            StatusCode::NOT_FOUND,
+            format!("no menu for {mensa} on {date}"),
+        )
+            .into_response(),
+    }
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    if std::env::var(ENV_LOG_LEVEL).is_err() {
+        unsafe { std::env::set_var(ENV_LOG_LEVEL, DEF_LOG_LEVEL) };
+    }
+    pretty_env_logger::init();
+
+    let args = Args::parse();
+    let store = Store::default();
+
+    // The scraped weeks are kept in memory, so the page cache is not needed
+    let client = ScrapeClient::builder().user_agent(USER_AGENT).build()?;
+    let scraper_store = store.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(args.interval * 60));
+        loop {
+            interval.tick().await;
+            scrape_all(&client, &scraper_store).await;
+        }
+    });
+
+    let app = Router::new()
+        .route("/api/v1/{mensa}/{date}", get(get_day))
+        .with_state(store);
+
+    log::info!("listening on {}", args.bind);
+    let listener = tokio::net::TcpListener::bind(args.bind).await?;
+    axum::serve(listener, app).await?;
+
+    Ok(())
+}
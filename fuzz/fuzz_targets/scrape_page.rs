@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// Feeds arbitrary bytes through decoding and parsing, strict and lenient. Malformed pages
+// may return `Err` (or warnings), but must never panic.
+fuzz_target!(|data: &[u8]| {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+
+    let _ = rt.block_on(libacmensa::scrape::scrape_bytes(data));
+    let page = libacmensa::scrape::decode_page(data, None);
+    let _ = rt.block_on(libacmensa::scrape::scrape_page_lenient(&page));
+});
@@ -0,0 +1,351 @@
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use rusqlite::{Connection, OptionalExtension, Transaction, params, params_from_iter};
+use serde::Serialize;
+
+use crate::{
+    SchemaVersion,
+    scrape::{DatedDay, DayData, WeekData},
+};
+
+const DATA_DIR: &str = "acmensa";
+const ARCHIVE_FILE: &str = "archive.sqlite";
+const DATE_FMT: &str = "%Y-%m-%d";
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS days (
+    mensa TEXT NOT NULL,
+    date TEXT NOT NULL,
+    archived_at TEXT NOT NULL,
+    PRIMARY KEY (mensa, date)
+);
+CREATE TABLE IF NOT EXISTS meals (
+    mensa TEXT NOT NULL,
+    date TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    category TEXT NOT NULL,
+    text TEXT NOT NULL,
+    subtext TEXT NOT NULL,
+    price_cents INTEGER,
+    diet TEXT NOT NULL,
+    allergens TEXT NOT NULL,
+    data TEXT NOT NULL,
+    PRIMARY KEY (mensa, date, position)
+);
+CREATE TABLE IF NOT EXISTS sides (
+    mensa TEXT NOT NULL,
+    date TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    category TEXT NOT NULL,
+    data TEXT NOT NULL,
+    PRIMARY KEY (mensa, date, position)
+);
+";
+
+/// Default archive location (`$XDG_DATA_HOME/acmensa/archive.sqlite`, or
+/// `~/.local/share/acmensa/archive.sqlite`)
+pub fn default_path() -> Option<PathBuf> {
+    std::env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share"))
+        })
+        .map(|dir| dir.join(DATA_DIR).join(ARCHIVE_FILE))
+}
+
+/// Filter for `Archive::days`. Every field is optional; an empty query returns everything.
+#[derive(Clone, Debug, Default)]
+pub struct Query {
+    /// Only days of this mensa (slug)
+    pub mensa: Option<String>,
+    /// Only days on or after this date
+    pub from: Option<NaiveDate>,
+    /// Only days on or before this date
+    pub to: Option<NaiveDate>,
+    /// Only main meals whose text contains this (see `MealInfo::matches`). Sides are dropped and
+    /// days without a matching meal are skipped.
+    pub text: Option<String>,
+}
+
+/// Local history of scraped menus, stored in SQLite.
+///
+/// A day is keyed by (mensa, date). Archiving a day again replaces what was stored for it,
+/// so syncing regularly keeps the last published version of every day.
+pub struct Archive {
+    conn: Connection,
+}
+
+/// Name of a unit enum variant as serialized (e.g. `Klassiker`)
+fn variant_name<T: Serialize>(value: &T) -> anyhow::Result<String> {
+    match serde_json::to_value(value)? {
+        serde_json::Value::String(name) => Ok(name),
+        other => anyhow::bail!("not a unit variant: {other}"),
+    }
+}
+
+impl Archive {
+    /// Opens (and if needed creates) the archive at `path`
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        Self::init(Connection::open(path)?)
+    }
+
+    /// Opens an archive that only lives in memory (e.g. for tests)
+    pub fn open_in_memory() -> anyhow::Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> anyhow::Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    /// Stores every day of `week` that has dishes. Days without any (closed or not yet
+    /// published) do not overwrite what is already archived. Returns the number of days
+    /// stored. Fails if the mensa or the dates of the week are unknown.
+    pub fn insert_week(&mut self, week: &WeekData) -> anyhow::Result<usize> {
+        if week.mensa.is_none() {
+            anyhow::bail!("cannot archive a week of an unknown mensa");
+        }
+        if week.week_start.is_none() {
+            anyhow::bail!("cannot archive a week without dates");
+        }
+
+        let tx = self.conn.transaction()?;
+        let mut stored = 0;
+        for day in week.dated_days() {
+            if day.day.main_dishes.is_empty() && day.day.side_dishes.is_empty() {
+                continue;
+            }
+            insert_day(&tx, &day)?;
+            stored += 1;
+        }
+        tx.commit()?;
+        Ok(stored)
+    }
+
+    /// Archived days matching `query`, ordered by mensa and date
+    pub fn days(&self, query: &Query) -> anyhow::Result<Vec<DatedDay>> {
+        let mut conditions = vec![];
+        let mut values = vec![];
+        if let Some(mensa) = &query.mensa {
+            conditions.push("mensa = ?");
+            values.push(mensa.clone());
+        }
+        if let Some(from) = query.from {
+            conditions.push("date >= ?");
+            values.push(from.format(DATE_FMT).to_string());
+        }
+        if let Some(to) = query.to {
+            conditions.push("date <= ?");
+            values.push(to.format(DATE_FMT).to_string());
+        }
+
+        let mut sql = "SELECT mensa, date FROM days".to_string();
+        if !conditions.is_empty() {
+            sql += " WHERE ";
+            sql += &conditions.join(" AND ");
+        }
+        sql += " ORDER BY mensa, date";
+
+        let keys = self
+            .conn
+            .prepare(&sql)?
+            .query_map(params_from_iter(values), |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut days = vec![];
+        for (mensa, date) in keys {
+            let mut day = self.day(&mensa, &date)?;
+            if let Some(text) = &query.text {
+                day.main_dishes.retain(|meal| meal.matches(text));
+                day.side_dishes.clear();
+                if day.main_dishes.is_empty() {
+                    continue;
+                }
+            }
+            days.push(DatedDay {
+                schema_version: SchemaVersion::current(),
+                mensa: Some(mensa),
+                date: NaiveDate::parse_from_str(&date, DATE_FMT)?,
+                day,
+            });
+        }
+        Ok(days)
+    }
+
+    /// Time a day was last archived (RFC 3339), if it is archived at all
+    pub fn archived_at(&self, mensa: &str, date: NaiveDate) -> anyhow::Result<Option<String>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT archived_at FROM days WHERE mensa = ?1 AND date = ?2",
+                params![mensa, date.format(DATE_FMT).to_string()],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    fn day(&self, mensa: &str, date: &str) -> anyhow::Result<DayData> {
+        let mut mains = self.conn.prepare_cached(
+            "SELECT data FROM meals WHERE mensa = ?1 AND date = ?2 ORDER BY position",
+        )?;
+        let main_dishes = mains
+            .query_map(params![mensa, date], |row| row.get::<_, String>(0))?
+            .map(|data| Ok(serde_json::from_str(&data?)?))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let mut sides = self.conn.prepare_cached(
+            "SELECT data FROM sides WHERE mensa = ?1 AND date = ?2 ORDER BY position",
+        )?;
+        let side_dishes = sides
+            .query_map(params![mensa, date], |row| row.get::<_, String>(0))?
+            .map(|data| Ok(serde_json::from_str(&data?)?))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        // Rows carry no version, those archived before schema version 2 list the additives
+        // among the allergens
+        Ok(DayData {
+            main_dishes,
+            side_dishes,
+        }
+        .migrate(SchemaVersion::default()))
+    }
+}
+
+/// Replaces the archived version of a single day
+fn insert_day(tx: &Transaction, day: &DatedDay) -> anyhow::Result<()> {
+    let mensa = day.mensa.as_deref().unwrap_or_default();
+    let date = day.date.format(DATE_FMT).to_string();
+
+    tx.execute(
+        "DELETE FROM meals WHERE mensa = ?1 AND date = ?2",
+        params![mensa, date],
+    )?;
+    tx.execute(
+        "DELETE FROM sides WHERE mensa = ?1 AND date = ?2",
+        params![mensa, date],
+    )?;
+    tx.execute(
+        "INSERT OR REPLACE INTO days (mensa, date, archived_at) VALUES (?1, ?2, ?3)",
+        params![mensa, date, chrono::Local::now().to_rfc3339()],
+    )?;
+
+    for (position, meal) in day.day.main_dishes.iter().enumerate() {
+        tx.execute(
+            "INSERT INTO meals (mensa, date, position, category, text, subtext, price_cents, \
+             diet, allergens, data) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                mensa,
+                date,
+                position,
+                variant_name(&meal.typ)?,
+                meal.text,
+                meal.subtext,
+                meal.price_parsed.as_ref().map(|price| price.cents),
+                variant_name(&meal.diet)?,
+                meal.allergens.to_string(),
+                serde_json::to_string(meal)?,
+            ],
+        )?;
+    }
+
+    for (position, side) in day.day.side_dishes.iter().enumerate() {
+        tx.execute(
+            "INSERT INTO sides (mensa, date, position, category, data) \
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                mensa,
+                date,
+                position,
+                variant_name(&side.typ)?,
+                serde_json::to_string(side)?,
+            ],
+        )?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::meal::{Diet, MealInfo, MealType};
+
+    fn meal(typ: MealType, text: &str) -> MealInfo {
+        MealInfo {
+            diet: Diet::Vegan,
+            ..MealInfo::new(typ, text, "2,50 €")
+        }
+    }
+
+    fn week(monday: &str, mains: [&str; 2]) -> WeekData {
+        let mut week = WeekData::empty()
+            .with_mensa("academica")
+            .with_week_start(NaiveDate::parse_from_str(monday, DATE_FMT).unwrap());
+        week.main_dishes[0] = vec![meal(MealType::Klassiker, mains[0])];
+        week.main_dishes[1] = vec![meal(MealType::Wok, mains[1])];
+        week
+    }
+
+    #[test]
+    pub fn roundtrip() {
+        let mut archive = Archive::open_in_memory().unwrap();
+        assert!(archive.insert_week(&WeekData::empty()).is_err());
+
+        // Only the two days with dishes are stored
+        assert_eq!(
+            archive
+                .insert_week(&week("2026-10-12", ["Schnitzel", "Curry"]))
+                .unwrap(),
+            2
+        );
+        assert_eq!(
+            archive
+                .insert_week(&week("2026-10-19", ["Pasta", "Tofu"]))
+                .unwrap(),
+            2
+        );
+
+        let days = archive.days(&Query::default()).unwrap();
+        assert_eq!(days.len(), 4);
+        assert_eq!(days[0].mensa.as_deref(), Some("academica"));
+        assert_eq!(days[1].day.main_dishes, vec![meal(MealType::Wok, "Curry")]);
+
+        // Archiving a day again replaces it
+        archive
+            .insert_week(&week("2026-10-12", ["Schnitzel", "Ramen"]))
+            .unwrap();
+        let date = NaiveDate::from_ymd_opt(2026, 10, 13).unwrap();
+        let query = Query {
+            from: Some(date),
+            to: Some(date),
+            ..Default::default()
+        };
+        let days = archive.days(&query).unwrap();
+        assert_eq!(days.len(), 1);
+        assert_eq!(days[0].day.main_dishes[0].text, "Ramen");
+        assert!(archive.archived_at("academica", date).unwrap().is_some());
+        assert!(archive.archived_at("ahornstrasse", date).unwrap().is_none());
+
+        let query = Query {
+            text: Some("tofu".into()),
+            ..Default::default()
+        };
+        let days = archive.days(&query).unwrap();
+        assert_eq!(days.len(), 1);
+        assert_eq!(days[0].date, NaiveDate::from_ymd_opt(2026, 10, 20).unwrap());
+
+        let query = Query {
+            mensa: Some("ahornstrasse".into()),
+            ..Default::default()
+        };
+        assert!(archive.days(&query).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,149 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use chrono::{DateTime, Datelike, Utc};
+use chrono_tz::Tz;
+
+use crate::Week;
+
+const CACHE_DIR: &str = "acmensa";
+const PAGES_DIR: &str = "pages";
+
+/// Default time after which a cached page is fetched again.
+pub const DEFAULT_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Caches raw menu pages on disk, keyed by (mensa, week, language).
+///
+/// An entry is fresh as long as it is younger than the TTL and was written in the current
+/// ISO week (the endpoints are relative to today, so "this week" from last Friday is not
+/// "this week" anymore on Monday). Weeks are determined in the time zone of the cache
+/// (Europe/Berlin unless set with `with_timezone`).
+#[derive(Clone, Debug)]
+pub struct Cache {
+    dir: PathBuf,
+    ttl: Duration,
+    timezone: Tz,
+}
+
+/// Default cache directory (`$XDG_CACHE_HOME/acmensa`, or `~/.cache/acmensa`)
+pub fn default_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .map(|dir| dir.join(CACHE_DIR))
+}
+
+impl Cache {
+    /// Cache pages in `dir`
+    pub fn new(dir: impl Into<PathBuf>, ttl: Duration) -> Self {
+        Self {
+            dir: dir.into(),
+            ttl,
+            timezone: crate::client::DEFAULT_TIMEZONE,
+        }
+    }
+
+    /// Determine the current week in `timezone`
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// Cache pages in the default directory (see `default_dir`)
+    pub fn with_default_dir(ttl: Duration) -> Option<Self> {
+        default_dir().map(|dir| Self::new(dir.join(PAGES_DIR), ttl))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    fn path(&self, mensa: &str, week: Week, english: bool) -> PathBuf {
+        self.dir.join(format!(
+            "{mensa}_{week}_{}.html",
+            if english { "en" } else { "de" }
+        ))
+    }
+
+    fn is_fresh(&self, modified: SystemTime) -> bool {
+        let age = SystemTime::now()
+            .duration_since(modified)
+            .unwrap_or_default();
+        let (written, now) = (
+            DateTime::<Utc>::from(modified).with_timezone(&self.timezone),
+            Utc::now().with_timezone(&self.timezone),
+        );
+
+        age < self.ttl && written.iso_week() == now.iso_week()
+    }
+
+    /// Returns the cached page if there is a fresh one
+    pub fn get(&self, mensa: &str, week: Week, english: bool) -> Option<String> {
+        let path = self.path(mensa, week, english);
+        let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+        if !self.is_fresh(modified) {
+            log::debug!("[cache] stale: {}", path.display());
+            return None;
+        }
+
+        log::debug!("[cache] hit: {}", path.display());
+        std::fs::read_to_string(path).ok()
+    }
+
+    /// Returns the cached page (fresh or not) and the time it was written
+    pub fn get_stale(
+        &self,
+        mensa: &str,
+        week: Week,
+        english: bool,
+    ) -> Option<(String, SystemTime)> {
+        let path = self.path(mensa, week, english);
+        let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+        Some((std::fs::read_to_string(path).ok()?, modified))
+    }
+
+    /// Stores a page (already decoded to UTF-8)
+    pub fn put(&self, mensa: &str, week: Week, english: bool, html: &str) -> anyhow::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(self.path(mensa, week, english), html)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    pub fn roundtrip() {
+        let dir = std::env::temp_dir().join(format!("acmensa-cache-test-{}", std::process::id()));
+        let cache = Cache::new(&dir, DEFAULT_TTL);
+
+        assert_eq!(cache.get("academica", Week::This, false), None);
+        cache
+            .put("academica", Week::This, false, "<html></html>")
+            .unwrap();
+        assert_eq!(
+            cache.get("academica", Week::This, false).as_deref(),
+            Some("<html></html>")
+        );
+        // Other week/language are separate entries
+        assert_eq!(cache.get("academica", Week::Next, false), None);
+        assert_eq!(cache.get("academica", Week::This, true), None);
+
+        // Nothing is fresh with a zero TTL
+        assert_eq!(
+            Cache::new(&dir, Duration::ZERO).get("academica", Week::This, false),
+            None
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,469 @@
+use std::time::{Duration, Instant, SystemTime};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
+use reqwest::{StatusCode, header};
+
+use crate::{
+    Week,
+    cache::Cache,
+    scrape::{self, WeekData},
+    times::OpeningTimes,
+};
+
+pub use crate::config::{
+    DEFAULT_RETRIES, DEFAULT_RETRY_DELAY, DEFAULT_TIMEOUT, DEFAULT_USER_AGENT,
+};
+
+/// Default time zone "today" is determined in (the one of the mensen).
+pub const DEFAULT_TIMEZONE: Tz = chrono_tz::Europe::Berlin;
+
+/// Fetches menu pages with a timeout and retries, backed by an (optional) page cache.
+/// Stale cached pages are revalidated with a conditional GET.
+#[derive(Clone, Debug)]
+pub struct ScrapeClient {
+    http: reqwest::Client,
+    cache: Option<Cache>,
+    retries: u32,
+    retry_delay: Duration,
+    lenient: bool,
+    timezone: Tz,
+}
+
+/// Configures a `ScrapeClient`. See `ScrapeClient::builder`.
+#[derive(Clone, Debug)]
+pub struct ScrapeClientBuilder {
+    cache: Option<Cache>,
+    timeout: Duration,
+    retries: u32,
+    retry_delay: Duration,
+    user_agent: String,
+    lenient: bool,
+    timezone: Tz,
+}
+
+impl Default for ScrapeClientBuilder {
+    fn default() -> Self {
+        Self {
+            cache: None,
+            timeout: DEFAULT_TIMEOUT,
+            retries: DEFAULT_RETRIES,
+            retry_delay: DEFAULT_RETRY_DELAY,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            lenient: false,
+            timezone: DEFAULT_TIMEZONE,
+        }
+    }
+}
+
+impl ScrapeClientBuilder {
+    /// Page cache to read from and write to. Default is no cache.
+    pub fn cache(mut self, cache: Option<Cache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Timeout per request
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Number of retries after a failed request
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Delay before the first retry, doubled for every further one
+    pub fn retry_delay(mut self, retry_delay: Duration) -> Self {
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    /// `User-Agent` header sent with every request
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// Skip malformed rows and cells of pages (logged as warnings) instead of failing. See
+    /// `scrape::scrape_page_lenient`. Default is strict parsing.
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
+    /// Time zone "today" is determined in, i.e. the start of this and next week (also used
+    /// by the page cache). Default is Europe/Berlin.
+    pub fn timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    pub fn build(self) -> anyhow::Result<ScrapeClient> {
+        Ok(ScrapeClient {
+            http: reqwest::Client::builder()
+                .timeout(self.timeout)
+                .user_agent(self.user_agent)
+                .build()?,
+            cache: self.cache.map(|cache| cache.with_timezone(self.timezone)),
+            retries: self.retries,
+            retry_delay: self.retry_delay,
+            lenient: self.lenient,
+            timezone: self.timezone,
+        })
+    }
+}
+
+/// Where a fetched page came from
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CacheStatus {
+    /// Fresh page from the cache, no request was made
+    Hit,
+    /// Stale page from the cache, the server confirmed it is unchanged
+    NotModified,
+    /// Downloaded (no cache, nothing cached, or the page changed)
+    Miss,
+}
+
+impl std::fmt::Display for CacheStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            CacheStatus::Hit => "cache hit",
+            CacheStatus::NotModified => "not modified",
+            CacheStatus::Miss => "cache miss",
+        })
+    }
+}
+
+/// Formats a timestamp as an HTTP date (e.g. for `If-Modified-Since`)
+fn http_date(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time)
+        .format("%a, %d %b %Y %H:%M:%S GMT")
+        .to_string()
+}
+
+impl ScrapeClient {
+    pub fn new(cache: Option<Cache>, timeout: Duration, retries: u32) -> anyhow::Result<Self> {
+        Self::builder()
+            .cache(cache)
+            .timeout(timeout)
+            .retries(retries)
+            .build()
+    }
+
+    /// Builder starting with the default timeout, retries and user agent, without a page cache
+    pub fn builder() -> ScrapeClientBuilder {
+        ScrapeClientBuilder::default()
+    }
+
+    pub fn cache(&self) -> Option<&Cache> {
+        self.cache.as_ref()
+    }
+
+    /// Today in the time zone of the client (see `ScrapeClientBuilder::timezone`)
+    pub fn today(&self) -> NaiveDate {
+        Utc::now().with_timezone(&self.timezone).date_naive()
+    }
+
+    /// Sends a GET request, retrying on connection errors and server errors
+    pub(crate) async fn send(
+        &self,
+        url: &str,
+        modified: Option<SystemTime>,
+    ) -> anyhow::Result<reqwest::Response> {
+        let mut attempt = 0;
+        loop {
+            let mut req = self.http.get(url);
+            if let Some(modified) = modified {
+                req = req.header(header::IF_MODIFIED_SINCE, http_date(modified));
+            }
+
+            let err = match req.send().await {
+                Ok(resp) if !resp.status().is_server_error() => return Ok(resp),
+                Ok(resp) => anyhow::anyhow!("server error {}", resp.status()),
+                Err(e) => e.into(),
+            };
+
+            if attempt >= self.retries {
+                return Err(err);
+            }
+            log::warn!("[client] {url}: {err}, retrying");
+            tokio::time::sleep(self.retry_delay * 2u32.pow(attempt)).await;
+            attempt += 1;
+        }
+    }
+
+    /// Fetches the menu page of a mensa, decoded to UTF-8. A fresh cached page is used
+    /// unless `refresh` is set.
+    pub async fn fetch_html(
+        &self,
+        mensa: &str,
+        week: Week,
+        english: bool,
+        refresh: bool,
+    ) -> anyhow::Result<String> {
+        Ok(self
+            .fetch_html_status(mensa, week, english, refresh)
+            .await?
+            .0)
+    }
+
+    /// Like `fetch_html`, but also returns whether the page came from the cache
+    pub async fn fetch_html_status(
+        &self,
+        mensa: &str,
+        week: Week,
+        english: bool,
+        refresh: bool,
+    ) -> anyhow::Result<(String, CacheStatus)> {
+        if !refresh && let Some(html) = self.cache().and_then(|c| c.get(mensa, week, english)) {
+            log::info!("[client] {mensa} ({week} week): {}", CacheStatus::Hit);
+            return Ok((html, CacheStatus::Hit));
+        }
+
+        let cached = self.cache().and_then(|c| c.get_stale(mensa, week, english));
+        let resp = self
+            .send(
+                &scrape::menu_url(mensa, week, english),
+                cached.as_ref().map(|(_, modified)| *modified),
+            )
+            .await?;
+
+        let (html, status) = match cached {
+            Some((html, _)) if resp.status() == StatusCode::NOT_MODIFIED => {
+                log::debug!("[client] {mensa} ({week} week) not modified");
+                (html, CacheStatus::NotModified)
+            }
+            _ => {
+                let resp = resp.error_for_status()?;
+                let content_type = resp
+                    .headers()
+                    .get(header::CONTENT_TYPE)
+                    .and_then(|v| v.to_str().ok())
+                    .map(str::to_string);
+                let bytes = resp.bytes().await?;
+                let html = scrape::decode_page(&bytes, content_type.as_deref()).into_owned();
+                (html, CacheStatus::Miss)
+            }
+        };
+
+        // Also refreshes the timestamp of revalidated pages
+        if let Some(cache) = self.cache()
+            && let Err(e) = cache.put(mensa, week, english, &html)
+        {
+            log::warn!("could not cache page of {mensa}: {e}");
+        }
+
+        log::info!("[client] {mensa} ({week} week): {status}");
+        Ok((html, status))
+    }
+
+    /// Parses a menu page of a mensa, leniently if configured (see
+    /// `ScrapeClientBuilder::lenient`)
+    pub async fn parse_page(&self, mensa: &str, html: &str) -> anyhow::Result<WeekData> {
+        if !self.lenient {
+            return scrape::scrape_mensa_page(mensa, html).await;
+        }
+
+        let (week, warnings) = scrape::scrape_mensa_page_lenient(mensa, html).await?;
+        for warning in warnings {
+            log::warn!("[scrape] {mensa}: {warning}");
+        }
+        Ok(week)
+    }
+
+    /// Fetches, parses and sorts a week of a mensa
+    pub async fn fetch_week(
+        &self,
+        mensa: &str,
+        week: Week,
+        english: bool,
+    ) -> anyhow::Result<WeekData> {
+        let html = self.fetch_html(mensa, week, english, false).await?;
+        let week_start = scrape::week_start(week, self.today());
+        Ok(self
+            .parse_page(mensa, &html)
+            .await?
+            .sorted()
+            .with_week_start(week_start)
+            .with_mensa(mensa))
+    }
+
+    /// Parses the cached page of a week without making any request, even if the page is
+    /// stale. The week is the one relative to the day the page was cached.
+    pub async fn cached_week(
+        &self,
+        mensa: &str,
+        week: Week,
+        english: bool,
+    ) -> anyhow::Result<WeekData> {
+        let (html, modified) = self
+            .cache()
+            .and_then(|c| c.get_stale(mensa, week, english))
+            .ok_or_else(|| anyhow::anyhow!("no cached page of {mensa} ({week} week)"))?;
+        let cached_on = DateTime::<Utc>::from(modified)
+            .with_timezone(&self.timezone)
+            .date_naive();
+        Ok(self
+            .parse_page(mensa, &html)
+            .await?
+            .sorted()
+            .with_week_start(scrape::week_start(week, cached_on))
+            .with_mensa(mensa))
+    }
+
+    /// Sends a HEAD request to the menu page of a mensa (without the cache and retries).
+    /// Returns the HTTP status code and the time until the response arrived.
+    pub async fn ping(
+        &self,
+        mensa: &str,
+        week: Week,
+        english: bool,
+    ) -> anyhow::Result<(u16, Duration)> {
+        let start = Instant::now();
+        let resp = self
+            .http
+            .head(scrape::menu_url(mensa, week, english))
+            .send()
+            .await?;
+
+        Ok((resp.status().as_u16(), start.elapsed()))
+    }
+
+    /// Fetches the opening times page and parses the opening times of a mensa (without the
+    /// cache)
+    pub async fn fetch_times(&self, mensa: &str) -> anyhow::Result<OpeningTimes> {
+        let resp = self
+            .send(&scrape::times_url(), None)
+            .await?
+            .error_for_status()?;
+        let content_type = resp
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let bytes = resp.bytes().await?;
+
+        scrape::parse_times(mensa, &scrape::decode_page(&bytes, content_type.as_deref()))
+    }
+
+    /// Fetches this and next week of a mensa concurrently (see `fetch_week`)
+    pub async fn fetch_fortnight(
+        &self,
+        mensa: &str,
+        english: bool,
+    ) -> (anyhow::Result<WeekData>, anyhow::Result<WeekData>) {
+        tokio::join!(
+            self.fetch_week(mensa, Week::This, english),
+            self.fetch_week(mensa, Week::Next, english),
+        )
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::cache::DEFAULT_TTL;
+
+    #[test]
+    pub fn format_http_date() {
+        let time = SystemTime::UNIX_EPOCH + Duration::from_secs(784111777);
+        assert_eq!(http_date(time), "Sun, 06 Nov 1994 08:49:37 GMT");
+    }
+
+    #[test]
+    pub fn builder() {
+        let client = ScrapeClient::builder()
+            .retries(5)
+            .retry_delay(Duration::from_secs(1))
+            .user_agent("test")
+            .build()
+            .unwrap();
+        assert_eq!(client.retries, 5);
+        assert_eq!(client.retry_delay, Duration::from_secs(1));
+        assert!(client.cache().is_none());
+        assert!(!client.lenient);
+
+        let client = ScrapeClient::new(None, DEFAULT_TIMEOUT, 0).unwrap();
+        assert_eq!(client.retries, 0);
+        assert_eq!(client.retry_delay, DEFAULT_RETRY_DELAY);
+    }
+
+    #[tokio::test]
+    pub async fn parse_page_lenient() {
+        let page = r#"<table>
+            <tr class="main-dish"><td>Klassiker<br>3,50 €</td><td><span class="dish-text">Schnitzel</span></td></tr>
+        </table>"#;
+
+        let strict = ScrapeClient::builder().build().unwrap();
+        assert!(strict.parse_page("academica", page).await.is_err());
+
+        let lenient = ScrapeClient::builder().lenient(true).build().unwrap();
+        let week = lenient.parse_page("academica", page).await.unwrap();
+        assert_eq!(week.main_dishes[0][0].text, "Schnitzel");
+    }
+
+    #[tokio::test]
+    pub async fn fetch_week_cached() {
+        let dir = std::env::temp_dir().join(format!("acmensa-client-test-{}", std::process::id()));
+        let cache = Cache::new(&dir, DEFAULT_TTL);
+        let page = scrape::decode_page(include_bytes!("../fixtures/latin1.html"), None);
+        cache.put("academica", Week::This, false, &page).unwrap();
+
+        // Fresh page in the cache -> no request is made
+        let client = ScrapeClient::new(Some(cache.clone()), DEFAULT_TIMEOUT, 0).unwrap();
+        let (_, status) = client
+            .fetch_html_status("academica", Week::This, false, false)
+            .await
+            .unwrap();
+        assert_eq!(status, CacheStatus::Hit);
+
+        let week = client
+            .fetch_week("academica", Week::This, false)
+            .await
+            .unwrap();
+        assert_eq!(week.side_dishes[0][0].alternatives[0].text, "Spätzle");
+        assert_eq!(week.mensa.as_deref(), Some("academica"));
+
+        cache.put("academica", Week::Next, false, &page).unwrap();
+        let (this_week, next_week) = client.fetch_fortnight("academica", false).await;
+        assert_eq!(
+            next_week.unwrap().week_start.unwrap() - this_week.unwrap().week_start.unwrap(),
+            chrono::TimeDelta::days(7)
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    pub async fn cached_week_offline() {
+        let dir = std::env::temp_dir().join(format!("acmensa-offline-test-{}", std::process::id()));
+        // A TTL of zero makes every cached page stale
+        let cache = Cache::new(&dir, Duration::ZERO);
+        let client = ScrapeClient::new(Some(cache.clone()), DEFAULT_TIMEOUT, 0).unwrap();
+        assert!(
+            client
+                .cached_week("academica", Week::Next, false)
+                .await
+                .is_err()
+        );
+
+        let page = scrape::decode_page(include_bytes!("../fixtures/latin1.html"), None);
+        cache.put("academica", Week::Next, false, &page).unwrap();
+        assert!(cache.get("academica", Week::Next, false).is_none());
+
+        let week = client
+            .cached_week("academica", Week::Next, false)
+            .await
+            .unwrap();
+        assert_eq!(week.side_dishes[0][0].alternatives[0].text, "Spätzle");
+        assert_eq!(
+            week.week_start,
+            Some(scrape::week_start(Week::Next, client.today()))
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
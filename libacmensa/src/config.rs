@@ -1,16 +1,41 @@
+use std::collections::HashMap;
+
 use lazy_static::lazy_static;
 use serde::Deserialize;
 
-use crate::DeEnStr;
+use crate::{DeEnStr, Week};
 
 const CONFIG_TOML: &str = include_str!("../../res/mensen.toml");
 pub const OPEN_DAYS: usize = 5;
 
+/// Default timeout per request (of the async and the blocking client).
+#[cfg(any(feature = "scrape", feature = "blocking"))]
+pub const DEFAULT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+/// Default number of retries after a failed request (of the async and the blocking client).
+#[cfg(any(feature = "scrape", feature = "blocking"))]
+pub const DEFAULT_RETRIES: u32 = 2;
+/// Default delay before the first retry (doubled for every further one).
+#[cfg(any(feature = "scrape", feature = "blocking"))]
+pub const DEFAULT_RETRY_DELAY: std::time::Duration = std::time::Duration::from_millis(500);
+/// Default `User-Agent` header (of the async and the blocking client).
+#[cfg(any(feature = "scrape", feature = "blocking"))]
+pub const DEFAULT_USER_AGENT: &str = concat!("libacmensa/", env!("CARGO_PKG_VERSION"));
+
 /// Master config for library
 #[derive(Deserialize, Clone, Debug)]
 pub struct Config {
     /// Holds endpoint configurations
     pub endpoint: Endpoint,
+
+    /// Named selector sets for the menu table
+    pub layout: HashMap<String, Layout>,
+
+    /// Mensa slug -> layout name overrides. Mensen not listed here use `default`.
+    #[serde(default)]
+    pub mensa_layout: HashMap<String, String>,
+
+    /// Layout of the opening times page
+    pub times: TimesLayout,
 }
 
 /// Endpoint configuration
@@ -20,7 +45,6 @@ pub struct Endpoint {
     pub host: String,
 
     /// Endpoint to grab timeplan
-    #[allow(dead_code)] // timeplan is to be implemented
     pub timeplan: String,
 
     /// Templates for endpoints to mensa-specific menus
@@ -33,18 +57,55 @@ pub struct PathTemplate {
     pub prefix: String,
     /// Mensa-specific suffix template. Differenent suffix for each language.
     pub suffix_template: DeEnStr<String>,
+    /// Values of `{{week}}` in the suffix templates
+    pub week_tokens: WeekTokens,
 }
 
+#[derive(Deserialize, Clone, Debug)]
+pub struct WeekTokens {
+    pub this: DeEnStr<String>,
+    pub next: DeEnStr<String>,
+}
+
+impl WeekTokens {
+    pub fn get(&self, week: Week) -> &DeEnStr<String> {
+        match week {
+            Week::This => &self.this,
+            Week::Next => &self.next,
+        }
+    }
+}
+
+/// CSS selectors describing where the menu table lives in a page
+#[derive(Deserialize, Clone, Debug)]
+pub struct Layout {
+    /// Rows holding main dishes
+    pub main_row: String,
+    /// Rows holding side dishes
+    pub side_row: String,
+    /// Cells inside a row (first one is the category)
+    pub cell: String,
+    /// Dish description inside a main dish cell
+    pub dish_text: String,
+}
+
+/// CSS selectors describing where the opening times of each mensa live
+#[derive(Deserialize, Clone, Debug)]
+pub struct TimesLayout {
+    /// Block holding the opening times of a single mensa
+    pub section: String,
+    /// Title of a block (contains the mensa name)
+    pub title: String,
+    /// Lines inside a block (e.g. "Mo - Fr: 11:30 - 14:30")
+    pub line: String,
+    /// Mensa slug -> name used in the titles
+    pub names: HashMap<String, String>,
+}
+
+pub const DEFAULT_LAYOUT: &str = "default";
+
 lazy_static! {
     pub static ref CONFIG: Config = toml::from_str(CONFIG_TOML).unwrap();
-    pub static ref THIS_WEEK: DeEnStr<String> = DeEnStr {
-        de: "diese".into(),
-        en: "this".into()
-    };
-    pub static ref NEXT_WEEK: DeEnStr<String> = DeEnStr {
-        de: "naechste".into(),
-        en: "next".into()
-    };
 }
 
 impl PathTemplate {
@@ -52,8 +113,8 @@ impl PathTemplate {
         template.replace("{{name}}", name).replace("{{week}}", week)
     }
 
-    pub fn fill_suffix(&self, mensa: &str, next_week: bool, english: bool) -> String {
-        let week_t = if next_week { &*NEXT_WEEK } else { &*THIS_WEEK };
+    pub fn fill_suffix(&self, mensa: &str, week: Week, english: bool) -> String {
+        let week_t = self.week_tokens.get(week);
         let (template, week) = if english {
             (&self.suffix_template.en, &week_t.en)
         } else {
@@ -62,8 +123,23 @@ impl PathTemplate {
         Self::fill_suffix_v(template, mensa, week)
     }
 
-    pub fn build_path(&self, mensa: &str, next_week: bool, english: bool) -> String {
-        self.prefix.clone() + "/" + &self.fill_suffix(mensa, next_week, english)
+    pub fn build_path(&self, mensa: &str, week: Week, english: bool) -> String {
+        self.prefix.clone() + "/" + &self.fill_suffix(mensa, week, english)
+    }
+}
+
+impl Config {
+    /// Get the layout for a mensa, falling back to the default one
+    pub fn layout_for(&self, mensa: &str) -> anyhow::Result<&Layout> {
+        let name = self
+            .mensa_layout
+            .get(mensa)
+            .map(String::as_str)
+            .unwrap_or(DEFAULT_LAYOUT);
+
+        self.layout
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("layout \"{name}\" (for {mensa}) is not defined"))
     }
 }
 
@@ -84,7 +160,7 @@ pub mod tests {
 
         // DE, this week
         assert_eq!(
-            CONFIG.endpoint.menu.build_path(&mensa, false, false),
+            CONFIG.endpoint.menu.build_path(mensa, Week::This, false),
             String::from_str("files/content/Downloads/Gastronomie/Speiseplaene/speiseplan_mensa_")
                 .unwrap()
                 + mensa
@@ -93,7 +169,7 @@ pub mod tests {
 
         // DE, next week
         assert_eq!(
-            CONFIG.endpoint.menu.build_path(&mensa, true, false),
+            CONFIG.endpoint.menu.build_path(mensa, Week::Next, false),
             String::from_str("files/content/Downloads/Gastronomie/Speiseplaene/speiseplan_mensa_")
                 .unwrap()
                 + mensa
@@ -102,7 +178,7 @@ pub mod tests {
 
         // EN, this week
         assert_eq!(
-            CONFIG.endpoint.menu.build_path(&mensa, false, true),
+            CONFIG.endpoint.menu.build_path(mensa, Week::This, true),
             String::from_str("files/content/Downloads/Gastronomie/Speiseplaene/menu_mensa_")
                 .unwrap()
                 + mensa
@@ -111,11 +187,56 @@ pub mod tests {
 
         // EN, next week
         assert_eq!(
-            CONFIG.endpoint.menu.build_path(&mensa, true, true),
+            CONFIG.endpoint.menu.build_path(mensa, Week::Next, true),
             String::from_str("files/content/Downloads/Gastronomie/Speiseplaene/menu_mensa_")
                 .unwrap()
                 + mensa
                 + "_next_week.html"
         );
     }
+
+    #[test]
+    pub fn week_tokens() {
+        let menu = &CONFIG.endpoint.menu;
+        assert_eq!(menu.week_tokens.get(Week::Next).de, "naechste");
+
+        let template = PathTemplate {
+            week_tokens: WeekTokens {
+                this: DeEnStr {
+                    de: "aktuelle".into(),
+                    en: "current".into(),
+                },
+                ..menu.week_tokens.clone()
+            },
+            ..menu.clone()
+        };
+        assert_eq!(
+            template.fill_suffix("vita", Week::This, false),
+            "speiseplan_mensa_vita_aktuelle_woche.html"
+        );
+        assert_eq!(
+            template.fill_suffix("vita", Week::This, true),
+            "menu_mensa_vita_current_week.html"
+        );
+        assert_eq!(
+            template.fill_suffix("vita", Week::Next, true),
+            "menu_mensa_vita_next_week.html"
+        );
+    }
+
+    #[test]
+    pub fn layout_fallback() {
+        let default = CONFIG.layout.get(DEFAULT_LAYOUT).unwrap();
+        let academica = CONFIG.layout_for("academica").unwrap();
+
+        assert_eq!(academica.main_row, default.main_row);
+        assert_eq!(default.main_row, "tr.main-dish");
+    }
+
+    #[test]
+    pub fn times_names() {
+        for mensa in ["academica", "ahornstrasse", "juelich"] {
+            assert!(CONFIG.times.names.contains_key(mensa));
+        }
+    }
 }
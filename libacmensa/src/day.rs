@@ -0,0 +1,420 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, str::FromStr};
+
+use crate::{
+    SchemaVersion,
+    meal::{self, AllergenList, MealInfo, SideInfo},
+};
+
+#[cfg(feature = "json-schema")]
+use schemars::JsonSchema;
+
+/// Order of the main dishes (see `DayView::sorted_by`)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortKey {
+    /// Cheapest first, meals without a price last.
+    Price,
+    /// By category, in the order of `MealType`.
+    #[default]
+    Category,
+    /// Alphabetically by description.
+    Name,
+}
+
+impl FromStr for SortKey {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "price" => Ok(SortKey::Price),
+            "category" => Ok(SortKey::Category),
+            "name" => Ok(SortKey::Name),
+            _ => anyhow::bail!("unknown sort key \"{s}\" (expected price, category or name)"),
+        }
+    }
+}
+
+impl Display for SortKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SortKey::Price => "price",
+            SortKey::Category => "category",
+            SortKey::Name => "name",
+        })
+    }
+}
+
+/// DayView is a view into a day of WeekData. Serializes like `DayData`, which is the type
+/// to parse it back into.
+#[derive(Clone, Debug, Serialize)]
+pub struct DayView<'a> {
+    pub main_dishes: &'a Vec<MealInfo>,
+    pub side_dishes: &'a Vec<SideInfo>,
+}
+
+/// DayData is like DayView but owns its data. It holds
+/// data for dishes for a single day.
+#[derive(Clone, Serialize, Deserialize)]
+#[cfg_attr(debug_assertions, derive(Debug))]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct DayData {
+    /// Main dishes array (Klassiker, Vegetarisch, Wok, ...)
+    pub main_dishes: Vec<MealInfo>,
+    /// Side dishes array (Sättigungsbeilagen/Gemüsebeilagen)
+    pub side_dishes: Vec<SideInfo>,
+}
+
+impl From<DayView<'_>> for DayData {
+    fn from(view: DayView<'_>) -> Self {
+        Self {
+            main_dishes: view.main_dishes.clone(),
+            side_dishes: view.side_dishes.clone(),
+        }
+    }
+}
+
+impl<'a> DayView<'a> {
+    /// Copies the day into an owned `DayData` (same as `DayData::from`)
+    pub fn to_owned(&self) -> DayData {
+        DayData::from(self.clone())
+    }
+
+    /// Main dishes of the day
+    pub fn iter_main(&self) -> std::slice::Iter<'a, MealInfo> {
+        self.main_dishes.iter()
+    }
+
+    /// Side dishes of the day
+    pub fn iter_sides(&self) -> std::slice::Iter<'a, SideInfo> {
+        self.side_dishes.iter()
+    }
+
+    /// Allergens contained in every main dish of the day
+    pub fn common_allergens(&self) -> meal::AllergenList {
+        meal::AllergenList::common(self.iter_main().map(|m| &m.allergens))
+    }
+
+    /// Only the dishes free of all `excluded` allergens. Sides keep their safe
+    /// alternatives and are dropped if none are left.
+    pub fn filter_allergens(&self, excluded: &[&str]) -> DayData {
+        self.filter_codes(|allergens, _| allergens.contains_any(excluded))
+    }
+
+    /// Only the dishes free of all `excluded` additives (see `filter_allergens`)
+    pub fn filter_additives(&self, excluded: &[&str]) -> DayData {
+        self.filter_codes(|_, additives| additives.contains_any(excluded))
+    }
+
+    /// Only the main dishes free of all `excluded` contents (e.g. no pork). Sides are
+    /// not flagged and kept as they are.
+    pub fn filter_contents(&self, excluded: meal::MeatFlags) -> DayData {
+        DayData {
+            main_dishes: self
+                .main_dishes
+                .iter()
+                .filter(|m| !m.contains_any(excluded))
+                .cloned()
+                .collect(),
+            side_dishes: self.side_dishes.clone(),
+        }
+    }
+
+    /// Only the main dishes costing at most `max_cents` (the student price), dropping
+    /// those without a parsed price. Sides are kept.
+    pub fn filter_max_price(&self, max_cents: i64) -> DayData {
+        DayData {
+            main_dishes: self
+                .main_dishes
+                .iter()
+                .filter(|m| {
+                    m.price_parsed
+                        .as_ref()
+                        .is_some_and(|p| p.cents <= max_cents)
+                })
+                .cloned()
+                .collect(),
+            side_dishes: self.side_dishes.clone(),
+        }
+    }
+
+    /// The day with its main dishes sorted by `key`. The sort is stable, so dishes that
+    /// compare equal keep their order on the page.
+    pub fn sorted_by(&self, key: SortKey) -> DayData {
+        let mut day = self.to_owned();
+        match key {
+            SortKey::Price => day.main_dishes.sort_by_key(|m| {
+                m.price_parsed
+                    .as_ref()
+                    .map_or((true, 0), |p| (false, p.cents))
+            }),
+            SortKey::Category => day.main_dishes.sort_by(|a, b| a.typ.cmp(&b.typ)),
+            SortKey::Name => day
+                .main_dishes
+                .sort_by_cached_key(|m| m.text.to_lowercase()),
+        }
+        day
+    }
+
+    /// Drops the dishes for which `unwanted(allergens, additives)` holds
+    fn filter_codes(
+        &self,
+        unwanted: impl Fn(&meal::AllergenList, &meal::AllergenList) -> bool,
+    ) -> DayData {
+        let main_dishes = self
+            .main_dishes
+            .iter()
+            .filter(|m| !unwanted(&m.allergens, &m.additives))
+            .cloned()
+            .collect();
+
+        let side_dishes = self
+            .side_dishes
+            .iter()
+            .map(|side| {
+                let mut side = side.clone();
+                side.alternatives
+                    .retain(|alt| !unwanted(&alt.allergens, &alt.additives));
+                side
+            })
+            .filter(|side| !side.alternatives.is_empty())
+            .collect();
+
+        DayData {
+            main_dishes,
+            side_dishes,
+        }
+    }
+}
+
+/// Moves the additives listed among the allergens (before schema version 2) to `additives`
+fn move_additives(allergens: &mut AllergenList, additives: &mut AllergenList) {
+    let (rest, moved) = std::mem::take(allergens).split_additives();
+    *allergens = rest;
+    *additives = additives.union(&moved);
+}
+
+/// Converts dishes written with layout `version` to the current one (see `SCHEMA_VERSION`)
+pub(crate) fn migrate_dishes(
+    version: SchemaVersion,
+    main_dishes: &mut [MealInfo],
+    side_dishes: &mut [SideInfo],
+) {
+    if version >= SchemaVersion(2) {
+        return;
+    }
+    for main in main_dishes {
+        move_additives(&mut main.allergens, &mut main.additives);
+    }
+    for alt in side_dishes
+        .iter_mut()
+        .flat_map(|side| &mut side.alternatives)
+    {
+        move_additives(&mut alt.allergens, &mut alt.additives);
+    }
+}
+
+impl DayData {
+    /// Parses a day as serialized by `DayData`/`DayView`, e.g. served by a caching server.
+    /// Also accepts the day files of `acmensa-cli export` (their `date` and `mensa` are
+    /// ignored). Days of older schema versions are migrated (see `DayData::migrate`).
+    #[cfg(feature = "json")]
+    pub fn from_json_str(json: &str) -> anyhow::Result<Self> {
+        #[derive(Deserialize)]
+        struct Versioned {
+            #[serde(default)]
+            schema_version: SchemaVersion,
+            #[serde(flatten)]
+            day: DayData,
+        }
+
+        let versioned = serde_json::from_str::<Versioned>(json)?;
+        Ok(versioned.day.migrate(versioned.schema_version))
+    }
+
+    /// Converts a day written with layout `version` to the current one, e.g. moves the
+    /// additives out of the allergens of days before version 2
+    pub fn migrate(mut self, version: SchemaVersion) -> Self {
+        migrate_dishes(version, &mut self.main_dishes, &mut self.side_dishes);
+        self
+    }
+
+    /// Returns a `DayView` into this day
+    pub fn view(&self) -> DayView<'_> {
+        DayView {
+            main_dishes: &self.main_dishes,
+            side_dishes: &self.side_dishes,
+        }
+    }
+
+    /// Only the dishes not already served in `previous` (see `MealInfo::same_dish`).
+    /// Side alternatives are compared by text within sides of the same type.
+    pub fn added_since(&self, previous: &DayData) -> DayData {
+        let main_dishes = self
+            .main_dishes
+            .iter()
+            .filter(|m| !previous.main_dishes.iter().any(|p| p.same_dish(m)))
+            .cloned()
+            .collect();
+
+        let side_dishes = self
+            .side_dishes
+            .iter()
+            .map(|side| {
+                let mut side = side.clone();
+                side.alternatives.retain(|alt| {
+                    !previous
+                        .side_dishes
+                        .iter()
+                        .filter(|p| p.typ == side.typ)
+                        .flat_map(|p| &p.alternatives)
+                        .any(|p| p.text == alt.text)
+                });
+                side
+            })
+            .filter(|side| !side.alternatives.is_empty())
+            .collect();
+
+        DayData {
+            main_dishes,
+            side_dishes,
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "json")]
+pub mod tests {
+    use super::*;
+    use crate::meal::{Content, MealType, MeatFlags, SideAlternative, SideType};
+
+    fn day() -> DayData {
+        DayData {
+            main_dishes: vec![MealInfo {
+                subtext: "| Pommes".into(),
+                allergens: serde_json::from_str(r#"["A", "G"]"#).unwrap(),
+                additives: serde_json::from_str(r#"["2"]"#).unwrap(),
+                id: Some("0123456789abcdef".into()),
+                ..MealInfo::new(MealType::Klassiker, "Schnitzel", "3,50 €")
+            }],
+            side_dishes: vec![SideInfo {
+                typ: SideType::Main,
+                label: "Sättigungsbeilage".into(),
+                alternatives: vec![SideAlternative {
+                    text: "Reis".into(),
+                    allergens: Default::default(),
+                    additives: Default::default(),
+                }],
+            }],
+        }
+    }
+
+    #[test]
+    pub fn json_round_trip() {
+        let day = day();
+        let json = serde_json::to_string(&day).unwrap();
+        let parsed = DayData::from_json_str(&json).unwrap();
+        assert_eq!(parsed.main_dishes, day.main_dishes);
+        assert_eq!(parsed.side_dishes, day.side_dishes);
+
+        // Views serialize like the owned day
+        assert_eq!(serde_json::to_string(&day.view()).unwrap(), json);
+
+        // Exported days carry their date and mensa next to the dishes
+        let mut export = serde_json::to_value(&day).unwrap();
+        export["date"] = "2026-10-16".into();
+        export["mensa"] = "vita".into();
+        let parsed = DayData::from_json_str(&export.to_string()).unwrap();
+        assert_eq!(parsed.main_dishes, day.main_dishes);
+
+        assert!(DayData::from_json_str(r#"{"main_dishes": []}"#).is_err());
+    }
+
+    #[test]
+    pub fn migrate_additives() {
+        // Before schema version 2, the additives were listed among the allergens
+        let mut old = serde_json::to_value(day()).unwrap();
+        old["main_dishes"][0]["allergens"] = serde_json::json!(["2", "A", "G"]);
+        old["main_dishes"][0]["additives"] = serde_json::json!([]);
+        old["side_dishes"][0]["alternatives"][0]["allergens"] = serde_json::json!(["9", "G"]);
+        old["schema_version"] = 1.into();
+
+        let migrated = DayData::from_json_str(&old.to_string()).unwrap();
+        assert_eq!(migrated.main_dishes, day().main_dishes);
+        let side = &migrated.side_dishes[0].alternatives[0];
+        assert_eq!(side.allergens.to_string(), "G");
+        assert_eq!(side.additives.to_string(), "9");
+
+        // Unversioned days are migrated too, current ones are left alone
+        old.as_object_mut().unwrap().remove("schema_version");
+        let migrated = DayData::from_json_str(&old.to_string()).unwrap();
+        assert_eq!(migrated.main_dishes, day().main_dishes);
+        old["schema_version"] = crate::SCHEMA_VERSION.into();
+        let current = DayData::from_json_str(&old.to_string()).unwrap();
+        assert_eq!(current.main_dishes[0].allergens.to_string(), "2, A, G");
+    }
+
+    #[test]
+    pub fn filter_codes() {
+        let day = day();
+        assert!(day.view().filter_allergens(&["G"]).main_dishes.is_empty());
+        assert_eq!(day.view().filter_allergens(&["2"]).main_dishes.len(), 1);
+        assert!(
+            day.view()
+                .filter_additives(&["2", "9"])
+                .main_dishes
+                .is_empty()
+        );
+        assert_eq!(day.view().filter_additives(&["A"]).main_dishes.len(), 1);
+        assert_eq!(day.view().filter_additives(&["2"]).side_dishes.len(), 1);
+
+        assert_eq!(day.view().filter_max_price(350).main_dishes.len(), 1);
+        assert!(day.view().filter_max_price(349).main_dishes.is_empty());
+
+        let pork = [Content::Pork].into_iter().collect();
+        assert_eq!(day.view().filter_contents(pork).main_dishes.len(), 1);
+        let mut day = day;
+        day.main_dishes[0].contains = pork;
+        assert!(
+            day.view()
+                .filter_contents(MeatFlags::ANIMAL)
+                .main_dishes
+                .is_empty()
+        );
+        assert_eq!(day.view().filter_contents(pork).side_dishes.len(), 1);
+    }
+
+    #[test]
+    pub fn sort_mains() {
+        let mut day = day();
+        let meal = MealInfo::new;
+        day.main_dishes = vec![
+            meal(MealType::Wok, "Bami Goreng", "4,20 €"),
+            meal(MealType::Klassiker, "schnitzel", ""),
+            meal(MealType::Tellergericht, "Eintopf", "2,20 €"),
+        ];
+        let texts = |key: SortKey| {
+            day.view()
+                .sorted_by(key)
+                .main_dishes
+                .into_iter()
+                .map(|m| m.text)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            texts(SortKey::Price),
+            ["Eintopf", "Bami Goreng", "schnitzel"]
+        );
+        assert_eq!(
+            texts(SortKey::Category),
+            ["schnitzel", "Eintopf", "Bami Goreng"]
+        );
+        assert_eq!(
+            texts(SortKey::Name),
+            ["Bami Goreng", "Eintopf", "schnitzel"]
+        );
+        assert_eq!("Price".parse::<SortKey>().unwrap(), SortKey::Price);
+        assert!("cost".parse::<SortKey>().is_err());
+    }
+}
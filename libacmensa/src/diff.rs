@@ -0,0 +1,191 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    meal::MealInfo,
+    scrape::{DatedDay, DayData},
+};
+
+/// Changes of the main meals of a day between two scrapes
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct MealDiff {
+    /// Meals that were not served before
+    pub added: Vec<MealInfo>,
+    /// Meals that are not served anymore
+    pub removed: Vec<MealInfo>,
+    /// Meals that are still served, but e.g. with another price or subtext
+    pub changed: Vec<MealChange>,
+}
+
+/// The same dish (see `MealInfo::dish_hash`) before and after a change
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MealChange {
+    pub before: MealInfo,
+    pub after: MealInfo,
+}
+
+/// `MealDiff` of a single day
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DayDiff {
+    /// Date of the day (ISO, YYYY-MM-DD)
+    pub date: NaiveDate,
+    #[serde(flatten)]
+    pub diff: MealDiff,
+}
+
+impl MealDiff {
+    /// Whether nothing changed
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl MealChange {
+    /// Names of the fields that differ (`text` only if just its spelling changed)
+    pub fn fields(&self) -> Vec<&'static str> {
+        let (a, b) = (&self.before, &self.after);
+        [
+            ("text", a.text.trim() != b.text.trim()),
+            ("subtext", a.subtext.trim() != b.subtext.trim()),
+            ("price", a.price.trim() != b.price.trim()),
+            ("allergens", a.allergens != b.allergens),
+            ("additives", a.additives != b.additives),
+            ("diet", a.diet != b.diet),
+            ("contains", a.contains != b.contains),
+        ]
+        .into_iter()
+        .filter_map(|(field, differs)| differs.then_some(field))
+        .collect()
+    }
+}
+
+impl DayData {
+    /// Compares the main meals of the day with an earlier version of it. Meals are
+    /// matched by `MealInfo::dish_hash`; a matched meal counts as changed if any field
+    /// listed by `MealChange::fields` differs.
+    pub fn diff(&self, previous: &DayData) -> MealDiff {
+        let mut unmatched = previous.main_dishes.iter().collect::<Vec<_>>();
+        let mut diff = MealDiff::default();
+
+        for meal in &self.main_dishes {
+            let hash = meal.dish_hash();
+            match unmatched.iter().position(|p| p.dish_hash() == hash) {
+                Some(i) => {
+                    let change = MealChange {
+                        before: unmatched.remove(i).clone(),
+                        after: meal.clone(),
+                    };
+                    if !change.fields().is_empty() {
+                        diff.changed.push(change);
+                    }
+                }
+                None => diff.added.push(meal.clone()),
+            }
+        }
+        diff.removed = unmatched.into_iter().cloned().collect();
+
+        diff
+    }
+}
+
+/// Compares `current` days with `previous` ones of the same date. Days only known on one
+/// side are skipped, as are days without changes.
+pub fn diff_days(previous: &[DatedDay], current: &[DatedDay]) -> Vec<DayDiff> {
+    current
+        .iter()
+        .filter_map(|day| {
+            let before = previous.iter().find(|p| p.date == day.date)?;
+            let diff = day.day.diff(&before.day);
+            (!diff.is_empty()).then_some(DayDiff {
+                date: day.date,
+                diff,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{SchemaVersion, meal::MealType};
+
+    fn day(date: u32, main_dishes: Vec<MealInfo>) -> DatedDay {
+        DatedDay {
+            schema_version: SchemaVersion::current(),
+            mensa: None,
+            date: NaiveDate::from_ymd_opt(2026, 10, date).unwrap(),
+            day: DayData {
+                main_dishes,
+                side_dishes: vec![],
+            },
+        }
+    }
+
+    #[test]
+    pub fn diff() {
+        let previous = vec![
+            day(
+                12,
+                vec![
+                    MealInfo::new(MealType::Klassiker, "Schnitzel", "3,50 €"),
+                    MealInfo::new(MealType::Wok, "Curry", "4,20 €"),
+                    MealInfo::new(MealType::Tellergericht, "Eintopf", "2,20 €"),
+                ],
+            ),
+            day(
+                13,
+                vec![MealInfo::new(MealType::Klassiker, "Gulasch", "3,50 €")],
+            ),
+        ];
+        let current = vec![
+            day(
+                12,
+                vec![
+                    // Only whitespace/case differs: the same dish
+                    MealInfo::new(MealType::Klassiker, "schnitzel ", "3,50 €"),
+                    MealInfo::new(MealType::Wok, "Curry", "4,50 €"),
+                    MealInfo::new(MealType::Tellergericht, "Linsensuppe", "2,20 €"),
+                ],
+            ),
+            day(
+                13,
+                vec![MealInfo::new(MealType::Klassiker, "Gulasch", "3,50 €")],
+            ),
+            day(
+                14,
+                vec![MealInfo::new(MealType::Klassiker, "Pasta", "3,50 €")],
+            ),
+        ];
+
+        let diffs = diff_days(&previous, &current);
+        assert_eq!(diffs.len(), 1);
+        let diff = &diffs[0].diff;
+        assert_eq!(
+            diffs[0].date,
+            NaiveDate::from_ymd_opt(2026, 10, 12).unwrap()
+        );
+        assert_eq!(
+            diff.added,
+            vec![MealInfo::new(
+                MealType::Tellergericht,
+                "Linsensuppe",
+                "2,20 €"
+            )]
+        );
+        assert_eq!(
+            diff.removed,
+            vec![MealInfo::new(MealType::Tellergericht, "Eintopf", "2,20 €")]
+        );
+        assert_eq!(diff.changed.len(), 2);
+        assert_eq!(diff.changed[0].fields(), vec!["text"]);
+        assert_eq!(diff.changed[1].fields(), vec!["price"]);
+
+        // Same text in another category is another dish
+        let before = day(12, vec![MealInfo::new(MealType::Wok, "Curry", "")]).day;
+        let after = day(12, vec![MealInfo::new(MealType::Klassiker, "Curry", "")]).day;
+        let diff = after.diff(&before);
+        assert_eq!((diff.added.len(), diff.removed.len()), (1, 1));
+
+        assert!(before.diff(&before).is_empty());
+    }
+}
@@ -1,20 +1,132 @@
-use serde::Deserialize;
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "json-schema")]
+use schemars::JsonSchema;
 
 /// Meal data model. Can be used standalone to parse e.g. JSONs from
 /// a caching server.
 pub mod meal;
 
+/// Metadata (names, addresses, locations) of the supported mensen.
+pub mod mensa;
+
+/// Dishes of a single day (owned and borrowed). Can be used standalone to parse the
+/// JSONs of `acmensa-cli export` or a caching server (see `DayData::from_json_str`).
+pub mod day;
+
+/// Opening times data model.
+pub mod times;
+
+/// Flat (tabular) representation of menu data, e.g. for CSV.
+pub mod records;
+
 /// Configuration for scraping.
-#[cfg(feature = "scrape")]
+#[cfg(feature = "parse")]
 pub(crate) mod config;
 
-/// Scraper module.
+/// On-disk cache for fetched pages.
 #[cfg(feature = "scrape")]
+pub mod cache;
+
+/// Scraper module.
+#[cfg(feature = "parse")]
 pub mod scrape;
 
+/// Statistics over a range of days (frequent meals, prices, vegan share).
+#[cfg(feature = "parse")]
+pub mod stats;
+
+/// Change detection between two scrapes of the same days.
+#[cfg(feature = "parse")]
+pub mod diff;
+
+/// HTTP client with retries and page cache.
+#[cfg(feature = "scrape")]
+pub mod client;
+
+/// Local SQLite history of scraped menus.
+#[cfg(feature = "archive")]
+pub mod archive;
+
+/// Export into the OpenMensa XML feed format.
+#[cfg(feature = "openmensa")]
+pub mod openmensa;
+
+/// Layout version of serialized documents (days, weeks, opening times). Bumped on breaking
+/// changes, so that consumers can detect them.
+///
+/// - 1: `schema_version` added
+/// - 2: additives are listed separately from the allergens (`MealInfo::additives`)
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// `schema_version` field of serialized documents. New documents carry `SCHEMA_VERSION`
+/// (see `SchemaVersion::current`), documents written before the field existed are read as
+/// the default version 0.
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(transparent)]
+pub struct SchemaVersion(pub u32);
+
+impl SchemaVersion {
+    /// Version of documents written by this version of the library
+    pub fn current() -> Self {
+        Self(SCHEMA_VERSION)
+    }
+
+    /// Whether the document has the layout of this version of the library
+    pub fn is_current(&self) -> bool {
+        self.0 == SCHEMA_VERSION
+    }
+}
+
 /// Holds a German and English version of a string (e.g. for endpoint templates)
 #[derive(Deserialize, Clone, Debug)]
 pub struct DeEnStr<T> {
     pub de: T,
     pub en: T,
 }
+
+/// Week of a menu, relative to the current one
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Week {
+    #[default]
+    This,
+    Next,
+}
+
+impl Week {
+    /// All weeks a menu is published for
+    pub const ALL: [Week; 2] = [Week::This, Week::Next];
+
+    /// Number of weeks after the current one
+    pub fn offset(&self) -> i64 {
+        match self {
+            Week::This => 0,
+            Week::Next => 1,
+        }
+    }
+}
+
+/// `true` is the next week (compatible with the former `next_week: bool` parameters)
+impl From<bool> for Week {
+    fn from(next_week: bool) -> Self {
+        if next_week { Week::Next } else { Week::This }
+    }
+}
+
+impl From<Week> for bool {
+    fn from(week: Week) -> Self {
+        week == Week::Next
+    }
+}
+
+impl Display for Week {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Week::This => "this",
+            Week::Next => "next",
+        })
+    }
+}
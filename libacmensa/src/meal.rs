@@ -1,7 +1,13 @@
 use crate::DeEnStr;
 use lazy_static::lazy_static;
-use serde::{Deserialize, Serialize};
-use std::{collections::BTreeSet, fmt::Display, str::FromStr};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeSet, HashMap},
+    fmt::Display,
+    str::FromStr,
+};
 
 #[cfg(feature = "json-schema")]
 use schemars::JsonSchema;
@@ -36,6 +42,10 @@ pub enum SideType {
     Main,
     /// Secondary side (Gemüsebeilage).
     Secondary,
+    /// Salad (Salat).
+    Salad,
+    /// Dessert.
+    Dessert,
     /// Catch-all for sides that could not be parsed.
     Unknown,
 }
@@ -56,23 +66,87 @@ pub struct MealInfo {
     /// Meal price (unparsed directly from site). Usually has the form "{:.02f} €".
     pub price: String,
 
-    /// Sorted, deduplicated list of allergens.
+    /// Numeric price in EUR as parsed from `price` (see `parse_price`). A `Decimal` rather
+    /// than a float so that sums of prices are exact.
+    #[serde(default)]
+    pub price_value: Option<Decimal>,
+
+    /// Structured price (in cents, with the price tiers if the page lists several).
+    #[serde(default)]
+    pub price_parsed: Option<Price>,
+
+    /// Sorted, deduplicated list of allergens (letter codes, e.g. "A1").
     pub allergens: AllergenList,
 
-    /// Vegan indication. Especially needed since sometimes "normal" meals are
+    /// Sorted, deduplicated list of additives (Zusatzstoffe, number codes, e.g. "2"). Part of
+    /// `allergens` before schema version 2.
+    #[serde(default)]
+    pub additives: AllergenList,
+
+    /// Diet indication. Especially needed since sometimes "normal" meals are
     /// hijacked and replaced with vegan ones. Secondary heuristics are defined
-    /// in `libacmensa::scrape::vegan_detektiv`.
-    pub vegan: bool,
+    /// in `libacmensa::scrape::vegan_detektiv`. Also read from the former `vegan` flag.
+    #[serde(alias = "vegan", deserialize_with = "Diet::deserialize_compat")]
+    pub diet: Diet,
+
+    /// Meat, fish and alcohol the meal contains (see `scrape::content_detektiv`).
+    #[serde(default)]
+    pub contains: MeatFlags,
+
+    /// Stable identifier (see `MealInfo::id`). Set once the mensa and date of the meal are
+    /// known, e.g. by `WeekData::with_mensa`/`WeekData::with_week_start`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+}
+
+/// Dietary classification of a meal
+#[derive(
+    Clone, Copy, Default, Hash, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize,
+)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum Diet {
+    /// No animal products.
+    Vegan,
+    /// No meat or fish.
+    Vegetarian,
+    /// Contains meat or fish.
+    Omnivore,
+    /// Could not be determined.
+    #[default]
+    Unknown,
+}
+
+/// Kind of content flagged in `MeatFlags`
+#[derive(Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum Content {
+    Pork,
+    Beef,
+    Poultry,
+    Fish,
+    Alcohol,
 }
 
+/// Bitfield of the `Content`s of a meal. Serialized as a list, e.g. `["Pork", "Alcohol"]`.
+#[derive(
+    Clone, Copy, Default, Hash, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize,
+)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(from = "Vec<Content>", into = "Vec<Content>")]
+pub struct MeatFlags(u8);
+
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
 #[cfg_attr(feature = "json-schema", derive(JsonSchema))]
 pub struct SideAlternative {
     /// Main alternative description.
     pub text: String,
 
-    /// Sorted, deduplicated list of allergens.
+    /// Sorted, deduplicated list of allergens (letter codes).
     pub allergens: AllergenList,
+
+    /// Sorted, deduplicated list of additives (number codes).
+    #[serde(default)]
+    pub additives: AllergenList,
 }
 
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
@@ -82,15 +156,62 @@ pub struct SideInfo {
     #[serde(rename = "type")]
     pub typ: SideType,
 
+    /// Category name as found on the page (e.g. "Sättigungsbeilage").
+    #[serde(default)]
+    pub label: String,
+
     /// Alternative options for side.
     pub alternatives: Vec<SideAlternative>,
 }
 
+/// Price of a meal as parsed from `MealInfo::price`.
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
 #[cfg_attr(feature = "json-schema", derive(JsonSchema))]
-pub struct AllergenList(BTreeSet<String>);
+pub struct Price {
+    /// Price in cents. The student price if there are several tiers.
+    pub cents: i64,
+
+    /// Currency code (e.g. "EUR").
+    pub currency: String,
+
+    /// Prices by tier, if the page lists more than one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tiers: Option<PriceTiers>,
+}
+
+/// Prices (in cents) for the different groups of customers.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct PriceTiers {
+    /// Students (Studierende).
+    pub student: i64,
+
+    /// Employees (Bedienstete).
+    pub employee: Option<i64>,
+
+    /// Guests (Gäste).
+    pub guest: Option<i64>,
+}
+
+/// Set of allergen or additive codes
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct AllergenList(BTreeSet<AllergenCode>);
+
+/// A single allergen/additive code (e.g. "A1", "3"). Codes are ordered naturally,
+/// i.e. numbers inside them are compared by value ("A2" < "A10").
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+#[serde(transparent)]
+pub struct AllergenCode(pub String);
+
+const ALLERGENS_TOML: &str = include_str!("../../res/allergens.toml");
 
 lazy_static! {
+    /// Code -> name of the allergens/additives
+    static ref ALLERGEN_NAMES: HashMap<String, DeEnStr<String>> =
+        toml::from_str(ALLERGENS_TOML).unwrap();
+
     /// Type<->Name matcher definitions
     static ref NAMES_MAIN: Vec<(MealType, DeEnStr<&'static str>)> = vec![
         (
@@ -159,33 +280,52 @@ lazy_static! {
             SideType::Main,
             DeEnStr {
                 de: "Sättigungsbeilage",
-                en: "Main side-dish"
+                en: "Main side dish"
             }
         ),
         (
             SideType::Main,
             DeEnStr {
                 de: "Hauptbeilage",
-                en: "Main side-dish"
+                en: "Main side dish"
             }
         ),
         (
             SideType::Secondary,
             DeEnStr {
                 de: "Gemüsebeilage",
-                en: "Secondary"
+                en: "Secondary side dish"
             }
         ),
         (
             SideType::Secondary,
             DeEnStr {
                 de: "Nebenbeilage",
-                en: "Secondary"
+                en: "Secondary side dish"
+            }
+        ),
+        (
+            SideType::Salad,
+            DeEnStr {
+                de: "Salat",
+                en: "Salad"
+            }
+        ),
+        (
+            SideType::Dessert,
+            DeEnStr {
+                de: "Dessert",
+                en: "Dessert"
             }
         )
     ];
 }
 
+/// Normalizes a category name for matching (case- and hyphen-insensitive)
+fn matcher_key(s: &str) -> String {
+    s.to_lowercase().replace('-', " ")
+}
+
 impl MealType {
     pub fn name(&self, english: bool) -> &str {
         let name = NAMES_MAIN
@@ -198,6 +338,43 @@ impl MealType {
             });
         if english { name.en } else { name.de }
     }
+
+    /// Every category, `Unbekannt` last
+    pub const ALL: [MealType; 9] = [
+        MealType::Klassiker,
+        MealType::Tellergericht,
+        MealType::Empfehlung,
+        MealType::Wok,
+        MealType::BurgerClassics,
+        MealType::BurgerWoche,
+        MealType::PizzaTag,
+        MealType::Vegetarisch,
+        MealType::Unbekannt,
+    ];
+
+    /// Category with exactly this name: the variant (e.g. `BurgerWoche`) or the German or
+    /// English name (e.g. "Burger der Woche"), ignoring case, spaces, hyphens and underscores.
+    /// Unlike `FromStr`, which looks for the names inside page headings, unknown names are
+    /// `None`.
+    pub fn from_name(s: &str) -> Option<Self> {
+        let key = |s: &str| {
+            s.chars()
+                .filter(|c| !matches!(c, ' ' | '-' | '_'))
+                .flat_map(char::to_lowercase)
+                .collect::<String>()
+        };
+        let s = key(s);
+        Self::ALL.into_iter().find(|typ| {
+            [format!("{typ:?}").as_str(), typ.name(false), typ.name(true)]
+                .iter()
+                .any(|name| key(name) == s)
+        })
+    }
+
+    /// Whether the category is a rotating special offer (burger of the week, pizza of the day)
+    pub fn is_special(&self) -> bool {
+        matches!(self, MealType::BurgerWoche | MealType::PizzaTag)
+    }
 }
 
 impl SideType {
@@ -219,9 +396,9 @@ impl FromStr for MealType {
 
     /// Get MealType from Str. CANNOT FAIL. Safe to unwrap.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let s = s.to_lowercase();
+        let s = matcher_key(s);
         for it in NAMES_MAIN.iter() {
-            if s.contains(&it.1.en.to_lowercase()) || s.contains(&it.1.de.to_lowercase()) {
+            if s.contains(&matcher_key(it.1.en)) || s.contains(&matcher_key(it.1.de)) {
                 return Ok(it.0.clone());
             }
         }
@@ -242,9 +419,9 @@ impl FromStr for SideType {
 
     /// Get SideType from Str. CANNOT FAIL. Safe to unwrap.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let s = s.to_lowercase();
+        let s = matcher_key(s);
         for it in NAMES_SIDE.iter() {
-            if s.contains(&it.1.en.to_lowercase()) || s.contains(&it.1.de.to_lowercase()) {
+            if s.contains(&matcher_key(it.1.en)) || s.contains(&matcher_key(it.1.de)) {
                 return Ok(it.0.clone());
             }
         }
@@ -258,6 +435,104 @@ impl SideType {
         //  SAFETY: See implementation of `FromStr` for `Self`
         unsafe { Self::from_str(s).unwrap_unchecked() }
     }
+
+    /// Default position of the side type when displaying a day (lower comes first).
+    pub fn display_order(&self) -> usize {
+        match self {
+            SideType::Main => 0,
+            SideType::Secondary => 1,
+            SideType::Salad => 2,
+            SideType::Dessert => 3,
+            SideType::Unknown => 4,
+        }
+    }
+}
+
+impl Ord for AllergenCode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        natural_cmp(&self.0, &other.0)
+    }
+}
+
+impl PartialOrd for AllergenCode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for AllergenCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AllergenCode {
+    /// Whether this is an additive (Zusatzstoff, a number like "2") rather than an allergen
+    /// (a letter like "A" or "A1")
+    pub fn is_additive(&self) -> bool {
+        !self.0.is_empty() && self.0.chars().all(|c| c.is_ascii_digit())
+    }
+
+    /// Code this one is a specialisation of (e.g. "A" for "A1"), if it is known
+    fn parent(&self) -> Option<&str> {
+        let parent = self.0.trim_end_matches(|c: char| c.is_ascii_digit());
+        (!parent.is_empty() && parent != self.0 && ALLERGEN_NAMES.contains_key(parent))
+            .then_some(parent)
+    }
+
+    /// Human-readable name (e.g. "Gluten (Weizen)" for "A1"). Unknown codes are returned as-is.
+    pub fn describe(&self, english: bool) -> String {
+        let name = |code: &str| {
+            ALLERGEN_NAMES
+                .get(code)
+                .map(|n| if english { &n.en } else { &n.de })
+                .cloned()
+        };
+
+        match (name(&self.0), self.parent().and_then(name)) {
+            (Some(name), Some(parent)) => format!("{parent} ({name})"),
+            (Some(name), None) => name,
+            (None, _) => self.0.clone(),
+        }
+    }
+}
+
+/// Compares strings chunk by chunk, where runs of digits are compared by their value
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a.chars().peekable(), b.chars().peekable());
+    loop {
+        match (a.peek().copied(), b.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let take_number = |it: &mut std::iter::Peekable<std::str::Chars>| {
+                    let mut digits = String::new();
+                    while let Some(c) = it.next_if(char::is_ascii_digit) {
+                        digits.push(c);
+                    }
+                    digits
+                };
+                let (x, y) = (take_number(&mut a), take_number(&mut b));
+                let (x_trim, y_trim) = (x.trim_start_matches('0'), y.trim_start_matches('0'));
+                let ord = x_trim
+                    .len()
+                    .cmp(&y_trim.len())
+                    .then_with(|| x_trim.cmp(y_trim))
+                    .then_with(|| x.len().cmp(&y.len()));
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+            }
+            (Some(x), Some(y)) => {
+                if x != y {
+                    return x.cmp(&y);
+                }
+                a.next();
+                b.next();
+            }
+        }
+    }
 }
 
 impl Display for AllergenList {
@@ -270,14 +545,14 @@ impl Display for AllergenList {
                     res.push_str(", ");
                 }
 
-                res.push_str(item);
+                res.push_str(&item.0);
                 res
             }),
         )
     }
 }
 
-#[cfg(feature = "scrape")]
+#[cfg(feature = "parse")]
 impl From<(&regex::Regex, &str)> for AllergenList {
     fn from((regex, s): (&regex::Regex, &str)) -> Self {
         Self(
@@ -285,14 +560,700 @@ impl From<(&regex::Regex, &str)> for AllergenList {
                 .captures_iter(s)
                 .filter_map(|caps| caps.get(1).map(|m| m.as_str()))
                 .flat_map(|s| s.split(","))
-                .map(str::to_string)
+                .map(|code| AllergenCode(code.to_string()))
                 .collect(),
         )
     }
 }
 
+/// Canonical form of a meal description for comparisons: lowercase, whitespace collapsed
+/// to single spaces and trailing punctuation removed.
+pub fn normalize_text(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_end_matches(|c: char| ".,;:!?-".contains(c) || c.is_whitespace())
+        .to_lowercase()
+}
+
+/// 64-bit FNV-1a hash. Used for identifiers that have to be stable across builds and
+/// platforms (unlike `std::hash::DefaultHasher`).
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Parses a price as found on the menu pages ("2,50 €", "4,10€", "3.20 EUR").
+/// A single `,` or `.` is the decimal separator; if both occur, the last one is.
+/// Returns `None` for empty or unparseable prices.
+pub fn parse_price(price: &str) -> Option<Decimal> {
+    let number = price
+        .trim()
+        .trim_end_matches(|c: char| c == '€' || c.is_alphabetic() || c.is_whitespace());
+    let decimal_sep = number.chars().rfind(|c| *c == ',' || *c == '.');
+
+    let normalized = number
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .filter_map(|c| match c {
+            ',' | '.' if Some(c) == decimal_sep => Some('.'),
+            ',' | '.' => None,
+            c => Some(c),
+        })
+        .collect::<String>();
+
+    Decimal::from_str(&normalized).ok()
+}
+
+/// Formats a price in EUR in the style of the given language ("2,50 €" or "€2.50").
+pub fn format_price(value: Decimal, english: bool) -> String {
+    let value = format!("{:.2}", value.round_dp(2));
+    if english {
+        format!("€{value}")
+    } else {
+        format!("{} €", value.replace('.', ","))
+    }
+}
+
+/// Tier a labelled price belongs to ("Studierende 2,50 €", "guests: 4,50 €")
+fn price_tier(label: &str) -> Option<usize> {
+    let label = label.to_lowercase();
+    if label.contains("stud") {
+        Some(0)
+    } else if ["bedienst", "mitarb", "employee", "staff"]
+        .iter()
+        .any(|k| label.contains(k))
+    {
+        Some(1)
+    } else if ["gast", "gäst", "guest"].iter().any(|k| label.contains(k)) {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+impl Price {
+    /// Parses a raw price string. Several prices separated by "/" or "|" are read as
+    /// student/employee/guest tiers, in that order unless they are labelled.
+    pub fn parse(raw: &str) -> Option<Self> {
+        let mut tiers: [Option<i64>; 3] = [None; 3];
+        let parts = raw
+            .split(['/', '|', ';', '\n'])
+            .filter(|p| p.chars().any(|c| c.is_ascii_digit()))
+            .collect::<Vec<_>>();
+
+        for (i, part) in parts.iter().enumerate() {
+            let start = part.find(|c: char| c.is_ascii_digit())?;
+            let end = part.rfind(|c: char| c.is_ascii_digit())? + 1;
+            let cents = (parse_price(&part[start..end])? * Decimal::ONE_HUNDRED)
+                .round()
+                .try_into()
+                .ok()?;
+
+            let label = part[..start].to_string() + &part[end..];
+            let tier = price_tier(&label).unwrap_or(i);
+            *tiers.get_mut(tier)? = Some(cents);
+        }
+
+        let [student, employee, guest] = tiers;
+        let student = student?;
+        Some(Self {
+            cents: student,
+            currency: "EUR".to_string(),
+            tiers: (parts.len() > 1).then_some(PriceTiers {
+                student,
+                employee,
+                guest,
+            }),
+        })
+    }
+
+    /// Price as a decimal number (e.g. 2.50)
+    pub fn value(&self) -> Decimal {
+        Decimal::new(self.cents, 2)
+    }
+}
+
+impl MealInfo {
+    /// Meal of a category with a description and a price (parsed, see `Price::parse`), but
+    /// no subtext, codes or diet, e.g. for placeholders and tests
+    pub fn new(typ: MealType, text: impl Into<String>, price: &str) -> Self {
+        let price_parsed = Price::parse(price);
+        Self {
+            typ,
+            text: text.into(),
+            subtext: String::new(),
+            price: price.into(),
+            price_value: price_parsed.as_ref().map(Price::value),
+            price_parsed,
+            allergens: Default::default(),
+            additives: Default::default(),
+            diet: Diet::Unknown,
+            contains: Default::default(),
+            id: None,
+        }
+    }
+
+    /// Whether two meals are the same dish (same category and description), regardless of
+    /// the day they are served on or their price.
+    pub fn same_dish(&self, other: &MealInfo) -> bool {
+        self.typ == other.typ && normalize_text(&self.text) == normalize_text(&other.text)
+    }
+
+    /// Stable identity of the dish, see `same_dish`: a hash of the category and the
+    /// normalized description, so it survives whitespace tweaks and price changes.
+    pub fn dish_hash(&self) -> u64 {
+        fnv1a(format!("{:?}|{}", self.typ, normalize_text(&self.text)).as_bytes())
+    }
+
+    /// Stable identifier of the meal served at `mensa` (slug) on `date` (YYYY-MM-DD): a hash
+    /// of both and the `dish_hash`, as 16 hex digits. Unlike the position in the menu, it
+    /// stays the same across re-scrapes, even if the description gets whitespace tweaks.
+    pub fn id(&self, mensa: &str, date: &str) -> String {
+        format!(
+            "{:016x}",
+            fnv1a(format!("{mensa}|{date}|{:016x}", self.dish_hash()).as_bytes())
+        )
+    }
+
+    /// Sets `MealInfo::id`
+    pub fn with_id(self, mensa: &str, date: &str) -> Self {
+        Self {
+            id: Some(self.id(mensa, date)),
+            ..self
+        }
+    }
+
+    /// Whether the meal description contains `query` (compared via `normalize_text`)
+    pub fn matches(&self, query: &str) -> bool {
+        let query = normalize_text(query);
+        !query.is_empty() && normalize_text(&self.text).contains(&query)
+    }
+
+    /// Whether the description or the subtext contain `query` (case-insensitive, see
+    /// `normalize_text`)
+    pub fn mentions(&self, query: &str) -> bool {
+        let query = normalize_text(query);
+        !query.is_empty()
+            && [&self.text, &self.subtext]
+                .iter()
+                .any(|text| normalize_text(text).contains(&query))
+    }
+
+    /// First of `terms` the meal description contains (see `MealInfo::matches`)
+    pub fn first_match<'a, S: AsRef<str>>(&self, terms: &'a [S]) -> Option<&'a str> {
+        terms
+            .iter()
+            .map(AsRef::as_ref)
+            .find(|term| self.matches(term))
+    }
+
+    /// Whether the meal contains the allergen `code` (trimmed, case-insensitive)
+    pub fn has_allergen(&self, code: &str) -> bool {
+        self.allergens.contains(code)
+    }
+
+    /// Whether the meal contains any of the allergens in `codes`
+    pub fn has_any_allergen(&self, codes: &[&str]) -> bool {
+        self.allergens.contains_any(codes)
+    }
+
+    /// Whether the meal contains any of `contents` (e.g. `--no-pork`)
+    pub fn contains_any(&self, contents: MeatFlags) -> bool {
+        self.contains.intersects(contents)
+    }
+
+    /// Removes the parts of `subtext` (separated by "|") that merely repeat `text`.
+    pub fn clean(mut self) -> Self {
+        let text = normalize_text(&self.text);
+        let segments = self.subtext.split('|').map(str::trim).collect::<Vec<_>>();
+        let kept = segments
+            .iter()
+            .filter(|seg| seg.is_empty() || !text.contains(&normalize_text(seg)))
+            .copied()
+            .collect::<Vec<_>>();
+
+        if kept.len() != segments.len() {
+            let kept = kept
+                .into_iter()
+                .filter(|seg| !seg.is_empty())
+                .collect::<Vec<_>>();
+            self.subtext = match (self.subtext.trim_start().starts_with('|'), kept.is_empty()) {
+                (_, true) => String::new(),
+                (true, false) => format!("| {}", kept.join(" | ")),
+                (false, false) => kept.join(" | "),
+            };
+        }
+
+        self
+    }
+}
+
+impl Diet {
+    /// Whether the meal is vegan
+    pub fn is_vegan(&self) -> bool {
+        *self == Diet::Vegan
+    }
+
+    /// Whether the meal is vegetarian (vegan meals are, too)
+    pub fn is_vegetarian(&self) -> bool {
+        matches!(self, Diet::Vegan | Diet::Vegetarian)
+    }
+
+    /// Deserializes a `Diet` or the former `vegan: bool`. `true` used to mean "vegan or
+    /// vegetarian", so it is read as `Vegetarian`.
+    fn deserialize_compat<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Diet, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Compat {
+            Diet(Diet),
+            Vegan(bool),
+        }
+
+        Ok(match Compat::deserialize(deserializer)? {
+            Compat::Diet(diet) => diet,
+            Compat::Vegan(true) => Diet::Vegetarian,
+            Compat::Vegan(false) => Diet::Unknown,
+        })
+    }
+}
+
+impl Content {
+    pub const ALL: [Content; 5] = [
+        Content::Pork,
+        Content::Beef,
+        Content::Poultry,
+        Content::Fish,
+        Content::Alcohol,
+    ];
+
+    /// Localised name
+    pub fn name(&self, english: bool) -> &'static str {
+        match (self, english) {
+            (Content::Pork, false) => "Schwein",
+            (Content::Pork, true) => "Pork",
+            (Content::Beef, false) => "Rind",
+            (Content::Beef, true) => "Beef",
+            (Content::Poultry, false) => "Geflügel",
+            (Content::Poultry, true) => "Poultry",
+            (Content::Fish, false) => "Fisch",
+            (Content::Fish, true) => "Fish",
+            (Content::Alcohol, false) => "Alkohol",
+            (Content::Alcohol, true) => "Alcohol",
+        }
+    }
+
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+impl MeatFlags {
+    /// Meat and fish, everything but alcohol
+    pub const ANIMAL: MeatFlags = MeatFlags(0b1111);
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn contains(&self, content: Content) -> bool {
+        self.0 & content.bit() != 0
+    }
+
+    /// Whether any of the flags in `other` are set
+    pub fn intersects(&self, other: MeatFlags) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    pub fn insert(&mut self, content: Content) {
+        self.0 |= content.bit();
+    }
+
+    /// The flags not set in `other`
+    pub fn difference(&self, other: MeatFlags) -> MeatFlags {
+        MeatFlags(self.0 & !other.0)
+    }
+
+    /// The set flags in the order of `Content::ALL`
+    pub fn iter(&self) -> impl Iterator<Item = Content> + '_ {
+        Content::ALL.into_iter().filter(|c| self.contains(*c))
+    }
+
+    /// Localised names, comma separated (e.g. "Schwein, Alkohol")
+    pub fn describe(&self, english: bool) -> String {
+        self.iter()
+            .map(|c| c.name(english))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+impl FromIterator<Content> for MeatFlags {
+    fn from_iter<T: IntoIterator<Item = Content>>(iter: T) -> Self {
+        let mut flags = MeatFlags::default();
+        iter.into_iter().for_each(|c| flags.insert(c));
+        flags
+    }
+}
+
+impl From<Vec<Content>> for MeatFlags {
+    fn from(contents: Vec<Content>) -> Self {
+        contents.into_iter().collect()
+    }
+}
+
+impl From<MeatFlags> for Vec<Content> {
+    fn from(flags: MeatFlags) -> Self {
+        flags.iter().collect()
+    }
+}
+
+impl Display for MeatFlags {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.describe(true))
+    }
+}
+
+impl SideInfo {
+    /// Name to display for the side. Falls back to the raw label from the page for
+    /// sides whose type could not be inferred.
+    pub fn display_name(&self, english: bool) -> &str {
+        if self.typ == SideType::Unknown && !self.label.is_empty() {
+            &self.label
+        } else {
+            self.typ.name(english)
+        }
+    }
+}
+
 impl AllergenList {
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
+
+    /// Whether the list contains `code` (trimmed, case-insensitive)
+    pub fn contains(&self, code: &str) -> bool {
+        let code = code.trim();
+        self.0.iter().any(|c| c.0.eq_ignore_ascii_case(code))
+    }
+
+    /// Whether the list contains any of `codes` (see `contains`)
+    pub fn contains_any(&self, codes: &[&str]) -> bool {
+        codes.iter().any(|code| self.contains(code))
+    }
+
+    /// Human-readable names of the allergens, comma separated. Codes that are only the
+    /// parent of another listed code (e.g. "A" next to "A1") are left out.
+    pub fn describe(&self, english: bool) -> String {
+        self.0
+            .iter()
+            .filter(|code| !self.0.iter().any(|c| c.parent() == Some(&code.0)))
+            .map(|code| code.describe(english))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Splits the codes into allergens and additives (see `AllergenCode::is_additive`)
+    pub fn split_additives(self) -> (AllergenList, AllergenList) {
+        let (additives, allergens) = self.0.into_iter().partition(AllergenCode::is_additive);
+        (Self(allergens), Self(additives))
+    }
+
+    /// Allergens contained in either list
+    pub fn union(&self, other: &AllergenList) -> AllergenList {
+        Self(self.0.union(&other.0).cloned().collect())
+    }
+
+    /// Allergens contained in both lists
+    pub fn intersection(&self, other: &AllergenList) -> AllergenList {
+        Self(self.0.intersection(&other.0).cloned().collect())
+    }
+
+    /// Allergens contained in every list (empty if there are no lists)
+    pub fn common<'a>(lists: impl IntoIterator<Item = &'a AllergenList>) -> AllergenList {
+        let mut lists = lists.into_iter();
+        let first = lists.next().cloned().unwrap_or_default();
+        lists.fold(first, |acc, list| acc.intersection(list))
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    pub fn meal_type_from_name() {
+        assert_eq!(
+            MealType::from_name("BurgerClassics"),
+            Some(MealType::BurgerClassics)
+        );
+        assert_eq!(
+            MealType::from_name("burger-der-woche"),
+            Some(MealType::BurgerWoche)
+        );
+        assert_eq!(MealType::from_name("Classics"), Some(MealType::Klassiker));
+        assert_eq!(MealType::from_name("unknown"), Some(MealType::Unbekannt));
+        assert_eq!(MealType::from_name("Klassiker Spezial"), None);
+        assert_eq!(MealType::from_name("Burger"), None);
+    }
+
+    #[test]
+    pub fn infer_side_de() {
+        assert_eq!(SideType::infer("Sättigungsbeilage"), SideType::Main);
+        assert_eq!(SideType::infer("Hauptbeilagen"), SideType::Main);
+        assert_eq!(SideType::infer("Gemüsebeilage"), SideType::Secondary);
+        assert_eq!(SideType::infer("Nebenbeilage"), SideType::Secondary);
+    }
+
+    #[test]
+    pub fn infer_side_en() {
+        assert_eq!(SideType::infer("Main side dish"), SideType::Main);
+        assert_eq!(SideType::infer("Main side-dishes"), SideType::Main);
+        assert_eq!(SideType::infer("Secondary side dish"), SideType::Secondary);
+        assert_eq!(
+            SideType::infer("<b>Secondary Side Dish</b>"),
+            SideType::Secondary
+        );
+        assert_eq!(SideType::infer("Salad"), SideType::Salad);
+        assert_eq!(SideType::infer("Soup"), SideType::Unknown);
+    }
+
+    #[test]
+    pub fn matches_query() {
+        let meal = MealInfo::new(MealType::Klassiker, "Schweineschnitzel  mit Pommes", "");
+        assert!(meal.matches("schnitzel"));
+        assert!(meal.matches("Schnitzel mit pommes."));
+        assert!(!meal.matches("Currywurst"));
+        assert!(!meal.matches(" "));
+        assert_eq!(meal.first_match(&["Currywurst", "POMMES"]), Some("POMMES"));
+        assert_eq!(meal.first_match::<&str>(&[]), None);
+
+        let meal = MealInfo {
+            subtext: "| Currysoße".into(),
+            ..meal
+        };
+        assert!(meal.mentions("CURRY"));
+        assert!(meal.mentions("pommes"));
+        assert!(!meal.matches("curry"));
+        assert!(!meal.mentions(""));
+    }
+
+    #[test]
+    pub fn ids() {
+        let meal = MealInfo::new;
+        let schnitzel = meal(MealType::Klassiker, "Schnitzel mit Pommes", "3,50 €");
+        let id = schnitzel.id("vita", "2026-10-16");
+        assert_eq!(id.len(), 16);
+
+        // Survives re-scrapes with whitespace tweaks and price changes
+        let tweaked = meal(MealType::Klassiker, " Schnitzel  mit Pommes", "3,80 €");
+        assert_eq!(tweaked.id("vita", "2026-10-16"), id);
+
+        assert_ne!(schnitzel.id("vita", "2026-10-15"), id);
+        assert_ne!(schnitzel.id("academica", "2026-10-16"), id);
+        let wok = meal(MealType::Wok, "Schnitzel mit Pommes", "3,50 €");
+        assert_ne!(wok.id("vita", "2026-10-16"), id);
+
+        let json = serde_json::to_value(schnitzel.clone()).unwrap();
+        assert!(json.get("id").is_none());
+        let json = serde_json::to_value(schnitzel.with_id("vita", "2026-10-16")).unwrap();
+        assert_eq!(json["id"], id);
+    }
+
+    #[test]
+    pub fn allergen_sets() {
+        let list = |items: &[&str]| {
+            AllergenList(items.iter().map(|s| AllergenCode(s.to_string())).collect())
+        };
+        let (a, b, c) = (list(&["A", "C", "G"]), list(&["A", "G"]), list(&["G", "L"]));
+
+        assert_eq!(a.intersection(&b), list(&["A", "G"]));
+        assert_eq!(b.union(&c), list(&["A", "G", "L"]));
+        assert_eq!(AllergenList::common([&a, &b, &c]), list(&["G"]));
+        assert!(AllergenList::common([]).is_empty());
+    }
+
+    #[test]
+    pub fn additives() {
+        let list = |items: &[&str]| {
+            AllergenList(items.iter().map(|s| AllergenCode(s.to_string())).collect())
+        };
+        assert!(AllergenCode("9".into()).is_additive());
+        assert!(!AllergenCode("A1".into()).is_additive());
+        assert!(!AllergenCode(String::new()).is_additive());
+
+        let (allergens, additives) = list(&["2", "A", "A1", "10", "G"]).split_additives();
+        assert_eq!(allergens, list(&["A", "A1", "G"]));
+        assert_eq!(additives, list(&["2", "10"]));
+    }
+
+    #[test]
+    pub fn has_allergens() {
+        let meal = MealInfo {
+            allergens: AllergenList(
+                ["A", "A1", "g"]
+                    .iter()
+                    .map(|s| AllergenCode(s.to_string()))
+                    .collect(),
+            ),
+            diet: Diet::Vegetarian,
+            ..MealInfo::new(MealType::Vegetarisch, "Gemüsecurry", "")
+        };
+
+        assert!(meal.has_allergen("A1"));
+        assert!(meal.has_allergen(" a1 "));
+        assert!(meal.has_allergen("G"));
+        assert!(!meal.has_allergen("A10"));
+        assert!(!meal.has_allergen(""));
+        assert!(meal.has_any_allergen(&["L", "g"]));
+        assert!(!meal.has_any_allergen(&["L", "C"]));
+        assert!(!meal.has_any_allergen(&[]));
+    }
+
+    #[test]
+    pub fn diet_compat() {
+        let json = |diet: &str| {
+            format!(
+                r#"{{"type":"Wok","text":"Bami Goreng","subtext":"","price":"","allergens":[],{diet}}}"#
+            )
+        };
+        let diet = |s: &str| serde_json::from_str::<MealInfo>(&json(s)).unwrap().diet;
+
+        assert_eq!(diet(r#""diet":"Vegan""#), Diet::Vegan);
+        assert_eq!(diet(r#""vegan":true"#), Diet::Vegetarian);
+        assert_eq!(diet(r#""vegan":false"#), Diet::Unknown);
+        assert!(Diet::Vegan.is_vegetarian());
+        assert!(!Diet::Vegetarian.is_vegan());
+    }
+
+    #[test]
+    pub fn meat_flags() {
+        let flags = [Content::Alcohol, Content::Pork, Content::Pork]
+            .into_iter()
+            .collect::<MeatFlags>();
+        assert!(flags.contains(Content::Pork));
+        assert!(!flags.contains(Content::Fish));
+        assert!(flags.intersects(MeatFlags::ANIMAL));
+        assert_eq!(
+            flags.difference(MeatFlags::ANIMAL).describe(false),
+            "Alkohol"
+        );
+        assert_eq!(flags.to_string(), "Pork, Alcohol");
+
+        let json = serde_json::to_string(&flags).unwrap();
+        assert_eq!(json, r#"["Pork","Alcohol"]"#);
+        assert_eq!(serde_json::from_str::<MeatFlags>(&json).unwrap(), flags);
+        assert!(serde_json::from_str::<MeatFlags>(r#"["Tofu"]"#).is_err());
+    }
+
+    #[test]
+    pub fn allergen_order() {
+        let list = AllergenList(
+            ["A10", "A2", "10", "A1", "2", "A", "B", "A01"]
+                .iter()
+                .map(|s| AllergenCode(s.to_string()))
+                .collect(),
+        );
+
+        assert_eq!(list.to_string(), "2, 10, A, A1, A01, A2, A10, B");
+        assert_eq!(
+            serde_json::to_string(&list).unwrap(),
+            r#"["2","10","A","A1","A01","A2","A10","B"]"#
+        );
+    }
+
+    #[test]
+    pub fn describe_allergens() {
+        let code = |s: &str| AllergenCode(s.to_string());
+        assert_eq!(code("A1").describe(false), "Gluten (Weizen)");
+        assert_eq!(code("A1").describe(true), "Gluten (Wheat)");
+        assert_eq!(code("G").describe(false), "Soja");
+        assert_eq!(code("9").describe(true), "Sweetener");
+        assert_eq!(code("Z3").describe(false), "Z3");
+
+        let list = AllergenList(["A", "A1", "G", "Z"].iter().map(|s| code(s)).collect());
+        assert_eq!(list.describe(false), "Gluten (Weizen), Soja, Z");
+        assert_eq!(AllergenList::default().describe(true), "");
+    }
+
+    #[test]
+    pub fn parse_prices() {
+        let dec = |s: &str| Some(Decimal::from_str(s).unwrap());
+        assert_eq!(parse_price("2,50 €"), dec("2.50"));
+        assert_eq!(parse_price("4,10€"), dec("4.10"));
+        assert_eq!(parse_price(" 3.20 EUR "), dec("3.20"));
+        assert_eq!(parse_price("1.234,50 €"), dec("1234.50"));
+        assert_eq!(parse_price("5 €"), dec("5"));
+        assert_eq!(parse_price(""), None);
+        assert_eq!(parse_price("Tagespreis"), None);
+    }
+
+    #[test]
+    pub fn price_tiers() {
+        let single = Price::parse("2,50 €").unwrap();
+        assert_eq!(single.cents, 250);
+        assert_eq!(single.currency, "EUR");
+        assert_eq!(single.tiers, None);
+        assert_eq!(single.value(), Decimal::from_str("2.50").unwrap());
+
+        let tiers = PriceTiers {
+            student: 250,
+            employee: Some(380),
+            guest: Some(450),
+        };
+        let positional = Price::parse("2,50 € / 3,80 € / 4,50 €").unwrap();
+        assert_eq!(positional.cents, 250);
+        assert_eq!(positional.tiers, Some(tiers.clone()));
+
+        let labelled = Price::parse("Gäste 4,50 € | Studierende 2,50 € | Bedienstete 3,80 €");
+        assert_eq!(labelled.unwrap().tiers, Some(tiers));
+
+        let partial = Price::parse("2,50 € / 4,50 € (Gäste)").unwrap();
+        assert_eq!(partial.tiers.unwrap().employee, None);
+
+        assert_eq!(Price::parse(""), None);
+        assert_eq!(Price::parse("Tagespreis"), None);
+        assert_eq!(Price::parse("Gäste 4,50 €"), None);
+    }
+
+    #[test]
+    pub fn format_prices() {
+        let dec = |s: &str| Decimal::from_str(s).unwrap();
+        assert_eq!(format_price(dec("2.5"), false), "2,50 €");
+        assert_eq!(format_price(dec("2.5"), true), "€2.50");
+        assert_eq!(format_price(dec("5"), false), "5,00 €");
+        assert_eq!(format_price(dec("1.005"), true), "€1.00");
+        assert_eq!(
+            format_price(parse_price(" 4,10€ ").unwrap(), false),
+            "4,10 €"
+        );
+    }
+
+    #[test]
+    pub fn normalize() {
+        assert_eq!(normalize_text("Schnitzel"), "schnitzel");
+        assert_eq!(
+            normalize_text("  Schnitzel \n mit\tPommes "),
+            "schnitzel mit pommes"
+        );
+        assert_eq!(
+            normalize_text("SCHNITZEL mit Pommes."),
+            "schnitzel mit pommes"
+        );
+        assert_eq!(
+            normalize_text("Schnitzel mit Pommes !"),
+            "schnitzel mit pommes"
+        );
+        assert_eq!(normalize_text("Käsespätzle"), "käsespätzle");
+        assert_eq!(normalize_text("Chili (sin carne)"), "chili (sin carne)");
+        assert_eq!(normalize_text(""), "");
+    }
+
+    #[test]
+    pub fn side_names() {
+        assert_eq!(SideType::Main.name(true), "Main side dish");
+        assert_eq!(SideType::Secondary.name(true), "Secondary side dish");
+        assert_eq!(SideType::Secondary.name(false), "Gemüsebeilage");
+    }
 }
@@ -0,0 +1,125 @@
+use std::{fmt::Display, str::FromStr};
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "json-schema")]
+use schemars::JsonSchema;
+
+const MENSEN_TOML: &str = include_str!("../../res/mensen.toml");
+
+/// Metadata of a supported mensa
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct MensaInfo {
+    /// Name used in the menu endpoint (e.g. "eupener_strasse").
+    pub slug: String,
+
+    /// Display name (e.g. "Mensa Eupener Straße").
+    pub name: String,
+
+    /// Postal address.
+    pub address: String,
+
+    /// Latitude (WGS 84).
+    pub latitude: f64,
+
+    /// Longitude (WGS 84).
+    pub longitude: f64,
+}
+
+/// A supported mensa (one of `mensen` in `mensen.toml`). Displays as its slug.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Mensa(usize);
+
+#[derive(Deserialize)]
+struct MensenToml {
+    mensen: Vec<MensaInfo>,
+}
+
+lazy_static! {
+    static ref MENSEN: Vec<MensaInfo> = toml::from_str::<MensenToml>(MENSEN_TOML).unwrap().mensen;
+    static ref ALL: Vec<Mensa> = (0..MENSEN.len()).map(Mensa).collect();
+}
+
+/// Metadata of all supported mensen, in display order
+pub fn all() -> &'static [MensaInfo] {
+    &MENSEN
+}
+
+impl Mensa {
+    /// All supported mensen, in display order
+    pub fn all() -> &'static [Mensa] {
+        &ALL
+    }
+
+    /// Looks up a mensa by its slug (case-insensitive, "-" may be used instead of "_")
+    pub fn from_slug(slug: &str) -> Option<Mensa> {
+        let slug = slug.trim().to_lowercase().replace('-', "_");
+        MENSEN.iter().position(|m| m.slug == slug).map(Mensa)
+    }
+
+    /// Metadata of the mensa
+    pub fn info(&self) -> &'static MensaInfo {
+        &MENSEN[self.0]
+    }
+
+    /// Name used in the menu endpoint (e.g. "eupener_strasse")
+    pub fn slug(&self) -> &'static str {
+        &self.info().slug
+    }
+
+    /// Display name (e.g. "Mensa Eupener Straße")
+    pub fn name(&self) -> &'static str {
+        &self.info().name
+    }
+}
+
+impl Display for Mensa {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.slug())
+    }
+}
+
+impl FromStr for Mensa {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_slug(s).ok_or_else(|| {
+            anyhow::anyhow!(
+                "unknown mensa \"{s}\" (expected one of {})",
+                ALL.iter().map(Mensa::slug).collect::<Vec<_>>().join(", ")
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    pub fn mensen() {
+        assert_eq!(all().len(), 9);
+        assert_eq!(all()[0].slug, "academica");
+
+        let juelich = Mensa::from_slug("juelich").unwrap();
+        assert_eq!(juelich.name(), "Mensa Jülich");
+        assert!(juelich.info().address.ends_with("Jülich"));
+        assert!((50.0..51.0).contains(&juelich.info().latitude));
+        assert!(Mensa::from_slug("mensa_x").is_none());
+    }
+
+    #[test]
+    pub fn mensa_slugs() {
+        assert_eq!(Mensa::all().len(), all().len());
+        for mensa in Mensa::all() {
+            assert_eq!(Mensa::from_str(&mensa.to_string()).unwrap(), *mensa);
+        }
+
+        let eupener = Mensa::from_slug("Eupener-Strasse").unwrap();
+        assert_eq!(eupener.slug(), "eupener_strasse");
+        assert_eq!(eupener.name(), "Mensa Eupener Straße");
+        assert!(Mensa::from_str("mensa_x").is_err());
+    }
+}
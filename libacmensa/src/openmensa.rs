@@ -0,0 +1,228 @@
+use std::fmt::Write;
+
+use anyhow::Context;
+use chrono::NaiveDate;
+
+use crate::{
+    meal::{AllergenList, Diet, MealInfo, Price, SideInfo},
+    scrape::{DayView, WeekData},
+};
+
+/// Maximum length of a meal name allowed by the feed schema
+const MAX_NAME_LEN: usize = 250;
+
+const HEADER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<openmensa version="2.1" xmlns="http://openmensa.org/open-mensa-v2" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:schemaLocation="http://openmensa.org/open-mensa-v2 http://openmensa.org/open-mensa-v2.xsd">
+  <canteen>
+"#;
+
+const FOOTER: &str = "  </canteen>
+</openmensa>
+";
+
+/// Escapes the XML special characters in `s`
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Meal name as allowed by the feed (1..=250 characters)
+fn meal_name(text: &str) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() <= MAX_NAME_LEN {
+        text
+    } else {
+        let mut name = text.chars().take(MAX_NAME_LEN - 1).collect::<String>();
+        name.push('…');
+        name
+    }
+}
+
+/// Formats cents as the feed expects them ("2.50")
+fn feed_price(cents: i64) -> String {
+    format!("{}.{:02}", cents / 100, cents % 100)
+}
+
+/// `<price>` elements of a meal, by OpenMensa role
+fn prices(price: &Price) -> Vec<(&'static str, i64)> {
+    match &price.tiers {
+        Some(tiers) => [
+            ("student", Some(tiers.student)),
+            ("employee", tiers.employee),
+            ("other", tiers.guest),
+        ]
+        .into_iter()
+        .filter_map(|(role, cents)| cents.map(|cents| (role, cents)))
+        .collect(),
+        None => vec![("student", price.cents)],
+    }
+}
+
+fn write_meal(out: &mut String, name: &str, notes: &[String], prices: &[(&str, i64)]) {
+    out.push_str("        <meal>\n");
+    let _ = writeln!(out, "          <name>{}</name>", escape(&meal_name(name)));
+    for note in notes.iter().filter(|n| !n.is_empty()) {
+        let _ = writeln!(out, "          <note>{}</note>", escape(note));
+    }
+    for (role, cents) in prices {
+        let _ = writeln!(
+            out,
+            "          <price role=\"{role}\">{}</price>",
+            feed_price(*cents)
+        );
+    }
+    out.push_str("        </meal>\n");
+}
+
+/// "Allergene: ..." and "Zusatzstoffe: ..." notes, if there are any
+fn code_notes(allergens: &AllergenList, additives: &AllergenList, english: bool) -> Vec<String> {
+    [
+        (allergens, if english { "Allergens" } else { "Allergene" }),
+        (
+            additives,
+            if english { "Additives" } else { "Zusatzstoffe" },
+        ),
+    ]
+    .into_iter()
+    .filter(|(codes, _)| !codes.is_empty())
+    .map(|(codes, label)| format!("{label}: {codes}"))
+    .collect()
+}
+
+fn write_main(out: &mut String, meal: &MealInfo, english: bool) {
+    let mut notes = vec![meal.subtext.trim_start_matches('|').trim().to_string()];
+    let diet = match meal.diet {
+        Diet::Vegan => Some("vegan"),
+        Diet::Vegetarian if english => Some("vegetarian"),
+        Diet::Vegetarian => Some("vegetarisch"),
+        _ => None,
+    };
+    if let Some(diet) = diet.filter(|d| !notes[0].eq_ignore_ascii_case(d)) {
+        notes.push(diet.to_string());
+    }
+    notes.extend(code_notes(&meal.allergens, &meal.additives, english));
+
+    let prices = meal.price_parsed.as_ref().map(prices).unwrap_or_default();
+    write_meal(out, &meal.text, &notes, &prices);
+}
+
+fn write_side(out: &mut String, side: &SideInfo, english: bool) {
+    let _ = writeln!(
+        out,
+        "      <category name=\"{}\">",
+        escape(side.display_name(english))
+    );
+    for alt in side
+        .alternatives
+        .iter()
+        .filter(|a| !a.text.trim().is_empty())
+    {
+        let notes = code_notes(&alt.allergens, &alt.additives, english);
+        write_meal(out, &alt.text, &notes, &[]);
+    }
+    out.push_str("      </category>\n");
+}
+
+fn write_day(out: &mut String, date: NaiveDate, day: DayView, english: bool) {
+    let _ = writeln!(out, "    <day date=\"{}\">", date.format("%Y-%m-%d"));
+    if day.main_dishes.is_empty() {
+        out.push_str("      <closed/>\n");
+    } else {
+        // Categories must be unique per day, so meals of the same type are grouped
+        let mut categories: Vec<(&str, Vec<&MealInfo>)> = vec![];
+        for meal in day.main_dishes {
+            let name = meal.typ.name(english);
+            match categories.iter_mut().find(|(n, _)| *n == name) {
+                Some((_, meals)) => meals.push(meal),
+                None => categories.push((name, vec![meal])),
+            }
+        }
+
+        for (name, meals) in categories {
+            let _ = writeln!(out, "      <category name=\"{}\">", escape(name));
+            for meal in meals {
+                write_main(out, meal, english);
+            }
+            out.push_str("      </category>\n");
+        }
+
+        for side in day.side_dishes {
+            if side.alternatives.iter().any(|a| !a.text.trim().is_empty()) {
+                write_side(out, side, english);
+            }
+        }
+    }
+    out.push_str("    </day>\n");
+}
+
+/// Serializes weeks into an OpenMensa feed (v2.1). Every week needs its `week_start`
+/// (see `WeekData::with_week_start`); days without main dishes are marked as closed.
+pub fn to_feed(weeks: &[WeekData], english: bool) -> anyhow::Result<String> {
+    let mut days = vec![];
+    for week in weeks {
+        week.week_start
+            .context("week has no start date, cannot export it as an OpenMensa feed")?;
+        for (i, day) in week.days() {
+            let date = week.date_of(i).context("day out of range")?;
+            days.push((date, day));
+        }
+    }
+    days.sort_by_key(|(date, _)| *date);
+
+    let mut out = HEADER.to_string();
+    for (date, day) in days {
+        write_day(&mut out, date, day, english);
+    }
+    out.push_str(FOOTER);
+
+    Ok(out)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::meal::MealType;
+
+    #[test]
+    pub fn feed() {
+        let meal = MealInfo {
+            subtext: "| Zitrone".into(),
+            diet: Diet::Omnivore,
+            ..MealInfo::new(MealType::Klassiker, "Schnitzel & Pommes", "2,50 € / 3,80 €")
+        };
+        let mut week =
+            WeekData::empty().with_week_start(NaiveDate::from_ymd_opt(2026, 10, 12).unwrap());
+        week.main_dishes[0].push(meal);
+
+        let feed = to_feed(&[week], false).unwrap();
+        assert!(feed.starts_with("<?xml"));
+        assert!(feed.contains("<day date=\"2026-10-12\">\n      <category name=\"Klassiker\">"));
+        assert!(feed.contains("<name>Schnitzel &amp; Pommes</name>"));
+        assert!(feed.contains("<note>Zitrone</note>"));
+        assert!(feed.contains("<price role=\"student\">2.50</price>"));
+        assert!(feed.contains("<price role=\"employee\">3.80</price>"));
+        assert!(!feed.contains("role=\"other\""));
+        assert!(feed.contains("<day date=\"2026-10-16\">\n      <closed/>"));
+        assert!(feed.trim_end().ends_with("</openmensa>"));
+
+        assert!(to_feed(&[WeekData::empty()], false).is_err());
+    }
+
+    #[test]
+    pub fn names() {
+        assert_eq!(meal_name(" Curry \n mit Reis "), "Curry mit Reis");
+        assert_eq!(meal_name(&"a".repeat(300)).chars().count(), MAX_NAME_LEN);
+        assert_eq!(escape("<\"a\">"), "&lt;&quot;a&quot;&gt;");
+        assert_eq!(feed_price(405), "4.05");
+    }
+}
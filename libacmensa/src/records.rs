@@ -0,0 +1,181 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+/// How boolean fields (e.g. `vegan`) are written in flat/tabular output such as CSV
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BoolStyle {
+    /// "yes"/"no"
+    YesNo,
+    /// "true"/"false"
+    #[default]
+    TrueFalse,
+    /// "1"/"0"
+    #[serde(rename = "01")]
+    ZeroOne,
+}
+
+impl BoolStyle {
+    /// Renders a boolean in this style
+    pub fn render(&self, value: bool) -> &'static str {
+        match (self, value) {
+            (BoolStyle::YesNo, true) => "yes",
+            (BoolStyle::YesNo, false) => "no",
+            (BoolStyle::TrueFalse, true) => "true",
+            (BoolStyle::TrueFalse, false) => "false",
+            (BoolStyle::ZeroOne, true) => "1",
+            (BoolStyle::ZeroOne, false) => "0",
+        }
+    }
+}
+
+impl FromStr for BoolStyle {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "yesno" => Ok(BoolStyle::YesNo),
+            "truefalse" => Ok(BoolStyle::TrueFalse),
+            "01" => Ok(BoolStyle::ZeroOne),
+            _ => anyhow::bail!("unknown bool style \"{s}\" (expected yesno, truefalse or 01)"),
+        }
+    }
+}
+
+impl Display for BoolStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            BoolStyle::YesNo => "yesno",
+            BoolStyle::TrueFalse => "truefalse",
+            BoolStyle::ZeroOne => "01",
+        })
+    }
+}
+
+/// A single dish as a flat row (see `WeekData::to_records`)
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MealRecord {
+    /// Date the dish is served on (ISO, YYYY-MM-DD), empty if unknown
+    pub date: String,
+    /// Slug of the mensa, empty if unknown
+    pub mensa: String,
+    /// Name of the meal category or side type
+    pub category: String,
+    pub text: String,
+    pub subtext: String,
+    /// Price in EUR (e.g. "2.50"), empty for sides and unparseable prices
+    pub price: String,
+    pub vegan: bool,
+    /// Allergen codes (e.g. "A, C")
+    pub allergens: String,
+    /// Additive codes (e.g. "2, 9")
+    pub additives: String,
+}
+
+impl MealRecord {
+    /// Column names, in the order of `MealRecord::fields`
+    pub const HEADER: [&'static str; 9] = [
+        "date",
+        "mensa",
+        "category",
+        "text",
+        "subtext",
+        "price",
+        "vegan",
+        "allergens",
+        "additives",
+    ];
+
+    /// Values of the row, rendering booleans in `bools`
+    pub fn fields(&self, bools: BoolStyle) -> [&str; 9] {
+        [
+            &self.date,
+            &self.mensa,
+            &self.category,
+            &self.text,
+            &self.subtext,
+            &self.price,
+            bools.render(self.vegan),
+            &self.allergens,
+            &self.additives,
+        ]
+    }
+}
+
+/// Quotes a field if it contains the delimiter, quotes or line breaks
+fn quote(field: &str, delimiter: char) -> String {
+    if field.contains([delimiter, '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Writes records as delimiter-separated values with a header line (`,` for CSV, `\t` for
+/// TSV). Fields are quoted as in RFC 4180 where necessary.
+pub fn to_dsv(
+    records: impl IntoIterator<Item = MealRecord>,
+    delimiter: char,
+    bools: BoolStyle,
+) -> String {
+    let line = |fields: &[&str]| {
+        fields
+            .iter()
+            .map(|f| quote(f, delimiter))
+            .collect::<Vec<_>>()
+            .join(&delimiter.to_string())
+            + "\n"
+    };
+
+    let mut out = line(&MealRecord::HEADER);
+    for record in records {
+        out.push_str(&line(&record.fields(bools)));
+    }
+    out
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    pub fn bool_styles() {
+        for style in [BoolStyle::YesNo, BoolStyle::TrueFalse, BoolStyle::ZeroOne] {
+            assert_eq!(BoolStyle::from_str(&style.to_string()).unwrap(), style);
+        }
+
+        assert_eq!(BoolStyle::YesNo.render(false), "no");
+        assert_eq!(BoolStyle::ZeroOne.render(true), "1");
+        assert_eq!(BoolStyle::default().render(true), "true");
+        assert!(BoolStyle::from_str("ja/nein").is_err());
+    }
+
+    #[test]
+    pub fn dsv() {
+        let record = MealRecord {
+            date: "2026-10-16".into(),
+            mensa: "vita".into(),
+            category: "Tellergericht".into(),
+            text: "Chili \"sin\" Carne".into(),
+            subtext: "Reis, Salat".into(),
+            price: "2.20".into(),
+            vegan: true,
+            allergens: "A,C".into(),
+            additives: "2".into(),
+        };
+
+        let csv = to_dsv([record.clone()], ',', BoolStyle::YesNo);
+        assert_eq!(
+            csv,
+            "date,mensa,category,text,subtext,price,vegan,allergens,additives\n\
+             2026-10-16,vita,Tellergericht,\"Chili \"\"sin\"\" Carne\",\"Reis, Salat\",2.20,yes,\"A,C\",2\n"
+        );
+
+        let tsv = to_dsv([record], '\t', BoolStyle::ZeroOne);
+        assert_eq!(
+            tsv.lines().nth(1).unwrap(),
+            "2026-10-16\tvita\tTellergericht\t\"Chili \"\"sin\"\" Carne\"\tReis, Salat\t2.20\t1\tA,C\t2"
+        );
+    }
+}
@@ -1,69 +1,669 @@
-use std::str::FromStr;
+use std::{borrow::Cow, str::FromStr};
 
+pub use crate::day::{DayData, DayView};
 use crate::{
+    SchemaVersion, Week,
     config::{self, *},
-    meal::{self, MealInfo, MealType, SideAlternative, SideInfo, SideType},
+    meal::{
+        self, Content, Diet, MealInfo, MealType, MeatFlags, Price, SideAlternative, SideInfo,
+        SideType,
+    },
+    records::MealRecord,
+    times::{DayHours, OpeningHours, OpeningTimes, TimesException, Weekday},
 };
+use chrono::{Days, NaiveDate};
+use encoding_rs::{Encoding, UTF_8, WINDOWS_1252};
 use lazy_static::lazy_static;
 use regex::Regex;
 use scraper::*;
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "scrape")]
+use crate::{
+    cache::Cache,
+    client::{self, CacheStatus, ScrapeClient},
+};
+#[cfg(feature = "scrape")]
+use std::time::Duration;
+
 #[cfg(feature = "json-schema")]
 use schemars::{JsonSchema, schema_for};
 
 /// Encapsulates DayData for five days (Mon..Fri)
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
 pub struct WeekData {
+    /// Layout version of the document, bumped on breaking changes (see `SCHEMA_VERSION`)
+    #[serde(default)]
+    pub schema_version: SchemaVersion,
+    /// Date of the Monday of the week, if known (see `WeekData::with_week_start`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub week_start: Option<NaiveDate>,
+    /// Slug of the mensa serving the week, if known (see `WeekData::with_mensa`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mensa: Option<String>,
     pub main_dishes: [Vec<MealInfo>; config::OPEN_DAYS],
     pub side_dishes: [Vec<SideInfo>; config::OPEN_DAYS],
 }
 
-/// DayView is a view into a day of WeekData.
-#[derive(Clone, Debug, Serialize)]
-pub struct DayView<'a> {
-    pub main_dishes: &'a Vec<MealInfo>,
-    pub side_dishes: &'a Vec<SideInfo>,
+/// Both weeks the site offers (this and next week)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TwoWeeks {
+    pub this_week: WeekData,
+    pub next_week: WeekData,
+}
+
+/// A main dish found by `WeekData::search`
+#[derive(Clone, Debug)]
+pub struct SearchMatch<'a> {
+    /// Day of the week (Monday = 0)
+    pub day: usize,
+    /// Position among the main dishes of the day
+    pub index: usize,
+    pub meal: &'a MealInfo,
 }
 
-/// DayData is like DayView but owns its data. It holds
-/// data for dishes for a single day.
+/// DayData with the date it is served on (used for JSON exports)
 #[derive(Clone, Serialize, Deserialize)]
 #[cfg_attr(debug_assertions, derive(Debug))]
 #[cfg_attr(feature = "json-schema", derive(JsonSchema))]
-pub struct DayData {
-    /// Main dishes array (Klassiker, Vegetarisch, Wok, ...)
-    pub main_dishes: Vec<MealInfo>,
-    /// Side dishes array (Sättigungsbeilagen/Gemüsebeilagen)
-    pub side_dishes: Vec<SideInfo>,
+pub struct DatedDay {
+    /// Layout version of the document, bumped on breaking changes (see `SCHEMA_VERSION`)
+    #[serde(default)]
+    pub schema_version: SchemaVersion,
+    /// Slug of the mensa serving the day, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mensa: Option<String>,
+    /// Date of the day (ISO, YYYY-MM-DD)
+    pub date: NaiveDate,
+    #[serde(flatten)]
+    pub day: DayData,
 }
 
 lazy_static! {
     static ref ALLERGEN_REGEX: Regex = Regex::from_str(r"\(([A-Z0-9,]*)\)").unwrap();
     static ref SPACE_REGEX: Regex = Regex::from_str(r"\s\s+").unwrap();
-    static ref SEL_MAIN: Selector = Selector::parse("tr.main-dish").unwrap();
-    static ref SEL_SIDE: Selector = Selector::parse("tr.side-dish").unwrap();
-    static ref SEL_X_CELL: Selector = Selector::parse("td").unwrap();
-    static ref SEL_X_DTEXT: Selector = Selector::parse(".dish-text").unwrap();
+    static ref CHARSET_REGEX: Regex = Regex::from_str(r#"(?i)charset\s*=\s*"?([\w.:-]+)"#).unwrap();
+    static ref META_CHARSET_REGEX: Regex =
+        Regex::from_str(r#"(?i)<meta[^>]+charset\s*=\s*"?([\w.:-]+)"#).unwrap();
+    static ref TIME_RANGE_REGEX: Regex =
+        Regex::from_str(r"(\d{1,2})[:.](\d{2})\s*(?:-|–|bis|to)\s*(\d{1,2})[:.](\d{2})(?:\s*Uhr)?")
+            .unwrap();
+    static ref DAY_RANGE_REGEX: Regex =
+        Regex::from_str(r"^\s*(\p{L}+)\.?(?:\s*(?:-|–|bis|to)\s*(\p{L}+)\.?)?").unwrap();
+    static ref DATE_RANGE_REGEX: Regex = Regex::from_str(
+        r"(\d{1,2})\.(\d{1,2})\.(\d{4})(?:\s*(?:-|–|bis|to)\s*(\d{1,2})\.(\d{1,2})\.(\d{4}))?"
+    )
+    .unwrap();
+    static ref DEFAULT_SELECTORS: Selectors = CONFIG
+        .layout_for(DEFAULT_LAYOUT)
+        .and_then(Selectors::try_from)
+        .unwrap();
 }
 
-pub async fn get(mensa: &str, next_week: bool, english: bool) -> anyhow::Result<reqwest::Response> {
-    let url = CONFIG.endpoint.host.clone()
-        + "/"
-        + &CONFIG.endpoint.menu.build_path(mensa, next_week, english);
+/// Table of a menu page
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Table {
+    Main,
+    Side,
+}
 
-    Ok(reqwest::get(url).await?)
+impl std::fmt::Display for Table {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            Table::Main => "main dish",
+            Table::Side => "side dish",
+        })
+    }
 }
 
-/// Heuristic to catch as many veg* meals as possible.
-pub async fn vegan_detektiv(typ: &MealType, html: &str) -> bool {
-    if typ == &MealType::Vegetarisch {
-        return true;
+/// Errors from parsing a menu page or accessing its days
+#[derive(Debug, thiserror::Error)]
+pub enum ScrapeError {
+    /// A row of a menu table has fewer cells than expected
+    #[error("could not get (row, col) = ({row}, {col}) of the {table} table")]
+    MissingCell {
+        table: Table,
+        row: usize,
+        col: usize,
+    },
+
+    /// A dish cell has no text in its description
+    #[error("could not get the dish text at (row, col) = ({row}, {col})")]
+    MissingText { row: usize, col: usize },
+
+    /// A day outside of Mon..Fri was requested
+    #[error("requested day {0} > {max}", max = config::OPEN_DAYS - 1)]
+    DayOutOfRange(usize),
+
+    /// The week has no dates attached (see `WeekData::with_week_start`)
+    #[error("the dates of the week are unknown")]
+    NoDates,
+
+    /// A date outside of the week (or of a week without `week_start`) was requested
+    #[error("{0} is not part of the week")]
+    DateOutOfRange(NaiveDate),
+}
+
+/// What `scrape_page_lenient` skipped because of a malformed row or cell
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Skipped {
+    /// A whole row (its category cell is missing)
+    Row,
+    /// The remaining days of a row (their cells are missing)
+    RestOfRow,
+    /// A single dish
+    Cell,
+}
+
+impl std::fmt::Display for Skipped {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Skipped::Row => "row",
+            Skipped::RestOfRow => "rest of the row",
+            Skipped::Cell => "cell",
+        })
     }
+}
 
-    let t = html.to_lowercase();
+/// Part of a page skipped by `scrape_page_lenient`, and the error strict parsing fails with
+#[derive(Debug, thiserror::Error)]
+#[error("skipped {skipped}: {error}")]
+pub struct ParseWarning {
+    pub skipped: Skipped,
+    pub error: ScrapeError,
+}
 
-    t.contains("vegan") || t.contains("vegetarian") || t.contains("vegetarisch")
+/// Compiled selectors of a `config::Layout`
+#[derive(Clone, Debug)]
+pub struct Selectors {
+    pub main_row: Selector,
+    pub side_row: Selector,
+    pub cell: Selector,
+    pub dish_text: Selector,
+}
+
+impl TryFrom<&Layout> for Selectors {
+    type Error = anyhow::Error;
+
+    fn try_from(layout: &Layout) -> Result<Self, Self::Error> {
+        let parse = |s: &str| {
+            Selector::parse(s).map_err(|e| anyhow::anyhow!("invalid selector \"{s}\": {e}"))
+        };
+
+        Ok(Self {
+            main_row: parse(&layout.main_row)?,
+            side_row: parse(&layout.side_row)?,
+            cell: parse(&layout.cell)?,
+            dish_text: parse(&layout.dish_text)?,
+        })
+    }
+}
+
+impl Selectors {
+    /// Selectors for the page layout of a given mensa (see `mensa_layout` in `mensen.toml`)
+    pub fn for_mensa(mensa: &str) -> anyhow::Result<Self> {
+        CONFIG.layout_for(mensa).and_then(Self::try_from)
+    }
+}
+
+/// Full URL of the menu page of a mensa
+pub fn menu_url(mensa: &str, week: Week, english: bool) -> String {
+    CONFIG.endpoint.host.clone() + "/" + &CONFIG.endpoint.menu.build_path(mensa, week, english)
+}
+
+/// Fetch a menu page with a default `client::ScrapeClient` (timeout and retries, no cache).
+/// Use a `ScrapeClient` directly to reuse it for several requests.
+#[cfg(feature = "scrape")]
+pub async fn get(mensa: &str, week: Week, english: bool) -> anyhow::Result<reqwest::Response> {
+    ScrapeClient::builder()
+        .build()?
+        .send(&menu_url(mensa, week, english), None)
+        .await
+}
+
+/// Fetch and scrape this and next week of a mensa concurrently, with a default
+/// `client::ScrapeClient`. See `ScrapeClient::fetch_fortnight`.
+#[cfg(feature = "scrape")]
+pub async fn get_fortnight(
+    mensa: &str,
+    english: bool,
+) -> anyhow::Result<(anyhow::Result<WeekData>, anyhow::Result<WeekData>)> {
+    Ok(ScrapeClient::builder()
+        .build()?
+        .fetch_fortnight(mensa, english)
+        .await)
+}
+
+/// Monday of `week`, relative to `today`
+pub fn week_start(week: Week, today: NaiveDate) -> NaiveDate {
+    today.week(chrono::Weekday::Mon).first_day() + Days::new(7 * week.offset() as u64)
+}
+
+/// Fetch a menu page through `cache` (see `client::ScrapeClient`). Also returns whether
+/// the page was a cache hit.
+#[cfg(feature = "scrape")]
+pub async fn get_cached(
+    cache: &Cache,
+    mensa: &str,
+    week: Week,
+    english: bool,
+) -> anyhow::Result<(String, CacheStatus)> {
+    ScrapeClient::new(
+        Some(cache.clone()),
+        client::DEFAULT_TIMEOUT,
+        client::DEFAULT_RETRIES,
+    )?
+    .fetch_html_status(mensa, week, english, false)
+    .await
+}
+
+/// Sends a HEAD request to the menu page of a mensa with a default `client::ScrapeClient`
+/// and `timeout`. Returns the HTTP status code and the time until the response arrived.
+/// Does not download or parse the page.
+#[cfg(feature = "scrape")]
+pub async fn ping(
+    mensa: &str,
+    week: Week,
+    english: bool,
+    timeout: Duration,
+) -> anyhow::Result<(u16, Duration)> {
+    ScrapeClient::builder()
+        .timeout(timeout)
+        .build()?
+        .ping(mensa, week, english)
+        .await
+}
+
+/// Fetch a menu page and decode it to UTF-8 according to its charset.
+#[cfg(feature = "scrape")]
+pub async fn get_html(mensa: &str, week: Week, english: bool) -> anyhow::Result<String> {
+    let resp = get(mensa, week, english).await?;
+    let content_type = resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    let bytes = resp.bytes().await?;
+
+    Ok(decode_page(&bytes, content_type.as_deref()).into_owned())
+}
+
+/// Full URL of the opening times page
+pub fn times_url() -> String {
+    CONFIG.endpoint.host.clone() + "/" + &CONFIG.endpoint.timeplan
+}
+
+/// Fetch the opening times page and parse the opening times of a mensa, with a default
+/// `client::ScrapeClient`. See `ScrapeClient::fetch_times`.
+#[cfg(feature = "scrape")]
+pub async fn scrape_times(mensa: &str) -> anyhow::Result<OpeningTimes> {
+    ScrapeClient::builder().build()?.fetch_times(mensa).await
+}
+
+/// Parse the opening times of a mensa from the opening times page (see `[times]` in
+/// `mensen.toml`). Lines that are neither regular hours nor exceptions are skipped.
+pub fn parse_times(mensa: &str, html: &str) -> anyhow::Result<OpeningTimes> {
+    let layout = &CONFIG.times;
+    let parse =
+        |s: &str| Selector::parse(s).map_err(|e| anyhow::anyhow!("invalid selector \"{s}\": {e}"));
+    let (section_sel, title_sel, line_sel) = (
+        parse(&layout.section)?,
+        parse(&layout.title)?,
+        parse(&layout.line)?,
+    );
+    let name = layout
+        .names
+        .get(mensa)
+        .map(|name| meal::normalize_text(name))
+        .ok_or_else(|| anyhow::anyhow!("no name for {mensa} in [times.names]"))?;
+
+    let dom = Html::parse_document(html);
+    let section = dom
+        .select(&section_sel)
+        .find(|section| {
+            section.select(&title_sel).any(|title| {
+                meal::normalize_text(&title.text().collect::<String>()).contains(&name)
+            })
+        })
+        .ok_or_else(|| anyhow::anyhow!("no opening times for {mensa} found"))?;
+
+    let mut times = OpeningTimes {
+        schema_version: SchemaVersion::current(),
+        ..Default::default()
+    };
+    for line in section.select(&line_sel) {
+        let text = line.text().collect::<Vec<_>>().join(" ");
+        if let Some(exception) = parse_times_exception(&text) {
+            times.exceptions.push(exception);
+        } else if let Some((days, hours)) = parse_regular_hours(&text) {
+            for weekday in days {
+                times.regular.retain(|d| d.weekday != weekday);
+                times.regular.push(DayHours {
+                    weekday,
+                    hours: hours.clone(),
+                });
+            }
+        } else {
+            log::debug!("[times] skipping line \"{}\"", text.trim());
+        }
+    }
+
+    times.regular.sort();
+    Ok(times)
+}
+
+fn is_closed(text: &str) -> bool {
+    let text = text.to_lowercase();
+    text.contains("geschlossen") || text.contains("closed")
+}
+
+/// All time ranges ("11:30 - 14:30") in a line
+fn parse_hours(text: &str) -> Vec<OpeningHours> {
+    TIME_RANGE_REGEX
+        .captures_iter(text)
+        .map(|c| OpeningHours {
+            open: format!("{:0>2}:{}", &c[1], &c[2]),
+            close: format!("{:0>2}:{}", &c[3], &c[4]),
+        })
+        .collect()
+}
+
+/// Parses lines like "Mo - Fr: 11:30 - 14:30" or "Samstag geschlossen"
+fn parse_regular_hours(text: &str) -> Option<(Vec<Weekday>, Vec<OpeningHours>)> {
+    let caps = DAY_RANGE_REGEX.captures(text)?;
+    let from = Weekday::from_name(&caps[1])?;
+    let until = match caps.get(2) {
+        Some(until) => Weekday::from_name(until.as_str())?,
+        None => from,
+    };
+
+    let hours = parse_hours(&text[caps[0].len()..]);
+    if hours.is_empty() && !is_closed(text) {
+        return None;
+    }
+    Some((from.through(until), hours))
+}
+
+/// Parses lines like "24.12.2026 - 06.01.2027: geschlossen (Weihnachtsferien)"
+fn parse_times_exception(text: &str) -> Option<TimesException> {
+    let caps = DATE_RANGE_REGEX.captures(text)?;
+    let iso = |d: usize| format!("{}-{:0>2}-{:0>2}", &caps[d + 2], &caps[d + 1], &caps[d]);
+    let from = iso(1);
+    let until = caps.get(4).map(|_| iso(4)).unwrap_or_else(|| from.clone());
+
+    // Parse the hours without the dates, so that they are not mistaken for times
+    let rest = text.replace(&caps[0], " ");
+    let hours = parse_hours(&rest);
+    if hours.is_empty() && !is_closed(&rest) {
+        return None;
+    }
+
+    let note = TIME_RANGE_REGEX
+        .replace_all(&rest, " ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let note = note.trim_matches(|c: char| ":-–,;".contains(c) || c.is_whitespace());
+    let note = match note.strip_prefix('(').and_then(|n| n.strip_suffix(')')) {
+        Some(inner) if !inner.contains(['(', ')']) => inner,
+        _ => note,
+    };
+
+    Some(TimesException {
+        from,
+        until,
+        hours,
+        note: note.to_string(),
+    })
+}
+
+/// Words that negate the following word ("nicht vegan", "ohne Fleisch")
+const NEGATIONS: [&str; 8] = [
+    "nicht", "kein", "keine", "ohne", "not", "non", "no", "without",
+];
+
+/// Lowercase words of a text, each with whether it is negated by the word before it
+fn words(text: &str) -> Vec<(String, bool)> {
+    let words = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>();
+    words
+        .iter()
+        .enumerate()
+        .map(|(i, word)| {
+            let negated = i > 0 && NEGATIONS.contains(&words[i - 1].as_str());
+            (word.clone(), negated)
+        })
+        .collect()
+}
+
+/// Whether a (non-negated) word of `words` starts with one of `stems` (German compounds put
+/// the meat first, e.g. "Rindergulasch"), unless it is one of `exceptions`
+fn mentions(words: &[(String, bool)], stems: &[&str], exceptions: &[&str]) -> bool {
+    words.iter().any(|(word, negated)| {
+        !negated
+            && stems.iter().any(|stem| word.starts_with(stem))
+            && !exceptions.contains(&word.as_str())
+    })
+}
+
+/// Names of the icons and classes of a meal cell (image file names without extension, `alt`
+/// and `title` texts, class names), lowercase. These are the markers the page uses for
+/// diets and contents (e.g. `vegan.png`, `Schwein.png`).
+pub fn cell_markers(cell: &ElementRef) -> Vec<String> {
+    let mut markers = vec![];
+    for element in cell.descendent_elements() {
+        let value = element.value();
+        if let Some(src) = value.attr("src") {
+            let file = src.rsplit('/').next().unwrap_or(src);
+            let stem = file.split(['.', '?']).next().unwrap_or(file);
+            markers.push(stem.to_lowercase());
+        }
+        for attr in ["alt", "title"] {
+            if let Some(text) = value.attr(attr) {
+                markers.push(text.trim().to_lowercase());
+            }
+        }
+        markers.extend(value.classes().map(str::to_lowercase));
+    }
+    markers.retain(|marker| !marker.is_empty());
+    markers
+}
+
+/// Diet markers of the page (see `cell_markers`)
+const DIET_MARKERS: [(&str, Diet); 5] = [
+    ("vegan", Diet::Vegan),
+    ("olv", Diet::Vegetarian),
+    ("vegetarisch", Diet::Vegetarian),
+    ("vegetarian", Diet::Vegetarian),
+    ("ovo-lacto-vegetarisch", Diet::Vegetarian),
+];
+
+/// Content markers of the page (see `cell_markers`): the icons and the S/R/G/F letters
+const CONTENT_MARKERS: [(&str, Content); 13] = [
+    ("schwein", Content::Pork),
+    ("s", Content::Pork),
+    ("rind", Content::Beef),
+    ("r", Content::Beef),
+    ("geflügel", Content::Poultry),
+    ("gefluegel", Content::Poultry),
+    ("g", Content::Poultry),
+    ("fisch", Content::Fish),
+    ("f", Content::Fish),
+    ("alkohol", Content::Alcohol),
+    ("pork", Content::Pork),
+    ("beef", Content::Beef),
+    ("fish", Content::Fish),
+];
+
+/// Contents marked in the cell (see `cell_markers`)
+fn marked_contents(markers: &[String]) -> MeatFlags {
+    CONTENT_MARKERS
+        .iter()
+        .filter(|(name, _)| markers.iter().any(|marker| marker == name))
+        .map(|(_, content)| *content)
+        .collect()
+}
+
+/// Words that indicate meat or fish besides those of `CONTENT_WORDS`
+const OMNIVORE_WORDS: [&str; 4] = ["fleisch", "meat", "lamm", "lamb"];
+
+/// Words starting like meat words that are no meat
+const OMNIVORE_EXCEPTIONS: [&str; 7] = [
+    "fleischlos",
+    "fleischlose",
+    "fleischloser",
+    "fleischloses",
+    "meatless",
+    "rinde",
+    "rinden",
+];
+
+/// Classifies the diet of a meal by the markers of its cell (see `cell_markers`), its
+/// category and the words of its text. Negated words ("nicht vegan", "ohne Fleisch") do not
+/// count.
+pub fn vegan_detektiv(typ: &MealType, text: &str, markers: &[String]) -> Diet {
+    let marked = |name: &str| markers.iter().any(|marker| marker == name);
+    if let Some((_, diet)) = DIET_MARKERS.iter().find(|(name, _)| marked(name)) {
+        return *diet;
+    }
+
+    if marked_contents(markers).intersects(MeatFlags::ANIMAL) {
+        return Diet::Omnivore;
+    }
+
+    let words = words(text);
+    if mentions(&words, &["vegan"], &[]) {
+        Diet::Vegan
+    } else if typ == &MealType::Vegetarisch || mentions(&words, &["vegetari"], &[]) {
+        Diet::Vegetarian
+    } else if mentions(&words, &OMNIVORE_WORDS, &OMNIVORE_EXCEPTIONS)
+        || CONTENT_WORDS
+            .iter()
+            .filter(|(content, _)| *content != Content::Alcohol)
+            .any(|(_, stems)| mentions(&words, stems, &OMNIVORE_EXCEPTIONS))
+    {
+        Diet::Omnivore
+    } else {
+        Diet::Unknown
+    }
+}
+
+/// Word stems hinting at a `Content`, lowercase. They match at the start of a word (see
+/// `mentions`), so compounds need their own entry if the content is not their first part.
+const CONTENT_WORDS: [(Content, &[&str]); 5] = [
+    (
+        Content::Pork,
+        &[
+            "schwein",
+            "speck",
+            "schinken",
+            "salami",
+            "wurst",
+            "würstchen",
+            "wuerstchen",
+            "bratwurst",
+            "currywurst",
+            "bockwurst",
+            "mettwurst",
+            "weißwurst",
+            "weisswurst",
+            "leberkäse",
+            "leberkaese",
+            "pork",
+            "bacon",
+            "ham",
+            "sausage",
+        ],
+    ),
+    (Content::Beef, &["rind", "kalb", "beef", "veal"]),
+    (
+        Content::Poultry,
+        &[
+            "geflügel",
+            "gefluegel",
+            "hähnchen",
+            "haehnchen",
+            "hühner",
+            "huhn",
+            "pute",
+            "chicken",
+            "turkey",
+            "poultry",
+        ],
+    ),
+    (
+        Content::Fish,
+        &[
+            "fisch",
+            "backfisch",
+            "lachs",
+            "seelachs",
+            "thunfisch",
+            "garnele",
+            "fish",
+            "salmon",
+            "tuna",
+            "shrimp",
+        ],
+    ),
+    (
+        Content::Alcohol,
+        &[
+            "alkohol",
+            "wein",
+            "rotwein",
+            "weißwein",
+            "weisswein",
+            "bier",
+            "alcohol",
+            "wine",
+            "beer",
+        ],
+    ),
+];
+
+/// Words starting like a content word that do not contain it
+const CONTENT_EXCEPTIONS: [&str; 12] = [
+    "rinde",
+    "rinden",
+    "weintraube",
+    "weintrauben",
+    "weinblatt",
+    "weinblätter",
+    "weinblaetter",
+    "schweineohr",
+    "schweineohren",
+    "hamburger",
+    "hamburgers",
+    "fishless",
+];
+
+/// Finds the meat, fish and alcohol contents of a meal. The markers of its cell (see
+/// `cell_markers`, e.g. `Schwein.png`) are used if there are any, the words of its text
+/// otherwise. Vegetarian meals only keep the alcohol flag, since their text may well
+/// mention meat ("nach Hähnchenart").
+pub fn content_detektiv(typ: &MealType, text: &str, markers: &[String]) -> MeatFlags {
+    let marked = marked_contents(markers);
+    if !marked.is_empty() {
+        return marked;
+    }
+
+    let words = words(text);
+    let contents = CONTENT_WORDS
+        .iter()
+        .filter(|(_, stems)| mentions(&words, stems, &CONTENT_EXCEPTIONS))
+        .map(|(content, _)| *content)
+        .collect::<MeatFlags>();
+
+    if vegan_detektiv(typ, text, markers).is_vegetarian() {
+        contents.difference(MeatFlags::ANIMAL)
+    } else {
+        contents
+    }
 }
 
 /// Remove all allergen groups and trim extra spaces.
@@ -76,33 +676,151 @@ pub fn remove_allergens(text: &str) -> String {
 
 /// Collect all allergens and retun them in a sorted vector.
 /// This is NOT a HashSet since we want it to be sorted and `Ord`.
+/// Additives are left out, see `collect_additives`.
 pub fn collect_allergens(full_text: &str) -> meal::AllergenList {
-    (&*ALLERGEN_REGEX, full_text).into()
+    meal::AllergenList::from((&*ALLERGEN_REGEX, full_text))
+        .split_additives()
+        .0
+}
+
+/// Collect all additives (the number codes in the allergen groups), sorted
+pub fn collect_additives(full_text: &str) -> meal::AllergenList {
+    meal::AllergenList::from((&*ALLERGEN_REGEX, full_text))
+        .split_additives()
+        .1
+}
+
+/// Find the charset of a page: `Content-Type` charset first, then a `<meta>` declaration
+/// in the head of the document, then UTF-8 if the body is valid, and Windows-1252 (a
+/// superset of Latin-1) as a last resort.
+pub fn detect_encoding(bytes: &[u8], content_type: Option<&str>) -> &'static Encoding {
+    let from_label = |label: &str| Encoding::for_label(label.trim().trim_matches('"').as_bytes());
+
+    if let Some(enc) = content_type
+        .and_then(|ct| CHARSET_REGEX.captures(ct))
+        .and_then(|caps| from_label(&caps[1]))
+    {
+        return enc;
+    }
+
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(1024)]);
+    if let Some(enc) = META_CHARSET_REGEX
+        .captures(&head)
+        .and_then(|caps| from_label(&caps[1]))
+    {
+        return enc;
+    }
+
+    if std::str::from_utf8(bytes).is_ok() {
+        UTF_8
+    } else {
+        log::warn!("[decode] no charset given and page is not valid UTF-8, assuming Windows-1252");
+        WINDOWS_1252
+    }
 }
 
-/// Scrape a single page HTML for `WeekData`.
+/// Decode a raw page body to UTF-8. `content_type` is the value of the `Content-Type`
+/// header, if known. See `detect_encoding`.
+pub fn decode_page<'a>(bytes: &'a [u8], content_type: Option<&str>) -> Cow<'a, str> {
+    let encoding = detect_encoding(bytes, content_type);
+    let (text, _, malformed) = encoding.decode(bytes);
+    if malformed {
+        log::warn!(
+            "[decode] page contains malformed {} sequences",
+            encoding.name()
+        );
+    }
+
+    text
+}
+
+/// Scrape an already downloaded page body. See `decode_page` and `scrape_page`.
+pub async fn scrape_bytes(bytes: &[u8]) -> Result<WeekData, ScrapeError> {
+    scrape_page(&decode_page(bytes, None)).await
+}
+
+/// Scrape a single page HTML for `WeekData` using the default layout.
 /// This should be one of the menu endpoints defined in `mensen.toml`.
 /// It is language-agnostic, only relying on the structure of the meal table.
-pub async fn scrape_page(html: &str) -> anyhow::Result<WeekData> {
+pub async fn scrape_page(html: &str) -> Result<WeekData, ScrapeError> {
+    scrape_page_with(html, &DEFAULT_SELECTORS).await
+}
+
+/// Like `scrape_page`, but uses the layout configured for `mensa`.
+pub async fn scrape_mensa_page(mensa: &str, html: &str) -> anyhow::Result<WeekData> {
+    Ok(scrape_page_with(html, &Selectors::for_mensa(mensa)?).await?)
+}
+
+/// Scrape a single page HTML for `WeekData` with an explicit set of selectors.
+pub async fn scrape_page_with(html: &str, sel: &Selectors) -> Result<WeekData, ScrapeError> {
+    parse_week(html, sel, None)
+}
+
+/// Like `scrape_page`, but skips malformed rows and cells instead of failing. Returns what
+/// was skipped along with the week.
+pub async fn scrape_page_lenient(html: &str) -> (WeekData, Vec<ParseWarning>) {
+    parse_week_lenient(html, &DEFAULT_SELECTORS)
+}
+
+/// Like `scrape_page_lenient`, but uses the layout configured for `mensa`.
+pub async fn scrape_mensa_page_lenient(
+    mensa: &str,
+    html: &str,
+) -> anyhow::Result<(WeekData, Vec<ParseWarning>)> {
+    Ok(parse_week_lenient(html, &Selectors::for_mensa(mensa)?))
+}
+
+fn parse_week_lenient(html: &str, sel: &Selectors) -> (WeekData, Vec<ParseWarning>) {
+    let mut warnings = vec![];
+    let week = parse_week(html, sel, Some(&mut warnings))
+        .expect("lenient parsing records errors instead of failing");
+    (week, warnings)
+}
+
+/// Fails with `error` when parsing strictly (no `warnings`), records it otherwise
+fn skip(
+    warnings: &mut Option<&mut Vec<ParseWarning>>,
+    skipped: Skipped,
+    error: ScrapeError,
+) -> Result<(), ScrapeError> {
+    match warnings {
+        Some(warnings) => {
+            warnings.push(ParseWarning { skipped, error });
+            Ok(())
+        }
+        None => Err(error),
+    }
+}
+
+/// Shared by the async and the `blocking` scrapers, parsing does not need to await anything.
+/// With `warnings`, malformed rows and cells are skipped and recorded instead of failing.
+fn parse_week(
+    html: &str,
+    sel: &Selectors,
+    mut warnings: Option<&mut Vec<ParseWarning>>,
+) -> Result<WeekData, ScrapeError> {
     let dom = scraper::Html::parse_document(html);
 
-    let main_rows = dom.select(&SEL_MAIN);
-    let side_rows = dom.select(&SEL_SIDE);
+    let main_rows = dom.select(&sel.main_row);
+    let side_rows = dom.select(&sel.side_row);
 
     let mut main_dishes: [Vec<MealInfo>; config::OPEN_DAYS] = Default::default();
     let mut side_dishes: [Vec<SideInfo>; config::OPEN_DAYS] = Default::default();
 
     for (row_num, row) in main_rows.into_iter().enumerate() {
-        let mut cells = row.select(&SEL_X_CELL);
+        let mut cells = row.select(&sel.cell);
 
         // Get and parse MealType from first cell
-        let type_text = cells
-            .next()
-            .unwrap_or_else(|| {
-                log::error!("[parse/main] could not get (row,col)=({row_num}, 0)",);
-                panic!()
-            })
-            .inner_html();
+        let Some(type_cell) = cells.next() else {
+            let error = ScrapeError::MissingCell {
+                table: Table::Main,
+                row: row_num,
+                col: 0,
+            };
+            skip(&mut warnings, Skipped::Row, error)?;
+            continue;
+        };
+        let type_text = type_cell.inner_html();
         let typ = MealType::infer(&type_text);
 
         // Price should be in the first cell, after a line break
@@ -111,43 +829,42 @@ pub async fn scrape_page(html: &str) -> anyhow::Result<WeekData> {
             .unwrap_or(("", ""))
             .1
             .to_string();
+        let price_parsed = Price::parse(&price);
 
         // Handle rest cells
-        for col_num in 0..config::OPEN_DAYS {
-            let curr = cells.next().unwrap_or_else(|| {
-                log::error!(
-                    "[parse/main] could not get (row,col)=({row_num}, {})",
-                    col_num + 1 // +1 -> we have parsed the 0'th cell separately
-                );
-                panic!()
-            });
+        for (col_num, day) in main_dishes.iter_mut().enumerate() {
+            let Some(curr) = cells.next() else {
+                let error = ScrapeError::MissingCell {
+                    table: Table::Main,
+                    row: row_num,
+                    col: col_num + 1, // +1 -> we have parsed the 0'th cell separately
+                };
+                skip(&mut warnings, Skipped::RestOfRow, error)?;
+                break;
+            };
 
             // There should only be one of these
-            if let Some(dishtext) = curr.select(&SEL_X_DTEXT).next() {
+            if let Some(dishtext) = curr.select(&sel.dish_text).next() {
                 let mut text_iter = dishtext.text();
 
                 // First field should be meal name
-                let text_v = text_iter
-                    .next()
-                    .unwrap_or_else(|| {
-                        log::error!(
-                            "[parse/main] could not get main text field at \
-                                (row,col)=({row_num}, {})",
-                            col_num + 1
-                        );
-                        panic!()
-                    })
-                    .trim();
+                let Some(text_v) = text_iter.next() else {
+                    let error = ScrapeError::MissingText {
+                        row: row_num,
+                        col: col_num + 1,
+                    };
+                    skip(&mut warnings, Skipped::Cell, error)?;
+                    continue;
+                };
+                let text_v = text_v.trim();
                 // Rest of field should be secondary info about the mean (e.g. sauces)
                 let subtext_v = text_iter.collect::<String>();
+                // Diet and contents are read from the icons and the text of the whole cell
+                let cell_text = curr.text().collect::<Vec<_>>().join(" ");
+                let markers = cell_markers(&curr);
 
-                main_dishes
-                    .get_mut(col_num)
-                    .unwrap_or_else(|| {
-                        log::error!("[parse/main/internal] failed to get_mut({col_num}) on vector");
-                        panic!()
-                    })
-                    .push(MealInfo {
+                day.push(
+                    MealInfo {
                         // Type inferred above
                         typ: typ.clone(),
                         // Cleaned up meal description fields
@@ -155,90 +872,320 @@ pub async fn scrape_page(html: &str) -> anyhow::Result<WeekData> {
                         subtext: remove_allergens(&subtext_v),
                         // Price inferred above (first cell w/ `MealType`)
                         price: price.clone(),
+                        price_value: price_parsed.as_ref().map(Price::value),
+                        price_parsed: price_parsed.clone(),
                         // Allergens separately
                         allergens: collect_allergens(&(text_v.to_string() + &subtext_v)),
-                        vegan: vegan_detektiv(&typ, &curr.inner_html()).await,
-                    });
+                        additives: collect_additives(&(text_v.to_string() + &subtext_v)),
+                        diet: vegan_detektiv(&typ, &cell_text, &markers),
+                        contains: content_detektiv(&typ, &cell_text, &markers),
+                        id: None,
+                    }
+                    .clean(),
+                );
             }
         }
     }
 
     for (row_num, row) in side_rows.into_iter().enumerate() {
-        let mut cells = row.select(&SEL_X_CELL);
+        let mut cells = row.select(&sel.cell);
 
         // Get and parse `SideType` from first cell
-        let type_text = cells
-            .next()
-            .unwrap_or_else(|| {
-                log::error!(
-                    "[parse/secondary] could not get \
-                    (row,col)=({row_num}, 0)",
-                );
-                panic!()
-            })
-            .inner_html();
+        let Some(type_cell) = cells.next() else {
+            let error = ScrapeError::MissingCell {
+                table: Table::Side,
+                row: row_num,
+                col: 0,
+            };
+            skip(&mut warnings, Skipped::Row, error)?;
+            continue;
+        };
+        let type_text = type_cell.inner_html();
         let typ = SideType::infer(&type_text);
+        // Keep the raw category name around for types we could not infer
+        let label = remove_allergens(&type_cell.text().collect::<String>());
 
-        for col_num in 0..config::OPEN_DAYS {
-            let curr = cells.next().unwrap_or_else(|| {
-                log::error!(
-                    "[parse/secondary] could not get (row,col)=({row_num}, {})",
-                    col_num + 1 // +1 -> we have parsed the 0'th cell separately
-                );
-                panic!()
-            });
+        for (col_num, day) in side_dishes.iter_mut().enumerate() {
+            let Some(curr) = cells.next() else {
+                let error = ScrapeError::MissingCell {
+                    table: Table::Side,
+                    row: row_num,
+                    col: col_num + 1, // +1 -> we have parsed the 0'th cell separately
+                };
+                skip(&mut warnings, Skipped::RestOfRow, error)?;
+                break;
+            };
 
-            side_dishes
-                .get_mut(col_num)
-                .unwrap_or_else(|| {
-                    log::error!(
-                        "[parse/secondary/internal] get_mut({col_num}) failed \
-                        on vector"
-                    );
-                    panic!()
-                })
-                .push(SideInfo {
-                    typ: typ.clone(),
-                    alternatives: curr
-                        .text()
-                        .filter(|s| s.ne(&"oder") && s.ne(&"or"))
-                        .map(|s| s.to_string())
-                        .map(|s| SideAlternative {
-                            text: remove_allergens(&s),
-                            allergens: collect_allergens(&s),
-                        })
-                        .collect::<Vec<_>>(),
-                });
+            day.push(SideInfo {
+                typ: typ.clone(),
+                label: label.clone(),
+                alternatives: curr
+                    .text()
+                    .filter(|s| s.ne(&"oder") && s.ne(&"or"))
+                    .map(|s| s.to_string())
+                    .map(|s| SideAlternative {
+                        text: remove_allergens(&s),
+                        allergens: collect_allergens(&s),
+                        additives: collect_additives(&s),
+                    })
+                    .collect::<Vec<_>>(),
+            });
         }
     }
 
     Ok(WeekData {
+        schema_version: SchemaVersion::current(),
+        week_start: None,
+        mensa: None,
         main_dishes,
         side_dishes,
     })
 }
 
+/// One record per dish of a day (mains first, then side alternatives)
+fn records(date: &str, mensa: &str, day: DayView, english: bool) -> Vec<MealRecord> {
+    let mains = day.main_dishes.iter().map(|meal| MealRecord {
+        date: date.to_string(),
+        mensa: mensa.to_string(),
+        category: meal.typ.name(english).to_string(),
+        text: meal.text.clone(),
+        subtext: meal
+            .subtext
+            .trim_start_matches(|c: char| c == '|' || c.is_whitespace())
+            .to_string(),
+        price: meal.price_value.map(|v| v.to_string()).unwrap_or_default(),
+        vegan: meal.diet.is_vegan(),
+        allergens: meal.allergens.to_string(),
+        additives: meal.additives.to_string(),
+    });
+    let sides = day.side_dishes.iter().flat_map(|side| {
+        side.alternatives
+            .iter()
+            .filter(|alt| !alt.text.trim().is_empty())
+            .map(|alt| MealRecord {
+                date: date.to_string(),
+                mensa: mensa.to_string(),
+                category: side.display_name(english).to_string(),
+                text: alt.text.clone(),
+                allergens: alt.allergens.to_string(),
+                additives: alt.additives.to_string(),
+                ..Default::default()
+            })
+    });
+
+    mains.chain(sides).collect()
+}
+
+impl DatedDay {
+    /// Converts the day to the current layout (see `DayData::migrate`)
+    pub fn migrated(self) -> Self {
+        Self {
+            schema_version: SchemaVersion::current(),
+            day: self.day.migrate(self.schema_version),
+            ..self
+        }
+    }
+
+    /// Flattens the day into one record per dish. See `WeekData::to_records`.
+    pub fn to_records(&self, english: bool) -> Vec<MealRecord> {
+        records(
+            &self.date.format("%Y-%m-%d").to_string(),
+            self.mensa.as_deref().unwrap_or_default(),
+            self.day.view(),
+            english,
+        )
+    }
+}
+
 impl WeekData {
+    /// A week without any dishes
+    pub fn empty() -> Self {
+        Self {
+            schema_version: SchemaVersion::current(),
+            week_start: None,
+            mensa: None,
+            main_dishes: Default::default(),
+            side_dishes: Default::default(),
+        }
+    }
+
+    /// Reassembles a week from its days (Monday first), e.g. as stored by `into_days`.
+    /// `week_start` and `mensa` are unknown, see `with_week_start`/`with_mensa`.
+    pub fn from_days(days: [DayData; config::OPEN_DAYS]) -> Self {
+        let mut week = Self::empty();
+        for (day, data) in days.into_iter().enumerate() {
+            week.main_dishes[day] = data.main_dishes;
+            week.side_dishes[day] = data.side_dishes;
+        }
+        week
+    }
+
+    /// Splits the week into its days (Monday first), without copying the dishes
+    pub fn into_days(self) -> [DayData; config::OPEN_DAYS] {
+        let mut side_dishes = self.side_dishes.into_iter();
+        self.main_dishes.map(|main_dishes| DayData {
+            main_dishes,
+            side_dishes: side_dishes.next().unwrap_or_default(),
+        })
+    }
+
+    /// Replaces a single day of `WeekData`
+    pub fn set_day(&mut self, day: usize, data: DayData) -> Result<(), ScrapeError> {
+        if day >= config::OPEN_DAYS {
+            return Err(ScrapeError::DayOutOfRange(day));
+        }
+
+        self.main_dishes[day] = data.main_dishes;
+        self.side_dishes[day] = data.side_dishes;
+        Ok(())
+    }
+
+    /// Sets the date of the Monday of the week (and the ids of the meals, if the mensa is
+    /// known)
+    pub fn with_week_start(self, week_start: NaiveDate) -> Self {
+        Self {
+            week_start: Some(week_start),
+            ..self
+        }
+        .with_ids()
+    }
+
+    /// Sets the slug of the mensa serving the week (and the ids of the meals, if the dates
+    /// are known)
+    pub fn with_mensa(self, mensa: &str) -> Self {
+        Self {
+            mensa: Some(mensa.to_string()),
+            ..self
+        }
+        .with_ids()
+    }
+
+    /// Sets `MealInfo::id` of every main meal, if mensa and dates are known
+    fn with_ids(mut self) -> Self {
+        let Some(mensa) = self.mensa.clone() else {
+            return self;
+        };
+        for day in 0..config::OPEN_DAYS {
+            let Some(date) = self.date_of(day) else {
+                return self;
+            };
+            let date = date.format("%Y-%m-%d").to_string();
+            for meal in &mut self.main_dishes[day] {
+                meal.id = Some(meal.id(&mensa, &date));
+            }
+        }
+        self
+    }
+
+    /// Date of a single day of the week, if `week_start` is known
+    pub fn date_of(&self, day: usize) -> Option<NaiveDate> {
+        self.week_start
+            .filter(|_| day < config::OPEN_DAYS)
+            .map(|start| start + Days::new(day as u64))
+    }
+
+    /// Returns a `DayView` into the day served on `date`
+    pub fn get_day_by_date(&self, date: NaiveDate) -> Result<DayView<'_>, ScrapeError> {
+        let day = (0..config::OPEN_DAYS)
+            .find(|day| self.date_of(*day) == Some(date))
+            .ok_or(ScrapeError::DateOutOfRange(date))?;
+        self.get_day(day)
+    }
+
+    /// A single day with its date. Fails if `week_start` is unknown.
+    pub fn get_dated_day(&self, day: usize) -> Result<DatedDay, ScrapeError> {
+        let view = self.get_day(day)?;
+        Ok(DatedDay {
+            schema_version: SchemaVersion::current(),
+            mensa: self.mensa.clone(),
+            date: self.date_of(day).ok_or(ScrapeError::NoDates)?,
+            day: view.into(),
+        })
+    }
+
+    /// Every day of the week (Monday = 0) with a `DayView` into it
+    pub fn days(&self) -> impl Iterator<Item = (usize, DayView<'_>)> {
+        self.main_dishes
+            .iter()
+            .zip(&self.side_dishes)
+            .map(|(main_dishes, side_dishes)| DayView {
+                main_dishes,
+                side_dishes,
+            })
+            .enumerate()
+    }
+
+    /// All days with their dates (empty if `week_start` is unknown)
+    pub fn dated_days(&self) -> Vec<DatedDay> {
+        self.days()
+            .filter_map(|(day, _)| self.get_dated_day(day).ok())
+            .collect()
+    }
+
+    /// Flattens the week into one record per dish (mains first, then side alternatives),
+    /// day by day. See `records::to_dsv`.
+    pub fn to_records(&self, english: bool) -> impl Iterator<Item = MealRecord> + '_ {
+        let mensa = self.mensa.clone().unwrap_or_default();
+        self.days().flat_map(move |(day, view)| {
+            let date = self
+                .date_of(day)
+                .map(|d| d.format("%Y-%m-%d").to_string())
+                .unwrap_or_default();
+            records(&date, &mensa, view, english)
+        })
+    }
+
+    /// Main dishes whose description or subtext contain `query` (see `MealInfo::mentions`),
+    /// day by day
+    pub fn search(&self, query: &str) -> Vec<SearchMatch<'_>> {
+        self.main_dishes
+            .iter()
+            .enumerate()
+            .flat_map(|(day, mains)| {
+                mains
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, meal)| meal.mentions(query))
+                    .map(move |(index, meal)| SearchMatch { day, index, meal })
+            })
+            .collect()
+    }
+
     /// Returns a `DayView` into a single day of `WeekData`
-    pub fn get_day<'a>(&'a self, day: usize) -> DayView<'a> {
+    pub fn get_day<'a>(&'a self, day: usize) -> Result<DayView<'a>, ScrapeError> {
         if day >= config::OPEN_DAYS {
-            log::error!("Requested {day} > {}", config::OPEN_DAYS - 1);
-            panic!()
+            return Err(ScrapeError::DayOutOfRange(day));
         }
 
-        DayView {
+        Ok(DayView {
             main_dishes: &self.main_dishes[day],
             side_dishes: &self.side_dishes[day],
-        }
+        })
     }
 
     /// Print json schema for `DayData`
     #[cfg(feature = "json-schema")]
     pub fn day_schema() -> anyhow::Result<String> {
-        let schema = schema_for!(DayData);
+        let schema = schema_for!(DatedDay);
         Ok(serde_json::to_string_pretty(&schema)?)
     }
 
+    /// Print json schema for `WeekData`
+    #[cfg(feature = "json-schema")]
+    pub fn week_schema() -> anyhow::Result<String> {
+        let schema = schema_for!(WeekData);
+        Ok(serde_json::to_string_pretty(&schema)?)
+    }
+
+    /// Converts the week to the current layout (see `DayData::migrate`)
+    pub fn migrated(mut self) -> Self {
+        for (mains, sides) in self.main_dishes.iter_mut().zip(&mut self.side_dishes) {
+            crate::day::migrate_dishes(self.schema_version, mains, sides);
+        }
+        self.schema_version = SchemaVersion::current();
+        self
+    }
+
     /// Sorts the dishes inside each day (order inherited from `MainInfo`, `SideInfo`)
     pub fn sorted(&self) -> Self {
         let mut main_dishes = self.main_dishes.clone();
@@ -248,8 +1195,804 @@ impl WeekData {
         side_dishes.iter_mut().for_each(|m| m.sort());
 
         Self {
+            schema_version: self.schema_version,
+            week_start: self.week_start,
+            mensa: self.mensa.clone(),
             main_dishes,
             side_dishes,
         }
     }
+
+    /// Drops all main dishes of the given categories
+    pub fn without_types(&self, hidden: &[MealType]) -> Self {
+        let mut main_dishes = self.main_dishes.clone();
+        main_dishes
+            .iter_mut()
+            .for_each(|m| m.retain(|main| !hidden.contains(&main.typ)));
+
+        Self {
+            schema_version: self.schema_version,
+            week_start: self.week_start,
+            mensa: self.mensa.clone(),
+            main_dishes,
+            side_dishes: self.side_dishes.clone(),
+        }
+    }
+}
+
+impl TwoWeeks {
+    /// Iterates over all main dishes of both weeks, day by day
+    pub fn main_dishes(&self) -> impl Iterator<Item = &MealInfo> {
+        self.this_week
+            .main_dishes
+            .iter()
+            .chain(self.next_week.main_dishes.iter())
+            .flatten()
+    }
+
+    /// All distinct dishes (see `MealInfo::same_dish`) of both weeks in category order.
+    /// For dishes served multiple times, the first occurrence is kept.
+    pub fn unique_meals(&self) -> Vec<MealInfo> {
+        let mut unique: Vec<MealInfo> = vec![];
+        for meal in self.main_dishes() {
+            if !unique.iter().any(|m| m.same_dish(meal)) {
+                unique.push(meal.clone());
+            }
+        }
+
+        unique.sort_by(|a, b| a.typ.cmp(&b.typ));
+        unique
+    }
+
+    /// First dish matching `term` (see `MealInfo::matches`) served on or after `from`,
+    /// with its date. `monday` is the Monday of `this_week`.
+    pub fn next_occurrence(
+        &self,
+        term: &str,
+        monday: NaiveDate,
+        from: NaiveDate,
+    ) -> Option<(NaiveDate, &MealInfo)> {
+        [&self.this_week, &self.next_week]
+            .into_iter()
+            .enumerate()
+            .flat_map(|(w, week)| {
+                week.main_dishes
+                    .iter()
+                    .enumerate()
+                    .map(move |(d, meals)| (monday + Days::new((7 * w + d) as u64), meals))
+            })
+            .filter(|(date, _)| *date >= from)
+            .find_map(|(date, meals)| {
+                meals
+                    .iter()
+                    .find(|meal| meal.matches(term))
+                    .map(|meal| (date, meal))
+            })
+    }
+}
+
+/// Sync versions of the fetching and scraping functions, for programs without an async
+/// runtime. Requires the `blocking` feature, which does not need the async client (feature
+/// `scrape`).
+#[cfg(feature = "blocking")]
+pub mod blocking {
+    use super::*;
+
+    /// See `scrape::get`. Uses the same timeout, retries and user agent as the default
+    /// `client::ScrapeClient`.
+    pub fn get(
+        mensa: &str,
+        week: Week,
+        english: bool,
+    ) -> anyhow::Result<reqwest::blocking::Response> {
+        let http = reqwest::blocking::Client::builder()
+            .timeout(DEFAULT_TIMEOUT)
+            .user_agent(DEFAULT_USER_AGENT)
+            .build()?;
+        let url = menu_url(mensa, week, english);
+
+        let mut attempt = 0;
+        loop {
+            let err = match http.get(&url).send() {
+                Ok(resp) if !resp.status().is_server_error() => return Ok(resp),
+                Ok(resp) => anyhow::anyhow!("server error {}", resp.status()),
+                Err(e) => e.into(),
+            };
+
+            if attempt >= DEFAULT_RETRIES {
+                return Err(err);
+            }
+            log::warn!("[blocking] {url}: {err}, retrying");
+            std::thread::sleep(DEFAULT_RETRY_DELAY * 2u32.pow(attempt));
+            attempt += 1;
+        }
+    }
+
+    /// See `scrape::get_html`
+    pub fn get_html(mensa: &str, week: Week, english: bool) -> anyhow::Result<String> {
+        let resp = get(mensa, week, english)?;
+        let content_type = resp
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let bytes = resp.bytes()?;
+
+        Ok(decode_page(&bytes, content_type.as_deref()).into_owned())
+    }
+
+    /// See `scrape::scrape_page`
+    pub fn scrape_page(html: &str) -> Result<WeekData, ScrapeError> {
+        parse_week(html, &DEFAULT_SELECTORS, None)
+    }
+
+    /// See `scrape::scrape_page_with`
+    pub fn scrape_page_with(html: &str, sel: &Selectors) -> Result<WeekData, ScrapeError> {
+        parse_week(html, sel, None)
+    }
+
+    /// See `scrape::scrape_mensa_page`
+    pub fn scrape_mensa_page(mensa: &str, html: &str) -> anyhow::Result<WeekData> {
+        Ok(parse_week(html, &Selectors::for_mensa(mensa)?, None)?)
+    }
+
+    /// See `scrape::scrape_page_lenient`
+    pub fn scrape_page_lenient(html: &str) -> (WeekData, Vec<ParseWarning>) {
+        parse_week_lenient(html, &DEFAULT_SELECTORS)
+    }
+
+    /// See `scrape::scrape_mensa_page_lenient`
+    pub fn scrape_mensa_page_lenient(
+        mensa: &str,
+        html: &str,
+    ) -> anyhow::Result<(WeekData, Vec<ParseWarning>)> {
+        Ok(parse_week_lenient(html, &Selectors::for_mensa(mensa)?))
+    }
+
+    /// Fetch and scrape the menu of a mensa
+    pub fn scrape_mensa(mensa: &str, week: Week, english: bool) -> anyhow::Result<WeekData> {
+        scrape_mensa_page(mensa, &get_html(mensa, week, english)?)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    const LATIN1_PAGE: &[u8] = include_bytes!("../fixtures/latin1.html");
+    const OVERLAP_PAGE: &str = include_str!("../fixtures/overlap.html");
+
+    #[test]
+    pub fn decode_latin1() {
+        let by_header = decode_page(LATIN1_PAGE, Some("text/html; charset=ISO-8859-1"));
+        assert!(by_header.contains("Sättigungsbeilage"));
+
+        // The fixture declares its charset in a <meta> tag
+        let by_meta = decode_page(LATIN1_PAGE, None);
+        assert!(by_meta.contains("Sättigungsbeilage"));
+
+        // Neither header nor <meta>
+        let no_meta = LATIN1_PAGE.splitn(2, |&b| b == b'\n').nth(1).unwrap();
+        assert!(decode_page(no_meta, None).contains("Sättigungsbeilage"));
+
+        assert_eq!(decode_page("Gemüse".as_bytes(), None), "Gemüse");
+    }
+
+    fn meal(typ: MealType, text: &str) -> MealInfo {
+        MealInfo::new(typ, text, "")
+    }
+
+    #[test]
+    pub fn unique_meals() {
+        let day = |main_dishes| DayData {
+            main_dishes,
+            side_dishes: vec![],
+        };
+        let mut this_week = WeekData::empty();
+        let mut next_week = WeekData::empty();
+
+        this_week
+            .set_day(
+                0,
+                day(vec![
+                    meal(MealType::Wok, "Bami Goreng"),
+                    meal(MealType::Klassiker, "Schnitzel"),
+                ]),
+            )
+            .unwrap();
+        this_week
+            .set_day(3, day(vec![meal(MealType::Klassiker, "schnitzel ")]))
+            .unwrap();
+        next_week
+            .set_day(
+                1,
+                day(vec![
+                    meal(MealType::Klassiker, "Schnitzel"),
+                    meal(MealType::Wok, "Schnitzel"),
+                ]),
+            )
+            .unwrap();
+
+        let unique = TwoWeeks {
+            this_week,
+            next_week,
+        }
+        .unique_meals();
+
+        assert_eq!(
+            unique
+                .iter()
+                .map(|m| (m.typ.clone(), m.text.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (MealType::Klassiker, "Schnitzel"),
+                (MealType::Wok, "Bami Goreng"),
+                (MealType::Wok, "Schnitzel"),
+            ]
+        );
+    }
+
+    #[test]
+    pub fn next_occurrence() {
+        let day = |main_dishes| DayData {
+            main_dishes,
+            side_dishes: vec![],
+        };
+        let mut this_week = WeekData::empty();
+        let mut next_week = WeekData::empty();
+        this_week
+            .set_day(1, day(vec![meal(MealType::Klassiker, "Currywurst")]))
+            .unwrap();
+        next_week
+            .set_day(
+                2,
+                day(vec![
+                    meal(MealType::Wok, "Bami Goreng"),
+                    meal(MealType::Klassiker, "Currywurst mit Pommes"),
+                ]),
+            )
+            .unwrap();
+        let weeks = TwoWeeks {
+            this_week,
+            next_week,
+        };
+
+        let monday = NaiveDate::from_ymd_opt(2026, 10, 12).unwrap();
+        let date = |d| NaiveDate::from_ymd_opt(2026, 10, d).unwrap();
+
+        let (found, meal) = weeks.next_occurrence("currywurst", monday, monday).unwrap();
+        assert_eq!((found, meal.text.as_str()), (date(13), "Currywurst"));
+
+        let (found, meal) = weeks
+            .next_occurrence("Currywurst", monday, date(14))
+            .unwrap();
+        assert_eq!(
+            (found, meal.text.as_str()),
+            (date(21), "Currywurst mit Pommes")
+        );
+
+        assert!(
+            weeks
+                .next_occurrence("currywurst", monday, date(22))
+                .is_none()
+        );
+        assert!(weeks.next_occurrence("Schnitzel", monday, monday).is_none());
+    }
+
+    #[test]
+    pub fn diets() {
+        let diet = |typ: MealType, text: &str| vegan_detektiv(&typ, text, &[]);
+        assert_eq!(diet(MealType::Wok, "Tofu (vegan)"), Diet::Vegan);
+        assert_eq!(diet(MealType::Vegetarisch, "Käsespätzle"), Diet::Vegetarian);
+        assert_eq!(
+            diet(MealType::Klassiker, "Schweineschnitzel"),
+            Diet::Omnivore
+        );
+        assert_eq!(diet(MealType::Klassiker, "Vegane Currywurst"), Diet::Vegan);
+        assert_eq!(diet(MealType::Wok, "Bami Goreng"), Diet::Unknown);
+
+        // Whole words only
+        assert_eq!(diet(MealType::Empfehlung, "Flammkuchen"), Diet::Unknown);
+        assert_eq!(diet(MealType::Empfehlung, "Meatless burger"), Diet::Unknown);
+        assert_eq!(diet(MealType::Klassiker, "Lammkeule"), Diet::Omnivore);
+
+        // Compounds with the meat not in front
+        for text in [
+            "Currywurst mit Pommes",
+            "Bratwurst mit Sauerkraut",
+            "Leberkäse mit Ei",
+            "Sausage with fries",
+        ] {
+            assert_eq!(diet(MealType::Klassiker, text), Diet::Omnivore, "{text}");
+        }
+
+        // Negations
+        assert_eq!(diet(MealType::Wok, "Curry (nicht vegan)"), Diet::Unknown);
+        assert_eq!(
+            diet(MealType::Wok, "Curry, not vegan, mit Hähnchen"),
+            Diet::Omnivore
+        );
+        assert_eq!(
+            diet(MealType::Wok, "Gemüsecurry ohne Fleisch"),
+            Diet::Unknown
+        );
+    }
+
+    #[test]
+    pub fn diet_markers() {
+        let html = Html::parse_fragment(
+            r#"<td><span class="dish-text">Schnitzel</span>
+            <img src="resources/images/inhalt/Schwein.png" alt=""></td>"#,
+        );
+        let markers = cell_markers(&html.root_element());
+        assert!(markers.contains(&"schwein".to_string()));
+        assert_eq!(
+            vegan_detektiv(&MealType::Klassiker, "Schnitzel", &markers),
+            Diet::Omnivore
+        );
+
+        // Icons win over the text, markup is not looked at as text
+        let html = Html::parse_fragment(
+            r#"<td><span class="dish-text">Burger nach Rindfleischart</span>
+            <img src="vegan.png" class="content-icon"></td>"#,
+        );
+        let cell = html.root_element();
+        let text = cell.text().collect::<Vec<_>>().join(" ");
+        assert_eq!(
+            vegan_detektiv(&MealType::Klassiker, &text, &cell_markers(&cell)),
+            Diet::Vegan
+        );
+    }
+
+    #[test]
+    pub fn contents() {
+        let contents = |typ: MealType, text: &str| content_detektiv(&typ, text, &[]).to_string();
+        assert_eq!(contents(MealType::Klassiker, "Schweineschnitzel"), "Pork");
+        assert_eq!(
+            contents(MealType::Klassiker, "Rindergulasch in Rotweinsoße"),
+            "Beef, Alcohol"
+        );
+        assert_eq!(contents(MealType::Wok, "Bami Goreng mit Garnelen"), "Fish");
+        assert_eq!(contents(MealType::Vegetarisch, "Tofu nach Hähnchenart"), "");
+        assert_eq!(contents(MealType::Wok, "Gemüse"), "");
+        assert_eq!(
+            contents(MealType::Klassiker, "Currywurst mit Pommes"),
+            "Pork"
+        );
+        assert_eq!(
+            contents(MealType::Klassiker, "Bratwurst mit Sauerkraut"),
+            "Pork"
+        );
+        assert_eq!(contents(MealType::Klassiker, "Leberkäse mit Ei"), "Pork");
+        assert_eq!(contents(MealType::Klassiker, "Sausage with fries"), "Pork");
+        assert_eq!(contents(MealType::Klassiker, "Vegane Currywurst"), "");
+
+        // Words that only start or end like a content
+        assert_eq!(contents(MealType::Empfehlung, "Käse mit Weintrauben"), "");
+        assert_eq!(contents(MealType::Empfehlung, "Gefüllte Weinblätter"), "");
+        assert_eq!(contents(MealType::Empfehlung, "Brot ohne Rinde"), "");
+        assert_eq!(contents(MealType::Klassiker, "Schweinebraten"), "Pork");
+        assert_eq!(contents(MealType::Empfehlung, "Salat ohne Speck"), "");
+    }
+
+    #[test]
+    pub fn content_markers() {
+        let html = Html::parse_fragment(
+            r#"<td><span class="dish-text">Burger mit Weinbergpfirsich</span>
+            <img src="img/Rind.png" title="Rind"><img src="img/Fisch.png" alt="F"></td>"#,
+        );
+        let cell = html.root_element();
+        let text = cell.text().collect::<Vec<_>>().join(" ");
+        assert_eq!(
+            content_detektiv(&MealType::Klassiker, &text, &cell_markers(&cell)).to_string(),
+            "Beef, Fish"
+        );
+
+        // Attribute values are no text
+        let html = Html::parse_fragment(
+            r#"<td><span class="dish-text" data-note="wein">Nudeln</span></td>"#,
+        );
+        let cell = html.root_element();
+        let text = cell.text().collect::<Vec<_>>().join(" ");
+        assert_eq!(
+            content_detektiv(&MealType::Wok, &text, &cell_markers(&cell)).to_string(),
+            ""
+        );
+    }
+
+    #[tokio::test]
+    pub async fn filter_allergens() {
+        let week = scrape_page(OVERLAP_PAGE).await.unwrap();
+        let day = week.get_day(0).unwrap();
+        let gluten = day
+            .main_dishes
+            .iter()
+            .filter(|m| m.has_allergen("A"))
+            .count();
+        assert!(gluten > 0);
+
+        let safe = day.filter_allergens(&["a"]);
+        assert_eq!(safe.main_dishes.len(), day.main_dishes.len() - gluten);
+        assert!(safe.main_dishes.iter().all(|m| !m.has_allergen("A")));
+        assert!(
+            safe.side_dishes
+                .iter()
+                .flat_map(|s| &s.alternatives)
+                .all(|alt| !alt.allergens.contains("A"))
+        );
+
+        let unfiltered = day.filter_allergens(&[]);
+        assert_eq!(unfiltered.main_dishes, *day.main_dishes);
+        assert_eq!(unfiltered.side_dishes.len(), day.side_dishes.len());
+    }
+
+    #[tokio::test]
+    pub async fn search() {
+        let week = scrape_page(OVERLAP_PAGE).await.unwrap();
+        let first = &week.main_dishes[0][0];
+        let query = first.text.to_uppercase();
+
+        let hits = week.search(&query);
+        assert!(!hits.is_empty());
+        for hit in &hits {
+            assert!(std::ptr::eq(
+                hit.meal,
+                &week.main_dishes[hit.day][hit.index]
+            ));
+            assert!(hit.meal.mentions(&query));
+        }
+        assert_eq!((hits[0].day, hits[0].index), (0, 0));
+        assert!(week.search("Gänsestopfleber").is_empty());
+    }
+
+    #[test]
+    pub fn added_since() {
+        let previous = DayData {
+            main_dishes: vec![meal(MealType::Klassiker, "Schnitzel")],
+            side_dishes: vec![SideInfo {
+                typ: SideType::Main,
+                label: String::new(),
+                alternatives: vec![SideAlternative {
+                    text: "Reis".into(),
+                    allergens: Default::default(),
+                    additives: Default::default(),
+                }],
+            }],
+        };
+
+        let mut current = previous.clone();
+        current.main_dishes.push(meal(MealType::Wok, "Bami Goreng"));
+        current.side_dishes[0].alternatives.push(SideAlternative {
+            text: "Pommes".into(),
+            allergens: Default::default(),
+            additives: Default::default(),
+        });
+
+        let added = current.added_since(&previous);
+        assert_eq!(added.main_dishes, vec![meal(MealType::Wok, "Bami Goreng")]);
+        assert_eq!(added.side_dishes[0].alternatives.len(), 1);
+        assert_eq!(added.side_dishes[0].alternatives[0].text, "Pommes");
+
+        let nothing = previous.added_since(&previous);
+        assert!(nothing.main_dishes.is_empty() && nothing.side_dishes.is_empty());
+    }
+
+    #[test]
+    pub fn set_day() {
+        let mut week = WeekData::empty();
+        assert!(week.main_dishes.iter().all(Vec::is_empty));
+
+        week.set_day(
+            4,
+            DayData {
+                main_dishes: vec![meal(MealType::Wok, "Bami Goreng")],
+                side_dishes: vec![],
+            },
+        )
+        .unwrap();
+        assert_eq!(week.get_day(4).unwrap().main_dishes.len(), 1);
+        assert!(week.get_day(3).unwrap().main_dishes.is_empty());
+
+        assert!(matches!(
+            week.get_day(5),
+            Err(ScrapeError::DayOutOfRange(5))
+        ));
+        assert!(
+            week.set_day(7, DayData::from(week.get_day(0).unwrap()))
+                .is_err()
+        );
+    }
+
+    #[test]
+    pub fn iterate_week() {
+        let mut week = WeekData::empty();
+        week.set_day(
+            2,
+            DayData {
+                main_dishes: vec![
+                    meal(MealType::Wok, "Bami Goreng"),
+                    meal(MealType::Vegetarisch, "Pizza Funghi"),
+                ],
+                side_dishes: vec![SideInfo {
+                    typ: SideType::Main,
+                    label: String::new(),
+                    alternatives: vec![],
+                }],
+            },
+        )
+        .unwrap();
+
+        let days = week.days().collect::<Vec<_>>();
+        assert_eq!(days.len(), 5);
+        assert_eq!(days.iter().map(|(day, _)| *day).sum::<usize>(), 10);
+
+        let (day, view) = week
+            .days()
+            .find(|(_, view)| view.iter_main().next().is_some())
+            .unwrap();
+        assert_eq!(day, 2);
+        assert_eq!(
+            view.iter_main()
+                .map(|m| m.text.as_str())
+                .collect::<Vec<_>>(),
+            ["Bami Goreng", "Pizza Funghi"]
+        );
+        assert_eq!(view.iter_sides().count(), 1);
+    }
+
+    #[test]
+    pub fn split_and_reassemble() {
+        let monday = NaiveDate::from_ymd_opt(2026, 10, 12).unwrap();
+        let mut week = WeekData::empty();
+        for (day, text) in [(0, "Schnitzel"), (4, "Bami Goreng")] {
+            week.set_day(
+                day,
+                DayData {
+                    main_dishes: vec![meal(MealType::Klassiker, text)],
+                    side_dishes: vec![],
+                },
+            )
+            .unwrap();
+        }
+        let week = week.with_mensa("vita").with_week_start(monday);
+
+        let friday = week.get_day(4).unwrap().to_owned();
+        assert_eq!(friday.main_dishes[0].text, "Bami Goreng");
+        assert!(friday.main_dishes[0].id.is_some());
+
+        let days = week.clone().into_days();
+        assert_eq!(days[0].main_dishes[0].text, "Schnitzel");
+        assert!(days[1..4].iter().all(|d| d.main_dishes.is_empty()));
+        assert_eq!(days[4].main_dishes, friday.main_dishes);
+
+        let reassembled = WeekData::from_days(days);
+        assert!(reassembled.week_start.is_none() && reassembled.mensa.is_none());
+        assert_eq!(reassembled.main_dishes, week.main_dishes);
+        assert_eq!(reassembled.side_dishes, week.side_dishes);
+    }
+
+    #[tokio::test]
+    pub async fn scrape_missing_cell() {
+        let page = r#"<table>
+            <tr class="main-dish"><td>Klassiker<br>3,50 €</td><td><span class="dish-text">Schnitzel</span></td></tr>
+        </table>"#;
+
+        assert!(matches!(
+            scrape_page(page).await,
+            Err(ScrapeError::MissingCell {
+                table: Table::Main,
+                row: 0,
+                col: 2
+            })
+        ));
+    }
+
+    #[tokio::test]
+    pub async fn scrape_lenient() {
+        let page = r#"<table>
+            <tr class="main-dish"><td>Klassiker<br>3,50 €</td><td><span class="dish-text">Schnitzel</span></td></tr>
+            <tr class="main-dish"><td>Tellergericht<br>2,90 €</td><td><span class="dish-text"></span></td>
+                <td><span class="dish-text">Eintopf</span></td><td></td><td></td><td></td></tr>
+        </table>"#;
+
+        let (week, warnings) = scrape_page_lenient(page).await;
+        assert_eq!(week.main_dishes[0][0].text, "Schnitzel");
+        assert_eq!(week.main_dishes[0].len(), 1);
+        assert_eq!(week.main_dishes[1][0].text, "Eintopf");
+
+        let skipped = warnings.iter().map(|w| w.skipped).collect::<Vec<_>>();
+        assert_eq!(skipped, vec![Skipped::RestOfRow, Skipped::Cell]);
+        assert!(matches!(
+            warnings[1].error,
+            ScrapeError::MissingText { row: 1, col: 1 }
+        ));
+
+        // Well-formed pages have no warnings
+        let (strict, warnings) = scrape_page_lenient(OVERLAP_PAGE).await;
+        assert!(warnings.is_empty());
+        assert_eq!(
+            strict.main_dishes,
+            scrape_page(OVERLAP_PAGE).await.unwrap().main_dishes
+        );
+    }
+
+    #[test]
+    pub fn dated_week() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let monday = NaiveDate::from_ymd_opt(2026, 10, 12).unwrap();
+        assert_eq!(week_start(Week::This, today), monday);
+        assert_eq!(
+            week_start(Week::Next, monday),
+            NaiveDate::from_ymd_opt(2026, 10, 19).unwrap()
+        );
+
+        let mut week = WeekData::empty();
+        week.set_day(
+            4,
+            DayData {
+                main_dishes: vec![meal(MealType::Wok, "Bami Goreng")],
+                side_dishes: vec![],
+            },
+        )
+        .unwrap();
+        assert!(week.get_day_by_date(today).is_err());
+        assert!(week.dated_days().is_empty());
+        assert!(matches!(week.get_dated_day(4), Err(ScrapeError::NoDates)));
+
+        let week = week.with_week_start(monday);
+        assert_eq!(week.get_day_by_date(today).unwrap().main_dishes.len(), 1);
+        assert!(matches!(
+            week.get_day_by_date(today + Days::new(1)),
+            Err(ScrapeError::DateOutOfRange(_))
+        ));
+
+        let days = week.dated_days();
+        assert_eq!(days.len(), 5);
+        assert_eq!(days[4].date, today);
+        let json = serde_json::to_value(&days[4]).unwrap();
+        assert_eq!(json["schema_version"], crate::SCHEMA_VERSION);
+        assert_eq!(json["date"], "2026-10-16");
+        assert_eq!(json["main_dishes"][0]["text"], "Bami Goreng");
+        assert!(json.get("mensa").is_none());
+        let unversioned = serde_json::from_str::<DatedDay>(
+            r#"{"date": "2026-10-16", "main_dishes": [], "side_dishes": []}"#,
+        )
+        .unwrap();
+        assert_eq!(unversioned.schema_version, SchemaVersion(0));
+        assert!(!unversioned.schema_version.is_current());
+
+        assert!(days[4].day.main_dishes[0].id.is_none());
+
+        let day = week.with_mensa("vita").get_dated_day(4).unwrap();
+        assert_eq!(day.mensa.as_deref(), Some("vita"));
+        assert_eq!(serde_json::to_value(&day).unwrap()["mensa"], "vita");
+        let id = meal(MealType::Wok, "Bami Goreng").id("vita", "2026-10-16");
+        assert_eq!(day.day.main_dishes[0].id.as_deref(), Some(id.as_str()));
+    }
+
+    #[test]
+    pub fn without_types() {
+        let mut week = WeekData::empty();
+        week.set_day(
+            0,
+            DayData {
+                main_dishes: vec![
+                    meal(MealType::BurgerClassics, "Cheeseburger"),
+                    meal(MealType::Wok, "Bami Goreng"),
+                ],
+                side_dishes: vec![],
+            },
+        )
+        .unwrap();
+
+        let week = week.without_types(&[MealType::BurgerClassics, MealType::PizzaTag]);
+        assert_eq!(
+            week.get_day(0).unwrap().main_dishes,
+            &vec![meal(MealType::Wok, "Bami Goreng")]
+        );
+    }
+
+    #[tokio::test]
+    pub async fn scrape_latin1() {
+        let week = scrape_bytes(LATIN1_PAGE).await.unwrap();
+        assert_eq!(week.side_dishes[0][0].typ, SideType::Main);
+        assert_eq!(week.side_dishes[0][0].label, "Sättigungsbeilage");
+        assert_eq!(week.side_dishes[0][0].alternatives[0].text, "Spätzle");
+    }
+
+    #[test]
+    pub fn parse_times_page() {
+        let page = include_str!("../fixtures/times.html");
+        let times = parse_times("ahornstrasse", page).unwrap();
+
+        let lunch = OpeningHours {
+            open: "11:30".into(),
+            close: "14:30".into(),
+        };
+        assert_eq!(times.regular.len(), 7);
+        assert_eq!(times.on(Weekday::Monday), std::slice::from_ref(&lunch));
+        assert_eq!(times.on(Weekday::Friday), [lunch]);
+        assert_eq!(times.on(Weekday::Saturday), []);
+        assert_eq!(times.on(Weekday::Sunday), []);
+
+        assert_eq!(
+            times.exceptions,
+            [
+                TimesException {
+                    from: "2026-12-24".into(),
+                    until: "2027-01-06".into(),
+                    hours: vec![],
+                    note: "geschlossen (Weihnachtsferien)".into(),
+                },
+                TimesException {
+                    from: "2026-10-30".into(),
+                    until: "2026-10-30".into(),
+                    hours: vec![OpeningHours {
+                        open: "11:30".into(),
+                        close: "13:00".into(),
+                    }],
+                    note: "Brückentag".into(),
+                },
+            ]
+        );
+
+        // Second section of the page
+        let academica = parse_times("academica", page).unwrap();
+        assert_eq!(academica.on(Weekday::Thursday)[1].open, "17:00");
+        assert!(parse_times("vita", page).is_err());
+    }
+
+    #[tokio::test]
+    pub async fn scrape_overlap() {
+        let week = scrape_page(OVERLAP_PAGE).await.unwrap();
+        let subtexts = week.main_dishes[0]
+            .iter()
+            .map(|m| m.subtext.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(subtexts, vec!["| Salat", "", "| mit Brötchen"]);
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    pub fn scrape_blocking() {
+        let week = blocking::scrape_page(OVERLAP_PAGE).unwrap();
+        assert_eq!(week.main_dishes[0].len(), 3);
+        assert!(blocking::scrape_mensa_page("academica", OVERLAP_PAGE).is_ok());
+    }
+
+    #[tokio::test]
+    pub async fn to_records() {
+        let week = scrape_page(OVERLAP_PAGE)
+            .await
+            .unwrap()
+            .with_week_start(NaiveDate::from_ymd_opt(2026, 10, 12).unwrap())
+            .with_mensa("vita");
+
+        let records = week.to_records(false).collect::<Vec<_>>();
+        let monday = records
+            .iter()
+            .filter(|r| r.date == "2026-10-12")
+            .collect::<Vec<_>>();
+        assert_eq!(monday[0].mensa, "vita");
+        assert_eq!(monday[0].subtext, "Salat");
+        assert!(monday.iter().take(3).all(|r| !r.category.is_empty()));
+        assert!(monday.iter().skip(3).all(|r| r.price.is_empty()));
+        assert_eq!(
+            week.get_dated_day(0).unwrap().to_records(false),
+            monday.into_iter().cloned().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            records.len(),
+            (0..5)
+                .map(|day| {
+                    week.main_dishes[day].len()
+                        + week.side_dishes[day]
+                            .iter()
+                            .flat_map(|s| &s.alternatives)
+                            .filter(|a| !a.text.trim().is_empty())
+                            .count()
+                })
+                .sum::<usize>()
+        );
+    }
 }
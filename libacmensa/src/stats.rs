@@ -0,0 +1,266 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{NaiveDate, Weekday};
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::{
+    meal::{Diet, MealType, normalize_text},
+    scrape::DatedDay,
+};
+
+/// Statistics over a range of days (e.g. from the archive, see `archive::Archive::days`)
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Stats {
+    /// Number of days with any main meal
+    pub days: usize,
+    /// Number of main meals served
+    pub meals: usize,
+    /// Most frequent main meals, most frequent first
+    pub top_meals: Vec<MealCount>,
+    /// Main meals per category, most frequent first
+    pub categories: Vec<CategoryStats>,
+    /// Share of vegan main meals per week, oldest first
+    pub weeks: Vec<WeekStats>,
+}
+
+/// How often a meal was served
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct MealCount {
+    /// Description as first seen
+    pub text: String,
+    pub category: MealType,
+    pub count: usize,
+    /// Last day it was served
+    pub last_served: NaiveDate,
+}
+
+/// How often a category appears and what it costs
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CategoryStats {
+    pub category: MealType,
+    pub count: usize,
+    /// Average price in EUR over all days (`None` if no price could be parsed)
+    pub average_price: Option<Decimal>,
+    /// Average price in EUR per month (YYYY-MM), oldest first
+    pub monthly_prices: Vec<MonthlyPrice>,
+}
+
+/// Average price of a category within a month
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct MonthlyPrice {
+    /// Month (YYYY-MM)
+    pub month: String,
+    pub average_price: Decimal,
+}
+
+/// Vegan share of a week
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct WeekStats {
+    /// Monday of the week
+    pub week_start: NaiveDate,
+    pub meals: usize,
+    pub vegan: usize,
+    /// `vegan / meals` (0 to 1)
+    pub vegan_share: f64,
+}
+
+/// Sum and count of prices, averaged in the end
+#[derive(Default)]
+struct PriceSum(Decimal, u32);
+
+impl PriceSum {
+    fn add(&mut self, price: Option<Decimal>) {
+        if let Some(price) = price {
+            self.0 += price;
+            self.1 += 1;
+        }
+    }
+
+    fn average(&self) -> Option<Decimal> {
+        (self.1 > 0).then(|| (self.0 / Decimal::from(self.1)).round_dp(2))
+    }
+}
+
+impl Stats {
+    /// Computes the statistics over the main meals of `days`, keeping the `top` most
+    /// frequent meals. Meals are counted as the same if category and description match
+    /// (see `MealInfo::same_dish`).
+    pub fn from_days(days: &[DatedDay], top: usize) -> Self {
+        let mut stats = Stats::default();
+        let mut meals = HashMap::<(MealType, String), MealCount>::new();
+        let mut categories =
+            BTreeMap::<MealType, (usize, PriceSum, BTreeMap<String, PriceSum>)>::new();
+        let mut weeks = BTreeMap::<NaiveDate, (usize, usize)>::new();
+
+        for day in days {
+            if day.day.main_dishes.is_empty() {
+                continue;
+            }
+            stats.days += 1;
+
+            let month = day.date.format("%Y-%m").to_string();
+            let week = weeks
+                .entry(day.date.week(Weekday::Mon).first_day())
+                .or_default();
+
+            for meal in &day.day.main_dishes {
+                stats.meals += 1;
+
+                let count = meals
+                    .entry((meal.typ.clone(), normalize_text(&meal.text)))
+                    .or_insert_with(|| MealCount {
+                        text: meal.text.trim().to_string(),
+                        category: meal.typ.clone(),
+                        count: 0,
+                        last_served: day.date,
+                    });
+                count.count += 1;
+                count.last_served = count.last_served.max(day.date);
+
+                let (count, total, monthly) = categories.entry(meal.typ.clone()).or_default();
+                *count += 1;
+                total.add(meal.price_value);
+                monthly
+                    .entry(month.clone())
+                    .or_default()
+                    .add(meal.price_value);
+
+                week.0 += 1;
+                if meal.diet == Diet::Vegan {
+                    week.1 += 1;
+                }
+            }
+        }
+
+        stats.top_meals = meals.into_values().collect();
+        stats.top_meals.sort_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then(b.last_served.cmp(&a.last_served))
+                .then(a.text.cmp(&b.text))
+        });
+        stats.top_meals.truncate(top);
+
+        stats.categories = categories
+            .into_iter()
+            .map(|(category, (count, total, monthly))| CategoryStats {
+                category,
+                count,
+                average_price: total.average(),
+                monthly_prices: monthly
+                    .into_iter()
+                    .filter_map(|(month, sum)| {
+                        sum.average().map(|average_price| MonthlyPrice {
+                            month,
+                            average_price,
+                        })
+                    })
+                    .collect(),
+            })
+            .collect();
+        // Stable, so equally frequent categories stay in their natural order
+        stats.categories.sort_by_key(|c| std::cmp::Reverse(c.count));
+
+        stats.weeks = weeks
+            .into_iter()
+            .map(|(week_start, (meals, vegan))| WeekStats {
+                week_start,
+                meals,
+                vegan,
+                vegan_share: vegan as f64 / meals as f64,
+            })
+            .collect();
+
+        stats
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::{SchemaVersion, meal::MealInfo, scrape::DayData};
+
+    fn meal(typ: MealType, text: &str, price: &str, diet: Diet) -> MealInfo {
+        MealInfo {
+            diet,
+            ..MealInfo::new(typ, text, price)
+        }
+    }
+
+    fn day(date: &str, main_dishes: Vec<MealInfo>) -> DatedDay {
+        DatedDay {
+            schema_version: SchemaVersion::current(),
+            mensa: Some("academica".into()),
+            date: NaiveDate::from_str(date).unwrap(),
+            day: DayData {
+                main_dishes,
+                side_dishes: vec![],
+            },
+        }
+    }
+
+    #[test]
+    pub fn from_days() {
+        let days = vec![
+            day(
+                "2026-09-30",
+                vec![
+                    meal(MealType::Klassiker, "Schnitzel", "3.00", Diet::Omnivore),
+                    meal(MealType::Wok, "Curry", "4.00", Diet::Vegan),
+                ],
+            ),
+            day(
+                "2026-10-01",
+                vec![
+                    meal(MealType::Klassiker, "schnitzel ", "3.50", Diet::Omnivore),
+                    meal(MealType::Wok, "Tofu", "", Diet::Vegan),
+                ],
+            ),
+            day("2026-10-02", vec![]),
+            day(
+                "2026-10-05",
+                vec![meal(MealType::Klassiker, "Gulasch", "4.00", Diet::Omnivore)],
+            ),
+        ];
+
+        let stats = Stats::from_days(&days, 2);
+        assert_eq!(stats.days, 3);
+        assert_eq!(stats.meals, 5);
+
+        assert_eq!(stats.top_meals.len(), 2);
+        assert_eq!(stats.top_meals[0].text, "Schnitzel");
+        assert_eq!(stats.top_meals[0].count, 2);
+        assert_eq!(stats.top_meals[0].last_served, days[1].date);
+        // Equally frequent: the most recent first
+        assert_eq!(stats.top_meals[1].text, "Gulasch");
+
+        let klassiker = &stats.categories[0];
+        assert_eq!(klassiker.category, MealType::Klassiker);
+        assert_eq!(klassiker.count, 3);
+        assert_eq!(klassiker.average_price, Decimal::from_str("3.50").ok());
+        let months = klassiker
+            .monthly_prices
+            .iter()
+            .map(|p| (p.month.as_str(), p.average_price.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            months,
+            vec![("2026-09", "3.00".into()), ("2026-10", "3.75".into())]
+        );
+
+        // Prices that could not be parsed are left out of the average
+        assert_eq!(
+            stats.categories[1].average_price,
+            Decimal::from_str("4.00").ok()
+        );
+
+        assert_eq!(stats.weeks.len(), 2);
+        assert_eq!(stats.weeks[0].meals, 4);
+        assert_eq!(stats.weeks[0].vegan, 2);
+        assert_eq!(stats.weeks[0].vegan_share, 0.5);
+        assert_eq!(stats.weeks[1].vegan_share, 0.0);
+    }
+}
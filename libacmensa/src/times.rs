@@ -0,0 +1,234 @@
+use serde::{Deserialize, Serialize};
+
+use crate::SchemaVersion;
+
+#[cfg(feature = "json-schema")]
+use schemars::{JsonSchema, schema_for};
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl Weekday {
+    pub const ALL: [Weekday; 7] = [
+        Weekday::Monday,
+        Weekday::Tuesday,
+        Weekday::Wednesday,
+        Weekday::Thursday,
+        Weekday::Friday,
+        Weekday::Saturday,
+        Weekday::Sunday,
+    ];
+
+    const NAMES_DE: [&str; 7] = [
+        "montag",
+        "dienstag",
+        "mittwoch",
+        "donnerstag",
+        "freitag",
+        "samstag",
+        "sonntag",
+    ];
+
+    /// Parses a German or English weekday name or abbreviation (at least two letters,
+    /// case-insensitive, e.g. "Mo", "Di.", "thu", "Freitag")
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().trim_end_matches('.').to_lowercase();
+        if name.chars().count() < 2 {
+            return None;
+        }
+
+        Self::ALL
+            .into_iter()
+            .zip(Self::NAMES_DE)
+            .find_map(|(day, de)| {
+                let en = format!("{day:?}").to_lowercase();
+                (de.starts_with(&name) || en.starts_with(&name)).then_some(day)
+            })
+    }
+
+    /// Weekdays from `self` to `until` (inclusive, wrapping around Sunday)
+    pub fn through(self, until: Weekday) -> Vec<Weekday> {
+        let (from, until) = (self as usize, until as usize);
+        let len = (until + 7 - from) % 7 + 1;
+        (0..len).map(|i| Self::ALL[(from + i) % 7]).collect()
+    }
+}
+
+/// A single opening interval.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct OpeningHours {
+    /// Opening time (local, "HH:MM").
+    pub open: String,
+
+    /// Closing time (local, "HH:MM").
+    pub close: String,
+}
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct DayHours {
+    /// Day of the week.
+    pub weekday: Weekday,
+
+    /// Opening intervals. Empty if closed on that day.
+    pub hours: Vec<OpeningHours>,
+}
+
+/// Deviation from the regular opening times (holidays, semester breaks etc.).
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct TimesException {
+    /// First day the exception applies to (ISO date, YYYY-MM-DD).
+    pub from: String,
+
+    /// Last day the exception applies to (ISO date, inclusive).
+    pub until: String,
+
+    /// Opening intervals during the exception. Empty if closed.
+    pub hours: Vec<OpeningHours>,
+
+    /// Description as found on the page (e.g. "Weihnachtsferien").
+    pub note: String,
+}
+
+/// Opening times of a single mensa.
+#[derive(Clone, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct OpeningTimes {
+    /// Layout version of the document, bumped on breaking changes (see `SCHEMA_VERSION`)
+    #[serde(default)]
+    pub schema_version: SchemaVersion,
+    /// Regular opening times by weekday.
+    pub regular: Vec<DayHours>,
+
+    /// Holiday exceptions (only if they could be parsed).
+    #[serde(default)]
+    pub exceptions: Vec<TimesException>,
+}
+
+impl OpeningTimes {
+    /// Regular opening intervals for the given weekday
+    pub fn on(&self, weekday: Weekday) -> &[OpeningHours] {
+        self.regular
+            .iter()
+            .find(|d| d.weekday == weekday)
+            .map(|d| d.hours.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Opening intervals on `date`: those of an exception covering it, the regular ones for
+    /// its weekday otherwise
+    #[cfg(feature = "parse")]
+    pub fn hours_on(&self, date: chrono::NaiveDate) -> &[OpeningHours] {
+        use chrono::Datelike;
+
+        let iso = date.format("%Y-%m-%d").to_string();
+        match self
+            .exceptions
+            .iter()
+            .find(|e| e.from.as_str() <= iso.as_str() && iso.as_str() <= e.until.as_str())
+        {
+            Some(exception) => &exception.hours,
+            None => self.on(Weekday::ALL[date.weekday().num_days_from_monday() as usize]),
+        }
+    }
+
+    /// Whether the mensa is open at `at` (local time, see `hours_on`)
+    #[cfg(feature = "parse")]
+    pub fn is_open(&self, at: chrono::NaiveDateTime) -> bool {
+        let time = at.format("%H:%M").to_string();
+        self.hours_on(at.date())
+            .iter()
+            .any(|hours| hours.open <= time && time < hours.close)
+    }
+
+    #[cfg(feature = "json-schema")]
+    pub fn schema() -> anyhow::Result<String> {
+        let schema = schema_for!(OpeningTimes);
+        Ok(serde_json::to_string_pretty(&schema)?)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    fn on_weekday() {
+        let times = OpeningTimes {
+            regular: vec![DayHours {
+                weekday: Weekday::Monday,
+                hours: vec![OpeningHours {
+                    open: "11:30".into(),
+                    close: "14:30".into(),
+                }],
+            }],
+            ..Default::default()
+        };
+
+        assert_eq!(times.on(Weekday::Monday)[0].open, "11:30");
+        assert!(times.on(Weekday::Sunday).is_empty());
+    }
+
+    #[cfg(feature = "parse")]
+    #[test]
+    fn open_at() {
+        use chrono::NaiveDateTime;
+
+        let hours = |open: &str, close: &str| OpeningHours {
+            open: open.into(),
+            close: close.into(),
+        };
+        let times = OpeningTimes {
+            regular: vec![DayHours {
+                weekday: Weekday::Friday,
+                hours: vec![hours("11:30", "14:30")],
+            }],
+            exceptions: vec![TimesException {
+                from: "2026-12-21".into(),
+                until: "2027-01-01".into(),
+                hours: vec![],
+                note: "Weihnachtsferien".into(),
+            }],
+            ..Default::default()
+        };
+        let at = |s: &str| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap();
+
+        assert!(times.is_open(at("2026-10-16 11:30")));
+        assert!(!times.is_open(at("2026-10-16 14:30")));
+        assert!(!times.is_open(at("2026-10-17 12:00")));
+        // Exceptions take precedence over the regular times
+        assert!(!times.is_open(at("2027-01-01 12:00")));
+        assert!(times.hours_on(at("2027-01-01 12:00").date()).is_empty());
+    }
+
+    #[test]
+    fn weekday_names() {
+        assert_eq!(Weekday::from_name("Mo"), Some(Weekday::Monday));
+        assert_eq!(Weekday::from_name("Di."), Some(Weekday::Tuesday));
+        assert_eq!(Weekday::from_name("thu"), Some(Weekday::Thursday));
+        assert_eq!(Weekday::from_name("Sonntag"), Some(Weekday::Sunday));
+        assert_eq!(Weekday::from_name("Mittagessen"), None);
+        assert_eq!(Weekday::from_name("M"), None);
+    }
+
+    #[test]
+    fn weekday_ranges() {
+        assert_eq!(Weekday::Monday.through(Weekday::Wednesday).len(), 3);
+        assert_eq!(Weekday::Friday.through(Weekday::Friday), [Weekday::Friday]);
+        assert_eq!(
+            Weekday::Saturday.through(Weekday::Monday),
+            [Weekday::Saturday, Weekday::Sunday, Weekday::Monday]
+        );
+    }
+}